@@ -83,7 +83,7 @@ impl PyMOFile {
 
     #[getter]
     fn version(&self) -> PyResult<u32> {
-        Ok(self.0.version.unwrap_or(0))
+        Ok(self.0.revision().into())
     }
 
     fn get_entries(&self) -> PyResult<Vec<PyMOEntry>> {
@@ -143,18 +143,21 @@ impl PyMOFile {
     }
 
     fn save(&self, path: &str) -> PyResult<()> {
-        self.0.save(path);
-        Ok(())
+        self.0.save(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn save_as_pofile(&self, path: &str) -> PyResult<()> {
-        self.0.save_as_pofile(path);
-        Ok(())
+        self.0.save_as_pofile(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn save_as_mofile(&self, path: &str) -> PyResult<()> {
-        self.0.save_as_mofile(path);
-        Ok(())
+        self.0.save_as_mofile(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn metadata_as_entry(&self) -> PyResult<PyMOEntry> {
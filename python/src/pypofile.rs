@@ -74,7 +74,7 @@ impl PyPOFile {
 
     #[setter]
     fn set_entries(&mut self, entries: Vec<PyPOEntry>) {
-        self.0.entries =
+        *self.0.entries_mut() =
             entries.into_iter().map(|e| e._inner()).collect();
     }
 
@@ -88,6 +88,16 @@ impl PyPOFile {
         self.0.header = header;
     }
 
+    #[getter]
+    fn trailing_comments(&self) -> PyResult<Option<String>> {
+        Ok(self.0.trailing_comments.clone())
+    }
+
+    #[setter]
+    fn set_trailing_comments(&mut self, comments: Option<String>) {
+        self.0.trailing_comments = comments;
+    }
+
     fn get_metadata(&self) -> PyResult<HashMap<String, String>> {
         Ok(self.0.metadata.clone())
     }
@@ -138,18 +148,21 @@ impl PyPOFile {
     }
 
     fn save(&self, path: &str) -> PyResult<()> {
-        self.0.save(path);
-        Ok(())
+        self.0.save(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn save_as_pofile(&self, path: &str) -> PyResult<()> {
-        self.0.save_as_pofile(path);
-        Ok(())
+        self.0.save_as_pofile(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn save_as_mofile(&self, path: &str) -> PyResult<()> {
-        self.0.save_as_mofile(path);
-        Ok(())
+        self.0.save_as_mofile(path).map_err(|e| {
+            PyErr::new::<exceptions::IOError, _>(e.to_string())
+        })
     }
 
     fn remove(&mut self, entry: &PyPOEntry) -> PyResult<()> {
@@ -172,8 +185,9 @@ impl PyPOFile {
     }
 
     fn append(&mut self, entry: &PyPOEntry) -> PyResult<()> {
-        self.0.entries.push(entry._inner());
-        Ok(())
+        self.0.append(entry._inner()).map_err(|e| {
+            PyErr::new::<exceptions::SyntaxError, _>(e.to_string())
+        })
     }
 
     #[pyo3(signature=(value, by="msgid", include_obsolete_entries=false, msgctxt=None))]
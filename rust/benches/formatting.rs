@@ -11,6 +11,21 @@ fn mofile_to_string(file: &MOFile) {
     file.to_string();
 }
 
+/// Returns a large file made of copies of the entries of
+/// `django-complete.po`, with distinct messages
+fn large_pofile(copies: usize) -> POFile {
+    let mut file = pofile("tests-data/django-complete.po").unwrap();
+    let entries = file.entries.clone();
+    for copy in 1..copies {
+        for entry in &entries {
+            let mut entry = entry.clone();
+            entry.msgid = format!("{} {}", entry.msgid, copy);
+            file.entries.push(entry);
+        }
+    }
+    file
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "POFile('django-complete.po').to_string()",
@@ -22,6 +37,11 @@ fn criterion_benchmark(c: &mut Criterion) {
             })
         },
     );
+    let large_file = large_pofile(50);
+    c.bench_function(
+        "POFile(50 x 'django-complete.po').to_string()",
+        |b| b.iter(|| pofile_to_string(black_box(&large_file))),
+    );
     c.bench_function("MOFile('all.mo').to_string()", |b| {
         b.iter(|| {
             mofile_to_string(black_box(
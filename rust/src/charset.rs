@@ -0,0 +1,125 @@
+//! Detection of the encoding of PO and MO files
+//!
+//! The encoding of a file is declared in the `charset`
+//! parameter of its `Content-Type` header, like
+//! `Content-Type: text/plain; charset=ISO-8859-1`. Labels are
+//! resolved following the WHATWG Encoding Standard, so
+//! `ISO-8859-1` is decoded as `windows-1252`, a superset of it.
+
+use std::borrow::Cow;
+
+use encoding_rs::{Encoding, UTF_8};
+
+/// Returns the encoding declared in the `charset` parameter
+/// found in `content`, which can be a raw header or the raw
+/// lines of a PO file
+///
+/// Defaults to UTF-8 if no charset is declared or the label
+/// is unknown, like the `CHARSET` placeholder of templates.
+pub(crate) fn encoding_from_header(
+    content: &[u8],
+) -> &'static Encoding {
+    const NEEDLE: &[u8] = b"charset=";
+    let Some(start) = content
+        .windows(NEEDLE.len())
+        .position(|window| window.eq_ignore_ascii_case(NEEDLE))
+    else {
+        return UTF_8;
+    };
+    let label = &content[start + NEEDLE.len()..];
+    let end = label
+        .iter()
+        .position(|b| {
+            !(b.is_ascii_alphanumeric() || b"-_.:".contains(b))
+        })
+        .unwrap_or(label.len());
+    Encoding::for_label_no_replacement(&label[..end]).unwrap_or(UTF_8)
+}
+
+/// Returns the `Content-Type` header `value` declaring UTF-8
+/// as its charset
+///
+/// Files are always written as UTF-8, so a charset resolved
+/// to another encoding is replaced to keep the written content
+/// consistent with its header. Unknown labels, like the
+/// `CHARSET` placeholder of templates, are kept.
+pub(crate) fn utf8_content_type(value: &str) -> Cow<'_, str> {
+    const NEEDLE: &str = "charset=";
+    if encoding_from_header(value.as_bytes()) == UTF_8 {
+        return Cow::Borrowed(value);
+    }
+    let start = value
+        .to_ascii_lowercase()
+        .find(NEEDLE)
+        .map(|index| index + NEEDLE.len())
+        .unwrap();
+    let end = value[start..]
+        .find(|c: char| {
+            !(c.is_ascii_alphanumeric() || "-_.:".contains(c))
+        })
+        .map_or(value.len(), |index| start + index);
+    Cow::Owned(format!("{}UTF-8{}", &value[..start], &value[end..]))
+}
+
+/// Decodes `bytes` using `encoding`, replacing malformed
+/// sequences with the replacement character
+pub(crate) fn decode<'a>(
+    bytes: &'a [u8],
+    encoding: &'static Encoding,
+) -> Cow<'a, str> {
+    encoding.decode_without_bom_handling(bytes).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::WINDOWS_1252;
+
+    #[test]
+    fn detect_encoding() {
+        assert_eq!(
+            encoding_from_header(
+                b"Content-Type: text/plain; charset=ISO-8859-1\\n"
+            ),
+            WINDOWS_1252,
+        );
+        assert_eq!(
+            encoding_from_header(
+                b"Content-Type: text/plain; charset=utf-8"
+            ),
+            UTF_8,
+        );
+        assert_eq!(
+            encoding_from_header(
+                b"Content-Type: text/plain; charset=CHARSET"
+            ),
+            UTF_8,
+        );
+        assert_eq!(encoding_from_header(b"Language: es"), UTF_8);
+    }
+
+    #[test]
+    fn decode_latin1() {
+        assert_eq!(decode(b"Espa\xf1a", WINDOWS_1252), "España");
+    }
+
+    #[test]
+    fn utf8_content_type_replaces_charset() {
+        assert_eq!(
+            utf8_content_type("text/plain; charset=ISO-8859-1"),
+            "text/plain; charset=UTF-8",
+        );
+        assert_eq!(
+            utf8_content_type("text/plain; Charset=latin1; x=y"),
+            "text/plain; Charset=UTF-8; x=y",
+        );
+        assert_eq!(
+            utf8_content_type("text/plain; charset=CHARSET"),
+            "text/plain; charset=CHARSET",
+        );
+        assert_eq!(
+            utf8_content_type("text/plain; charset=utf-8"),
+            "text/plain; charset=utf-8",
+        );
+    }
+}
@@ -1,17 +1,86 @@
 use std::borrow::Cow;
 use std::fmt;
 
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::escaping::escape;
-use crate::twrapper::wrap;
+use crate::twrapper::{wrap, wrap_hard_newlines};
 
 pub mod moentry;
 pub mod poentry;
 
-pub use moentry::MOEntry;
+pub use moentry::{MOEntry, MOEntryKey};
 pub use poentry::POEntry;
 
+/// Style used to format strings that contain newlines
+///
+/// ```rust
+/// use rspolib::{MultilineStyle, POEntry};
+///
+/// let entry = POEntry::from("First line\nSecond line");
+/// assert_eq!(
+///     entry.to_string_with_multiline_style(
+///         78,
+///         MultilineStyle::PreserveLines,
+///     ),
+///     concat!(
+///         "msgid \"\"\n",
+///         "\"First line\\n\"\n",
+///         "\"Second line\"\n",
+///         "msgstr \"\"\n",
+///     ),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultilineStyle {
+    /// Strings are wrapped at the wrap width, like any other.
+    #[default]
+    Wrap,
+    /// Each line of the string is placed in its own quoted
+    /// line and it is never wrapped. Strings without newlines
+    /// are wrapped as usual.
+    PreserveLines,
+    /// Newlines are hard breaks, so each line of the string
+    /// starts a new quoted line, and lines longer than the wrap
+    /// width are wrapped, like `msgcat` does. Strings whose only
+    /// newline is the last character are wrapped as usual.
+    WrapLines,
+}
+
+/// Style used to format the occurrences (`#:` lines) of entries
+///
+/// ```rust
+/// use rspolib::{FileOptions, OccurrencesStyle, POEntry};
+///
+/// let mut entry = POEntry::from("Hello");
+/// entry.add_occurrence("src/main.rs", "10");
+/// entry.add_occurrence("src/lib.rs", "5");
+///
+/// let options = FileOptions {
+///     occurrences_style: OccurrencesStyle::OnePerLine,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     entry.to_string_with_options(&options),
+///     concat!(
+///         "#: src/main.rs:10\n",
+///         "#: src/lib.rs:5\n",
+///         "msgid \"Hello\"\n",
+///         "msgstr \"\"\n",
+///     ),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OccurrencesStyle {
+    /// Occurrences are separated by spaces and wrapped at
+    /// the wrap width.
+    #[default]
+    Wrap,
+    /// Each occurrence is placed in its own `#:` line.
+    OnePerLine,
+}
+
 /// Provides a function `translated` to represent
 /// if an entry struct is translated
 pub trait Translated {
@@ -23,8 +92,8 @@ pub trait Translated {
 /// The MO files spec indicates:
 ///
 /// > Contexts are stored (in MO files) by storing
-/// the concatenation of the context, a EOT byte,
-/// and the original string.
+/// > the concatenation of the context, a EOT byte,
+/// > and the original string.
 ///
 /// This trait provides a way to get the string
 /// representation of `msgid` + `EOT` + `msgctxt`.
@@ -52,13 +121,41 @@ pub(crate) fn maybe_msgid_msgctxt_eot_split<'a>(
     }
 }
 
+/// Returns a one line `msgid -> msgstr` preview of an entry
+///
+/// The strings are escaped, so newlines don't break the line,
+/// and the preview is truncated to `max_len` grapheme
+/// clusters, ending with an ellipsis if it doesn't fit.
+pub(crate) fn preview(
+    msgid: &str,
+    msgstr: &str,
+    max_len: usize,
+) -> String {
+    let preview = format!("{} -> {}", escape(msgid), escape(msgstr));
+    let starts = preview
+        .grapheme_indices(true)
+        .map(|(start, _)| start)
+        .collect::<Vec<_>>();
+    if starts.len() <= max_len {
+        return preview;
+    } else if max_len == 0 {
+        return String::new();
+    }
+    // `max_len - 1` grapheme clusters and the ellipsis
+    format!("{}…", &preview[..starts[max_len - 1]])
+}
+
 fn metadata_msgstr_formatter(
     msgstr: &str,
     _: &str,
     _: usize,
+    _: MultilineStyle,
 ) -> String {
     let mut ret = String::from("msgstr \"\"\n");
     for line in msgstr.lines() {
+        if line.is_empty() {
+            continue;
+        }
         ret.push('"');
         ret.push_str(&escape(line));
         ret.push_str(r"\n");
@@ -68,10 +165,14 @@ fn metadata_msgstr_formatter(
     ret
 }
 
+type MsgstrFormatter =
+    dyn Fn(&str, &str, usize, MultilineStyle) -> String;
+
 fn default_mo_entry_msgstr_formatter(
     msgstr: &str,
     delflag: &str,
     wrapwidth: usize,
+    multiline_style: MultilineStyle,
 ) -> String {
     POStringField::new(
         "msgstr",
@@ -80,6 +181,7 @@ fn default_mo_entry_msgstr_formatter(
         "",
         wrapwidth,
     )
+    .multiline_style(multiline_style)
     .to_string()
 }
 
@@ -87,7 +189,8 @@ fn mo_entry_to_string_with_msgstr_formatter(
     entry: &MOEntry,
     wrapwidth: usize,
     delflag: &str,
-    msgstr_formatter: &dyn Fn(&str, &str, usize) -> String,
+    multiline_style: MultilineStyle,
+    msgstr_formatter: &MsgstrFormatter,
 ) -> String {
     let mut ret = String::new();
 
@@ -96,6 +199,7 @@ fn mo_entry_to_string_with_msgstr_formatter(
             &POStringField::new(
                 "msgctxt", delflag, msgctxt, "", wrapwidth,
             )
+            .multiline_style(multiline_style)
             .to_string(),
         );
     }
@@ -108,6 +212,7 @@ fn mo_entry_to_string_with_msgstr_formatter(
             "",
             wrapwidth,
         )
+        .multiline_style(multiline_style)
         .to_string(),
     );
 
@@ -120,6 +225,7 @@ fn mo_entry_to_string_with_msgstr_formatter(
                 "",
                 wrapwidth,
             )
+            .multiline_style(multiline_style)
             .to_string(),
         );
     }
@@ -129,13 +235,15 @@ fn mo_entry_to_string_with_msgstr_formatter(
             Some(msgstr) => msgstr,
             None => "",
         };
-        let formatted_msgstr =
-            msgstr_formatter(msgstr, delflag, wrapwidth);
+        let formatted_msgstr = msgstr_formatter(
+            msgstr,
+            delflag,
+            wrapwidth,
+            multiline_style,
+        );
         ret.push_str(&formatted_msgstr);
     } else {
-        for (i, msgstr_plural) in
-            entry.msgstr_plural.iter().enumerate()
-        {
+        for (i, msgstr_plural) in entry.plural_msgstrs() {
             ret.push_str(
                 &POStringField::new(
                     "msgstr",
@@ -144,6 +252,7 @@ fn mo_entry_to_string_with_msgstr_formatter(
                     &i.to_string(),
                     wrapwidth,
                 )
+                .multiline_style(multiline_style)
                 .to_string(),
             );
         }
@@ -156,11 +265,13 @@ pub(crate) fn mo_entry_to_string(
     entry: &MOEntry,
     wrapwidth: usize,
     delflag: &str,
+    multiline_style: MultilineStyle,
 ) -> String {
     mo_entry_to_string_with_msgstr_formatter(
         entry,
         wrapwidth,
         delflag,
+        multiline_style,
         &default_mo_entry_msgstr_formatter,
     )
 }
@@ -185,6 +296,7 @@ pub fn mo_metadata_entry_to_string(entry: &MOEntry) -> String {
         entry,
         78,
         "",
+        MultilineStyle::Wrap,
         &metadata_msgstr_formatter,
     )
 }
@@ -224,6 +336,7 @@ pub(crate) struct POStringField<'a> {
     value: &'a str,
     plural_index: &'a str,
     wrapwidth: usize,
+    multiline_style: MultilineStyle,
 }
 
 impl<'a> POStringField<'a> {
@@ -240,8 +353,17 @@ impl<'a> POStringField<'a> {
             value,
             plural_index,
             wrapwidth,
+            multiline_style: MultilineStyle::Wrap,
         }
     }
+
+    pub fn multiline_style(
+        mut self,
+        multiline_style: MultilineStyle,
+    ) -> Self {
+        self.multiline_style = multiline_style;
+        self
+    }
 }
 
 impl<'a> fmt::Display for POStringField<'a> {
@@ -259,7 +381,25 @@ impl<'a> fmt::Display for POStringField<'a> {
             UnicodeWidthStr::width(escaped_value.as_ref())
                 + UnicodeWidthStr::width(self.fieldname)
                 + 1;
-        if real_width > self.wrapwidth {
+        if self.multiline_style == MultilineStyle::PreserveLines
+            && self.value.contains('\n')
+        {
+            lines.extend(
+                self.value
+                    .split_inclusive('\n')
+                    .map(|line| escape(line).into_owned()),
+            );
+        } else if self.wrapwidth == 0 {
+            // a wrap width of 0 disables wrapping
+            lines = vec![escaped_value.into_owned()];
+        } else if self.multiline_style == MultilineStyle::WrapLines
+            && self.value.trim_end_matches('\n').contains('\n')
+        {
+            lines.extend(wrap_hard_newlines(
+                &escaped_value,
+                self.wrapwidth,
+            ));
+        } else if real_width > self.wrapwidth {
             let new_lines = wrap(&escaped_value, self.wrapwidth);
             lines.extend(new_lines);
         } else {
@@ -1,9 +1,11 @@
 use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use crate::entry::{
-    maybe_msgid_msgctxt_eot_split, mo_entry_to_string,
-    EntryCmpByOptions, MsgidEotMsgctxt, POEntry, Translated,
+    maybe_msgid_msgctxt_eot_split, mo_entry_to_string, preview,
+    EntryCmpByOptions, MsgidEotMsgctxt, MultilineStyle, POEntry,
+    Translated,
 };
 use crate::traits::Merge;
 
@@ -23,7 +25,15 @@ use crate::traits::Merge;
 /// - If `msgid_plural` is not `None`, then the entry
 ///   is a translation of a plural form contained in
 ///   `msgstr_plural`.
-#[derive(Default, Clone, Debug, PartialEq)]
+///
+/// Entries are equal, and hashed, by all their fields. To
+/// compare the messages of two catalogs regardless of their
+/// translations, use their identities as [MOEntryKey]s.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct MOEntry {
     /// untranslated string
     pub msgid: String,
@@ -54,12 +64,44 @@ impl MOEntry {
         }
     }
 
+    /// Returns a one line preview of the entry, like
+    /// `msgid -> msgstr`, for logging purposes
+    ///
+    /// See [POEntry::preview](crate::POEntry::preview).
+    pub fn preview(&self, max_len: usize) -> String {
+        let msgstr = match self.msgstr_plural.first() {
+            Some(msgstr) if self.is_plural() => msgstr,
+            _ => self.msgstr.as_deref().unwrap_or(""),
+        };
+        preview(&self.msgid, msgstr, max_len)
+    }
+
+    /// Returns `true` if the entry has a `msgid_plural`
+    pub fn is_plural(&self) -> bool {
+        self.msgid_plural.is_some()
+    }
+
+    /// Returns the number of plural forms of the translation
+    pub fn plural_count(&self) -> usize {
+        self.msgstr_plural.len()
+    }
+
+    /// Returns the translations of the plural forms along
+    /// with the index of their form, ordered by index
+    pub fn plural_msgstrs(&self) -> Vec<(usize, &str)> {
+        self.msgstr_plural
+            .iter()
+            .map(String::as_str)
+            .enumerate()
+            .collect()
+    }
+
     /// Convert to a string representation with a given wrap width
     pub fn to_string_with_wrapwidth(
         &self,
         wrapwidth: usize,
     ) -> String {
-        mo_entry_to_string(self, wrapwidth, "")
+        mo_entry_to_string(self, wrapwidth, "", MultilineStyle::Wrap)
     }
 
     /// Compare the current entry with other entry
@@ -158,6 +200,67 @@ impl MsgidEotMsgctxt for MOEntry {
     }
 }
 
+/// Identity of a [MOEntry], its `msgctxt` and `msgid`
+///
+/// Keys are equal and hash together when their entries are
+/// the same message, whatever their translations, so sets of
+/// keys can be used to compare the messages of two catalogs:
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use rspolib::{mofile, MOEntryKey};
+///
+/// let old = mofile("tests-data/all.mo").unwrap();
+/// let mut new = old.clone();
+/// let removed_entry = new.entries.remove(1);
+/// new.entries[0].msgstr = Some("Changed".to_string());
+///
+/// let old_keys: HashSet<MOEntryKey> =
+///     old.entries.iter().map(MOEntryKey::from).collect();
+/// let new_keys: HashSet<MOEntryKey> =
+///     new.entries.iter().map(MOEntryKey::from).collect();
+/// let removed = old_keys
+///     .difference(&new_keys)
+///     .map(|key| key.entry())
+///     .collect::<Vec<_>>();
+/// assert_eq!(removed, vec![&removed_entry]);
+/// assert_eq!(
+///     old_keys.intersection(&new_keys).count(),
+///     new.entries.len(),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MOEntryKey<'a>(&'a MOEntry);
+
+impl<'a> MOEntryKey<'a> {
+    /// Returns the entry identified by the key
+    pub fn entry(&self) -> &'a MOEntry {
+        self.0
+    }
+}
+
+impl<'a> From<&'a MOEntry> for MOEntryKey<'a> {
+    fn from(entry: &'a MOEntry) -> Self {
+        Self(entry)
+    }
+}
+
+impl PartialEq for MOEntryKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.msgctxt == other.0.msgctxt
+            && self.0.msgid == other.0.msgid
+    }
+}
+
+impl Eq for MOEntryKey<'_> {}
+
+impl Hash for MOEntryKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.msgctxt.hash(state);
+        self.0.msgid.hash(state);
+    }
+}
+
 impl Translated for MOEntry {
     /// Returns `true` if the entry is translated
     ///
@@ -223,6 +326,23 @@ impl From<&POEntry> for MOEntry {
     }
 }
 
+impl From<POEntry> for MOEntry {
+    /// Generates a [MOEntry] from a [POEntry], consuming it
+    ///
+    /// The strings of the entry are moved instead of cloned,
+    /// losing the rest of its information as the conversion
+    /// from a reference to a [POEntry] does.
+    fn from(entry: POEntry) -> Self {
+        MOEntry {
+            msgid: entry.msgid,
+            msgstr: entry.msgstr,
+            msgid_plural: entry.msgid_plural,
+            msgstr_plural: entry.msgstr_plural,
+            msgctxt: entry.msgctxt,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,6 +364,59 @@ mod tests {
         assert_eq!(moentry.msgctxt, None);
     }
 
+    #[test]
+    fn key() {
+        use std::collections::HashSet;
+
+        let entry = MOEntry::from("msgid");
+        let mut translated = entry.clone();
+        translated.msgstr = Some("msgstr".to_string());
+        let mut with_context = entry.clone();
+        with_context.msgctxt = Some("context".to_string());
+
+        // same identity, different translations
+        assert_ne!(entry, translated);
+        assert_eq!(MOEntryKey::from(&entry), (&translated).into());
+        assert_ne!(MOEntryKey::from(&entry), (&with_context).into());
+
+        let set_a = HashSet::from([
+            MOEntryKey::from(&translated),
+            MOEntryKey::from(&with_context),
+        ]);
+        let set_b = HashSet::from([MOEntryKey::from(&entry)]);
+        let difference = set_a.difference(&set_b).collect::<Vec<_>>();
+        assert_eq!(difference.len(), 1);
+        assert_eq!(difference[0].entry(), &with_context);
+        assert_eq!(set_a.intersection(&set_b).count(), 1);
+    }
+
+    #[test]
+    fn plural_msgstrs() {
+        let msgstr_plural = (0..12)
+            .map(|i| format!("form {}", i))
+            .collect::<Vec<String>>();
+        let moentry = MOEntry::new(
+            "msgid".to_string(),
+            None,
+            Some("msgid_plural".to_string()),
+            msgstr_plural,
+            None,
+        );
+
+        let plural_msgstrs = moentry.plural_msgstrs();
+        assert_eq!(plural_msgstrs.len(), 12);
+        assert_eq!(plural_msgstrs[2], (2, "form 2"));
+        assert_eq!(plural_msgstrs[10], (10, "form 10"));
+        for (i, (index, msgstr)) in plural_msgstrs.iter().enumerate()
+        {
+            assert_eq!(*index, i);
+            assert_eq!(*msgstr, format!("form {}", i));
+        }
+
+        let moentry = MOEntry::from("msgid");
+        assert!(moentry.plural_msgstrs().is_empty());
+    }
+
     #[test]
     fn moentry_translated() {
         // empty msgstr means untranslated
@@ -254,7 +427,7 @@ mod tests {
             vec![],
             None,
         );
-        assert_eq!(moentry.translated(), false);
+        assert!(!moentry.translated());
 
         let moentry = MOEntry::new(
             "msgid".to_string(),
@@ -263,7 +436,7 @@ mod tests {
             vec![],
             None,
         );
-        assert_eq!(moentry.translated(), true);
+        assert!(moentry.translated());
 
         // empty msgstr_plural means untranslated
         let moentry = MOEntry::new(
@@ -273,7 +446,7 @@ mod tests {
             vec![],
             None,
         );
-        assert_eq!(moentry.translated(), false);
+        assert!(!moentry.translated());
 
         // empty msgstr in msgstr_plural means untranslated
         let moentry = MOEntry::new(
@@ -283,7 +456,7 @@ mod tests {
             vec!["".to_string()],
             None,
         );
-        assert_eq!(moentry.translated(), false);
+        assert!(!moentry.translated());
     }
 
     #[test]
@@ -380,5 +553,24 @@ msgstr "msgstr"
         );
         assert_eq!(moentry.msgstr_plural, msgstr_plural);
         assert_eq!(moentry.msgctxt, Some("msgctxt".to_string()));
+
+        assert_eq!(MOEntry::from(poentry), moentry);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let moentry = MOEntry::new(
+            "file".to_string(),
+            None,
+            Some("files".to_string()),
+            vec!["fichero".to_string(), "ficheros".to_string()],
+            Some("noun".to_string()),
+        );
+
+        let json = serde_json::to_string(&moentry).unwrap();
+        let deserialized: MOEntry =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, moentry);
     }
 }
@@ -1,17 +1,21 @@
 use std::cmp::Ordering;
 use std::fmt;
 
+use natord::compare as compare_natural_order;
+
 use unicode_width::UnicodeWidthStr;
 
 use crate::entry::{
-    maybe_msgid_msgctxt_eot_split, mo_entry_to_string,
-    EntryCmpByOptions, MOEntry, MsgidEotMsgctxt, POStringField,
-    Translated,
+    maybe_msgid_msgctxt_eot_split, mo_entry_to_string, preview,
+    EntryCmpByOptions, MOEntry, MsgidEotMsgctxt, MultilineStyle,
+    OccurrencesStyle, POStringField, Translated,
 };
-use crate::errors::EscapingError;
+use crate::errors::{EscapingError, FormatError};
 use crate::escaping::unescape;
+use crate::file::FileOptions;
 use crate::traits::Merge;
 use crate::twrapper::wrap;
+use crate::validation;
 
 /// PO file entry representing a message
 ///
@@ -35,6 +39,10 @@ use crate::twrapper::wrap;
 /// The `previous_msgctxt` field is used to store the previous
 /// `msgctxt` value when the entry is obsolete.
 #[derive(Default, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct POEntry {
     /// untranslated string
     pub msgid: String,
@@ -87,6 +95,406 @@ impl POEntry {
         self.flags.contains(&"fuzzy".to_string())
     }
 
+    /// Returns `true` if the entry has the `no-wrap` flag, so
+    /// its strings are not wrapped when formatted
+    pub fn no_wrap(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "no-wrap")
+    }
+
+    /// Adds or removes the `fuzzy` flag of the entry
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from("msgid");
+    /// entry.set_fuzzy(true);
+    /// assert!(entry.fuzzy());
+    /// entry.set_fuzzy(false);
+    /// assert!(!entry.fuzzy());
+    /// ```
+    pub fn set_fuzzy(&mut self, value: bool) {
+        match value {
+            true => self.add_flag("fuzzy"),
+            false => {
+                self.remove_flag("fuzzy");
+            }
+        }
+    }
+
+    /// Adds a flag to the entry if it doesn't have it yet
+    pub fn add_flag(&mut self, flag: &str) {
+        if !self.flags.iter().any(|f| f == flag) {
+            self.flags.push(flag.to_string());
+        }
+    }
+
+    /// Removes a flag from the entry
+    ///
+    /// Returns `true` if the entry had the flag.
+    pub fn remove_flag(&mut self, flag: &str) -> bool {
+        let len = self.flags.len();
+        self.flags.retain(|f| f != flag);
+        self.flags.len() != len
+    }
+
+    /// Adds the format flag of the placeholders of the message
+    ///
+    /// Like the automatic detection of `xgettext`, the
+    /// `msgid` and `msgid_plural` are scanned for `printf`
+    /// specifiers (`%s`, `%1$d`...), Python mapping keys
+    /// (`%(name)s`) and brace fields (`{}`, `{name}`) to add
+    /// the `c-format`, `python-format` or
+    /// `python-brace-format` flag, so [POEntry::check_format]
+    /// can check the translations.
+    ///
+    /// The detection is conservative: nothing is added when
+    /// the entry already has a `*-format` flag, including the
+    /// negated ones like `no-c-format`, or when the syntax of
+    /// the messages is ambiguous.
+    ///
+    /// Returns the added flag, if any.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from("%(name)s has %(count)d files");
+    /// assert_eq!(entry.detect_format_flags(), Some("python-format"));
+    /// assert_eq!(entry.flags, vec!["python-format"]);
+    ///
+    /// let mut entry = POEntry::from("100% sure");
+    /// assert_eq!(entry.detect_format_flags(), None);
+    /// assert!(entry.flags.is_empty());
+    /// ```
+    pub fn detect_format_flags(&mut self) -> Option<&'static str> {
+        if self.flags.iter().any(|flag| flag.ends_with("-format")) {
+            return None;
+        }
+        let syntax = validation::FormatSyntax::detect(&self.msgid);
+        let plural_syntax = self
+            .msgid_plural
+            .as_deref()
+            .and_then(validation::FormatSyntax::detect);
+        let syntax = match (syntax, plural_syntax) {
+            (Some(a), Some(b)) if a != b => return None,
+            (syntax, plural_syntax) => syntax.or(plural_syntax)?,
+        };
+        self.add_flag(syntax.flag());
+        Some(syntax.flag())
+    }
+
+    /// Returns a one line preview of the entry, like
+    /// `msgid -> msgstr`, for logging purposes
+    ///
+    /// The preview is truncated to `max_len` grapheme clusters
+    /// with an ellipsis. The first plural form is used as the
+    /// translation of plural entries.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let entry = POEntry::from(("Café", "Cafetería"));
+    /// assert_eq!(entry.preview(50), "Café -> Cafetería");
+    /// assert_eq!(entry.preview(10), "Café -> C…");
+    /// ```
+    pub fn preview(&self, max_len: usize) -> String {
+        let msgstr = match self.msgstr_plural.first() {
+            Some(msgstr) if self.is_plural() => msgstr,
+            _ => self.msgstr.as_deref().unwrap_or(""),
+        };
+        preview(&self.msgid, msgstr, max_len)
+    }
+
+    /// Returns `true` if the entry has a `msgid_plural`
+    pub fn is_plural(&self) -> bool {
+        self.msgid_plural.is_some()
+    }
+
+    /// Returns the number of plural forms of the translation
+    pub fn plural_count(&self) -> usize {
+        self.msgstr_plural.len()
+    }
+
+    /// Returns `true` if both entries contain the same message
+    ///
+    /// Compares `msgid`, `msgctxt`, `msgstr`, the plural
+    /// fields, whether they are obsolete and the flags,
+    /// regardless of their order. The line numbers,
+    /// occurrences, comments and previous fields are ignored,
+    /// so entries parsed from different positions of a file
+    /// compare equal.
+    pub fn semantic_eq(&self, other: &POEntry) -> bool {
+        let same_flags = self.flags.len() == other.flags.len()
+            && self
+                .flags
+                .iter()
+                .all(|flag| other.flags.contains(flag))
+            && other
+                .flags
+                .iter()
+                .all(|flag| self.flags.contains(flag));
+        self.msgid == other.msgid
+            && self.msgctxt == other.msgctxt
+            && self.msgstr == other.msgstr
+            && self.msgid_plural == other.msgid_plural
+            && self.msgstr_plural == other.msgstr_plural
+            && self.obsolete == other.obsolete
+            && same_flags
+    }
+
+    /// Makes the entry plural
+    ///
+    /// Sets `msgid_plural` and the translations of the plural
+    /// forms, ordered by their index. The singular `msgstr` is
+    /// removed, as plural entries are translated only by
+    /// `msgstr_plural`.
+    pub fn set_plural(&mut self, msgid_plural: &str, forms: &[&str]) {
+        self.msgid_plural = Some(msgid_plural.to_string());
+        self.msgstr_plural =
+            forms.iter().map(|form| form.to_string()).collect();
+        self.msgstr = None;
+    }
+
+    /// Returns the translations of the plural forms along
+    /// with the index of their form, ordered by index
+    pub fn plural_msgstrs(&self) -> Vec<(usize, &str)> {
+        self.msgstr_plural
+            .iter()
+            .map(String::as_str)
+            .enumerate()
+            .collect()
+    }
+
+    /// Adds an occurrence to the entry
+    ///
+    /// The occurrence is not added if the entry already
+    /// contains it.
+    pub fn add_occurrence(&mut self, path: &str, linenum: &str) {
+        if !self
+            .occurrences
+            .iter()
+            .any(|(p, l)| p == path && l == linenum)
+        {
+            self.occurrences
+                .push((path.to_string(), linenum.to_string()));
+        }
+    }
+
+    /// Adds an occurrence to the entry given its line number
+    ///
+    /// The file path is trimmed and the line number, if any,
+    /// converted to the string stored in [POEntry::occurrences],
+    /// so `None` is stored as an empty line number. As with
+    /// [POEntry::add_occurrence], the occurrence is not added
+    /// if the entry already contains it.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from("Hello");
+    /// entry.add_occurrence_at("src/main.rs", Some(10));
+    /// entry.add_occurrence_at(" src/main.rs ", Some(10));
+    /// entry.add_occurrence_at("README.md", None);
+    /// assert_eq!(entry.occurrences_line(), "src/main.rs:10 README.md");
+    /// ```
+    pub fn add_occurrence_at(
+        &mut self,
+        file: &str,
+        line: Option<usize>,
+    ) {
+        let linenum = line.map(|l| l.to_string()).unwrap_or_default();
+        self.add_occurrence(file.trim(), &linenum);
+    }
+
+    /// Merges the comments, occurrences and flags of another
+    /// entry into this one without losing the current ones
+    ///
+    /// Unlike [Merge::merge], which replaces them:
+    ///
+    /// - The occurrences and flags of `other` not found in
+    ///   this entry are appended, so the `fuzzy` flag is kept
+    ///   if any of the entries has it.
+    /// - The translator and generated comments are only
+    ///   replaced if the ones of `other` are not empty.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from(("Hello", "Hola"));
+    /// entry.tcomment = Some("Informal greeting".to_string());
+    /// entry.add_occurrence("src/old.rs", "1");
+    /// entry.flags.push("fuzzy".to_string());
+    ///
+    /// let mut template_entry = POEntry::from("Hello");
+    /// template_entry.add_occurrence("src/new.rs", "2");
+    /// template_entry.flags.push("python-format".to_string());
+    ///
+    /// entry.merge_comments(&template_entry);
+    /// assert_eq!(entry.tcomment.as_deref(), Some("Informal greeting"));
+    /// assert_eq!(entry.occurrences_line(), "src/old.rs:1 src/new.rs:2");
+    /// assert_eq!(entry.flags, vec!["fuzzy", "python-format"]);
+    /// ```
+    pub fn merge_comments(&mut self, other: &POEntry) {
+        for (path, linenum) in &other.occurrences {
+            self.add_occurrence(path, linenum);
+        }
+        for flag in &other.flags {
+            if !self.flags.contains(flag) {
+                self.flags.push(flag.clone());
+            }
+        }
+        let non_empty = |comment: &Option<String>| {
+            comment.as_ref().filter(|c| !c.is_empty()).cloned()
+        };
+        if let Some(tcomment) = non_empty(&other.tcomment) {
+            self.tcomment = Some(tcomment);
+        }
+        if let Some(comment) = non_empty(&other.comment) {
+            self.comment = Some(comment);
+        }
+    }
+
+    /// Checks the format specifiers of the translations
+    ///
+    /// When the entry has a `c-format`, `python-format` or
+    /// `python-brace-format` flag, the specifiers of each
+    /// translation are compared with the ones of the msgid,
+    /// allowing them to be reordered. Untranslated strings and
+    /// entries without format flags are not checked.
+    ///
+    /// In plural entries, the specifiers of the translations
+    /// must be defined by `msgid` or `msgid_plural`, but only
+    /// the ones included in both messages are required, so
+    /// forms used for a single number can omit it.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from((
+    ///     "%(name)s has %(count)d files",
+    ///     "%(count)d archivos tiene %(name)s",
+    /// ));
+    /// entry.flags.push("python-format".to_string());
+    /// assert!(entry.check_format().is_ok());
+    ///
+    /// entry.msgstr = Some("%(nombre)s tiene archivos".to_string());
+    /// assert!(entry.check_format().is_err());
+    /// ```
+    pub fn check_format(&self) -> Result<(), FormatError> {
+        validation::check_format(self)
+    }
+
+    /// Removes the duplicated occurrences
+    ///
+    /// The first appearance of each `(file, line)` pair is
+    /// kept, so the order of the occurrences is preserved.
+    pub fn dedup_occurrences(&mut self) {
+        let mut seen = Vec::with_capacity(self.occurrences.len());
+        self.occurrences.retain(|occurrence| {
+            if seen.contains(occurrence) {
+                return false;
+            }
+            seen.push(occurrence.clone());
+            true
+        });
+    }
+
+    /// Sorts the occurrences by file and line number
+    ///
+    /// Files are compared using natural order and line numbers
+    /// numerically, placing first the occurrences without
+    /// line number.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from("Hello");
+    /// entry.add_occurrence("src/main.rs", "10");
+    /// entry.add_occurrence("src/file10.rs", "1");
+    /// entry.add_occurrence("src/main.rs", "9");
+    /// entry.add_occurrence("src/file2.rs", "");
+    /// entry.sort_occurrences();
+    /// assert_eq!(
+    ///     entry.occurrences_line(),
+    ///     "src/file2.rs src/file10.rs:1 src/main.rs:9 src/main.rs:10",
+    /// );
+    /// ```
+    pub fn sort_occurrences(&mut self) {
+        self.occurrences.sort_by(
+            |(file_a, line_a), (file_b, line_b)| {
+                compare_natural_order(file_a, file_b).then_with(
+                    || compare_natural_order(line_a, line_b),
+                )
+            },
+        );
+    }
+
+    /// Adds an extracted comment to the entry
+    ///
+    /// The lines of `text` are appended to the generated
+    /// comment, separated by newlines, excluding those that
+    /// are already included in it. Useful to accumulate the
+    /// comments found for the same message in different
+    /// places of the source code.
+    pub fn add_extracted_comment(&mut self, text: &str) {
+        let mut comment = self.comment.take().unwrap_or_default();
+        for line in text.lines() {
+            if comment.lines().any(|l| l == line) {
+                continue;
+            }
+            if !comment.is_empty() {
+                comment.push('\n');
+            }
+            comment.push_str(line);
+        }
+        self.comment = match comment.is_empty() {
+            true => None,
+            false => Some(comment),
+        };
+    }
+
+    /// Removes the generated and translator comments
+    pub fn strip_comments(&mut self) {
+        self.comment = None;
+        self.tcomment = None;
+    }
+
+    /// Removes the occurrences
+    pub fn strip_occurrences(&mut self) {
+        self.occurrences.clear();
+    }
+
+    /// Returns the occurrences separated by spaces, as they
+    /// are written in `#:` lines without wrapping
+    ///
+    /// Occurrences are written as `path:line`, or just as
+    /// `path` when they have no line number. Parsing the
+    /// line reconstructs the same occurrences as long as the
+    /// paths don't contain whitespaces nor colons followed by
+    /// line numbers.
+    ///
+    /// ```rust
+    /// use rspolib::POEntry;
+    ///
+    /// let mut entry = POEntry::from("Hello");
+    /// entry.add_occurrence("src/main.rs", "10");
+    /// entry.add_occurrence("README.md", "");
+    /// assert_eq!(entry.occurrences_line(), "src/main.rs:10 README.md");
+    /// ```
+    pub fn occurrences_line(&self) -> String {
+        self.formatted_occurrences().collect::<Vec<_>>().join(" ")
+    }
+
+    fn formatted_occurrences(
+        &self,
+    ) -> impl Iterator<Item = String> + '_ {
+        self.occurrences.iter().map(|(fpath, lineno)| {
+            if lineno.is_empty() {
+                return fpath.clone();
+            }
+            format!("{}:{}", fpath, lineno)
+        })
+    }
+
     fn format_comment_inplace(
         &self,
         comment: &str,
@@ -95,7 +503,9 @@ impl POEntry {
         target: &mut String,
     ) {
         for line in comment.lines() {
-            if UnicodeWidthStr::width(line) + 2 > wrapwidth {
+            if wrapwidth > 0
+                && UnicodeWidthStr::width(line) + 2 > wrapwidth
+            {
                 target.push_str(&wrap(line, wrapwidth - 2).join("\n"))
             } else {
                 target.push_str(prefix);
@@ -106,9 +516,55 @@ impl POEntry {
     }
 
     /// Convert to string with a given wrap width
+    ///
+    /// The strings of entries with the `no-wrap` flag are not
+    /// wrapped, whatever the width.
     pub fn to_string_with_wrapwidth(
         &self,
         wrapwidth: usize,
+    ) -> String {
+        self.to_string_with_multiline_style(
+            wrapwidth,
+            MultilineStyle::Wrap,
+        )
+    }
+
+    /// Convert to string with a given wrap width and style
+    /// for strings that contain newlines
+    ///
+    /// See [MultilineStyle].
+    pub fn to_string_with_multiline_style(
+        &self,
+        wrapwidth: usize,
+        multiline_style: MultilineStyle,
+    ) -> String {
+        self.format(
+            wrapwidth,
+            multiline_style,
+            OccurrencesStyle::Wrap,
+        )
+    }
+
+    /// Convert to string using the formatting options of a file
+    ///
+    /// The wrap width, [MultilineStyle] and [OccurrencesStyle]
+    /// are taken from `options`.
+    pub fn to_string_with_options(
+        &self,
+        options: &FileOptions,
+    ) -> String {
+        self.format(
+            options.wrapwidth,
+            options.multiline_style,
+            options.occurrences_style,
+        )
+    }
+
+    pub(crate) fn format(
+        &self,
+        wrapwidth: usize,
+        multiline_style: MultilineStyle,
+        occurrences_style: OccurrencesStyle,
     ) -> String {
         let mut ret = String::new();
 
@@ -127,17 +583,18 @@ impl POEntry {
         }
 
         // occurrences
-        if !self.obsolete && !self.occurrences.is_empty() {
-            let whitespace_sep_occurrences = self
-                .occurrences
-                .iter()
-                .map(|(fpath, lineno)| {
-                    if lineno.is_empty() {
-                        return fpath.clone();
-                    }
-                    format!("{}:{}", fpath, lineno)
-                })
-                .collect::<Vec<String>>();
+        if !self.obsolete
+            && !self.occurrences.is_empty()
+            && occurrences_style == OccurrencesStyle::OnePerLine
+        {
+            for occ in self.formatted_occurrences() {
+                ret.push_str("#: ");
+                ret.push_str(&occ);
+                ret.push('\n');
+            }
+        } else if !self.obsolete && !self.occurrences.is_empty() {
+            let whitespace_sep_occurrences =
+                self.formatted_occurrences().collect::<Vec<String>>();
 
             let mut files_repr: Vec<String> = vec![];
 
@@ -146,12 +603,16 @@ impl POEntry {
             for occ in &whitespace_sep_occurrences {
                 let occ_width = UnicodeWidthStr::width(occ.as_str());
                 let width = current_width + occ_width + 1;
-                if width > wrapwidth && !current_line_occs.is_empty()
+                if wrapwidth > 0
+                    && width > wrapwidth
+                    && !current_line_occs.is_empty()
                 {
                     let curr_line =
                         format!("#: {}", current_line_occs.join(" "));
                     files_repr.push(curr_line);
-                    current_width = occ_width + 2;
+                    current_line_occs.clear();
+                    current_line_occs.push(occ);
+                    current_width = occ_width + 3;
                 } else {
                     current_line_occs.push(occ);
                     current_width += occ_width + 1;
@@ -171,6 +632,12 @@ impl POEntry {
             ret.push_str(&format!("#, {}\n", self.flags.join(", ")));
         }
 
+        // the strings of `no-wrap` entries are written in one line
+        let strings_wrapwidth = match self.no_wrap() {
+            true => 0,
+            false => wrapwidth,
+        };
+
         // previous context and previous msgid/msgid_plural
         let mut prefix = String::from("#");
         if self.obsolete {
@@ -185,8 +652,9 @@ impl POEntry {
                     &prefix,
                     previous_msgctxt,
                     "",
-                    wrapwidth,
+                    strings_wrapwidth,
                 )
+                .multiline_style(multiline_style)
                 .to_string(),
             );
         }
@@ -198,8 +666,9 @@ impl POEntry {
                     &prefix,
                     previous_msgid,
                     "",
-                    wrapwidth,
+                    strings_wrapwidth,
                 )
+                .multiline_style(multiline_style)
                 .to_string(),
             );
         }
@@ -209,24 +678,25 @@ impl POEntry {
         {
             ret.push_str(
                 &POStringField::new(
-                    "msgid",
+                    "msgid_plural",
                     &prefix,
                     previous_msgid_plural,
                     "",
-                    wrapwidth,
+                    strings_wrapwidth,
                 )
+                .multiline_style(multiline_style)
                 .to_string(),
             );
-            ret.push('\n');
         }
 
         ret.push_str(&mo_entry_to_string(
             &MOEntry::from(self),
-            wrapwidth,
+            strings_wrapwidth,
             match self.obsolete {
                 true => "#~ ",
                 false => "",
             },
+            multiline_style,
         ));
         ret
     }
@@ -502,11 +972,39 @@ mod tests {
     #[test]
     fn fuzzy() {
         let non_fuzzy_entry = POEntry::new(0);
-        assert_eq!(non_fuzzy_entry.fuzzy(), false);
+        assert!(!non_fuzzy_entry.fuzzy());
 
         let mut fuzzy_entry = POEntry::new(0);
         fuzzy_entry.flags.push("fuzzy".to_string());
-        assert_eq!(fuzzy_entry.fuzzy(), true);
+        assert!(fuzzy_entry.fuzzy());
+    }
+
+    #[test]
+    fn semantic_eq() {
+        let mut entry = POEntry::from(("msgid 1", "msgstr 1"));
+        entry.flags =
+            vec!["fuzzy".to_string(), "c-format".to_string()];
+        entry.linenum = 3;
+
+        let mut other = entry.clone();
+        other.linenum = 10;
+        other
+            .occurrences
+            .push(("src/main.rs".to_string(), "5".to_string()));
+        other.flags.reverse();
+        assert_ne!(entry, other);
+        assert!(entry.semantic_eq(&other));
+
+        other.flags.pop();
+        assert!(!entry.semantic_eq(&other));
+
+        let mut other = entry.clone();
+        other.msgctxt = Some("context".to_string());
+        assert!(!entry.semantic_eq(&other));
+
+        let mut other = entry.clone();
+        other.obsolete = true;
+        assert!(!entry.semantic_eq(&other));
     }
 
     #[test]
@@ -514,43 +1012,187 @@ mod tests {
         // obsolete means untranslated
         let mut obsolete_entry = POEntry::new(0);
         obsolete_entry.obsolete = true;
-        assert_eq!(obsolete_entry.translated(), false);
+        assert!(!obsolete_entry.translated());
 
         // fuzzy means untranslated
         let mut fuzzy_entry = POEntry::new(0);
         fuzzy_entry.flags.push("fuzzy".to_string());
-        assert_eq!(fuzzy_entry.translated(), false);
+        assert!(!fuzzy_entry.translated());
 
         // no msgstr means untranslated
         let no_msgstr_entry = POEntry::new(0);
-        assert_eq!(no_msgstr_entry.translated(), false);
+        assert!(!no_msgstr_entry.translated());
 
         // empty msgstr means untranslated
         let mut empty_msgstr_entry = POEntry::new(0);
         empty_msgstr_entry.msgstr = Some("".to_string());
-        assert_eq!(empty_msgstr_entry.translated(), false);
+        assert!(!empty_msgstr_entry.translated());
 
         // with msgstr means translated
         let mut translated_entry = POEntry::new(0);
         translated_entry.msgstr = Some("msgstr".to_string());
-        assert_eq!(translated_entry.translated(), true);
+        assert!(translated_entry.translated());
 
         // empty msgstr_plural means untranslated
         let mut empty_msgstr_plural_entry = POEntry::new(0);
         empty_msgstr_plural_entry.msgstr_plural = vec![];
-        assert_eq!(empty_msgstr_plural_entry.translated(), false);
+        assert!(!empty_msgstr_plural_entry.translated());
 
         // with empty msgstr_plural means untranslated
         let mut empty_msgstr_plural_entry = POEntry::new(0);
         empty_msgstr_plural_entry.msgstr_plural =
             vec!["".to_string()];
-        assert_eq!(empty_msgstr_plural_entry.translated(), false);
+        assert!(!empty_msgstr_plural_entry.translated());
 
         // with msgstr_plural means translated
         let mut translated_plural_entry = POEntry::new(0);
         translated_plural_entry.msgstr_plural =
             vec!["msgstr_plural".to_string()];
-        assert_eq!(translated_plural_entry.translated(), true);
+        assert!(translated_plural_entry.translated());
+    }
+
+    #[test]
+    fn set_plural() {
+        let mut entry = POEntry::from(("file", "plik"));
+        entry.set_plural("files", &["plik", "pliki", "plików"]);
+
+        assert_eq!(entry.msgid_plural.as_deref(), Some("files"));
+        assert_eq!(entry.msgstr, None);
+        assert_eq!(
+            entry.to_string(),
+            concat!(
+                "msgid \"file\"\n",
+                "msgid_plural \"files\"\n",
+                "msgstr[0] \"plik\"\n",
+                "msgstr[1] \"pliki\"\n",
+                "msgstr[2] \"plików\"\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn plural_msgstrs() {
+        let content = "msgid \"file\"
+msgid_plural \"files\"
+msgstr[0] \"fichero\"
+msgstr[1] \"ficheros\"
+msgstr[2] \"ficheros (2)\"
+";
+        let file = crate::pofile(content).unwrap();
+        let entry = &file.entries[0];
+
+        assert_eq!(
+            entry.plural_msgstrs(),
+            vec![
+                (0, "fichero"),
+                (1, "ficheros"),
+                (2, "ficheros (2)")
+            ],
+        );
+        assert!(POEntry::from("msgid").plural_msgstrs().is_empty());
+    }
+
+    #[test]
+    fn dedup_and_sort_occurrences() {
+        let mut entry = POEntry::from("Save");
+        for (path, linenum) in [
+            ("src/menu.rs", "10"),
+            ("src/dialog.rs", "25"),
+            ("src/menu.rs", "10"),
+            ("src/menu.rs", "9"),
+            ("src/menu.rs", ""),
+            ("src/dialog.rs", "25"),
+        ] {
+            entry.occurrences.push((path.into(), linenum.into()));
+        }
+
+        entry.dedup_occurrences();
+        assert_eq!(
+            entry.occurrences_line(),
+            "src/menu.rs:10 src/dialog.rs:25 src/menu.rs:9 src/menu.rs",
+        );
+
+        entry.sort_occurrences();
+        assert_eq!(
+            entry.occurrences_line(),
+            "src/dialog.rs:25 src/menu.rs src/menu.rs:9 src/menu.rs:10",
+        );
+    }
+
+    #[test]
+    fn add_occurrence_at() {
+        let mut entry = POEntry::from("Save");
+        entry.add_occurrence("src/menu.rs", "10");
+        entry.add_occurrence_at("src/menu.rs", Some(10));
+        entry.add_occurrence_at("\tsrc/dialog.rs", Some(25));
+        entry.add_occurrence_at("src/menu.rs", None);
+        entry.add_occurrence_at("src/menu.rs", None);
+
+        assert_eq!(
+            entry.occurrences,
+            vec![
+                ("src/menu.rs".to_string(), "10".to_string()),
+                ("src/dialog.rs".to_string(), "25".to_string()),
+                ("src/menu.rs".to_string(), "".to_string()),
+            ],
+        );
+    }
+
+    #[test]
+    fn add_extracted_comment() {
+        let mut entry = POEntry::from("Save");
+
+        // recorded from two places of the source code
+        entry.add_occurrence("src/menu.rs", "10");
+        entry.add_extracted_comment("Button of the menu");
+        entry.add_occurrence("src/dialog.rs", "25");
+        entry.add_extracted_comment("Button of the dialog");
+        // recorded again from the first place
+        entry.add_occurrence("src/menu.rs", "10");
+        entry.add_extracted_comment("Button of the menu");
+
+        assert_eq!(entry.occurrences.len(), 2);
+        assert_eq!(
+            entry.comment.as_ref().unwrap(),
+            "Button of the menu\nButton of the dialog",
+        );
+        assert_eq!(
+            entry.to_string(),
+            "#. Button of the menu
+#. Button of the dialog
+#: src/menu.rs:10 src/dialog.rs:25
+msgid \"Save\"
+msgstr \"\"
+",
+        );
+
+        let mut entry = POEntry::from("Save");
+        entry.add_extracted_comment("");
+        assert_eq!(entry.comment, None);
+    }
+
+    #[test]
+    fn strip_comments_and_occurrences() {
+        let mut entry = POEntry::from(("msgid", "msgstr"));
+        entry.comment = Some("extracted comment".to_string());
+        entry.tcomment = Some("translator comment".to_string());
+        entry.occurrences =
+            vec![("src/main.rs".to_string(), "42".to_string())];
+
+        entry.strip_comments();
+        assert_eq!(entry.comment, None);
+        assert_eq!(entry.tcomment, None);
+        assert_eq!(
+            entry.to_string(),
+            "#: src/main.rs:42\nmsgid \"msgid\"\nmsgstr \"msgstr\"\n",
+        );
+
+        entry.strip_occurrences();
+        assert!(entry.occurrences.is_empty());
+        assert_eq!(
+            entry.to_string(),
+            "msgid \"msgid\"\nmsgstr \"msgstr\"\n",
+        );
     }
 
     #[test]
@@ -767,6 +1409,183 @@ mod tests {
             "msgstr[1] \"plural 2\"\n"
         );
         assert_eq!(entry.to_string(), expected);
+
+        // previous msgid_plural
+        entry.previous_msgid_plural =
+            Some("A previous msgid_plural".to_string());
+        let expected = concat!(
+            "# translator comment\n#. comment\n",
+            "#: file1.rs:1 file2.rs:2\n",
+            "#, fuzzy, python-format, rspolib\n",
+            "#| msgctxt \"A previous msgctxt\"\n",
+            "#| msgid \"A previous msgid\"\n",
+            "#| msgid_plural \"A previous msgid_plural\"\n",
+            "msgctxt \"msgctxt\"\n",
+            "msgid \"msgid\"\n",
+            "msgid_plural \"msgid_plural\"\n",
+            "msgstr[0] \"plural 1\"\n",
+            "msgstr[1] \"plural 2\"\n"
+        );
+        assert_eq!(entry.to_string(), expected);
+    }
+
+    #[test]
+    fn occurrences_style() {
+        let mut entry = POEntry::from("msgid");
+        for (path, linenum) in [
+            ("src/first/module.rs", "10"),
+            ("src/second/module.rs", "20"),
+            ("src/third/module.rs", "30"),
+            ("README.md", ""),
+        ] {
+            entry.add_occurrence(path, linenum);
+        }
+
+        let options = FileOptions {
+            wrapwidth: 50,
+            occurrences_style: OccurrencesStyle::OnePerLine,
+            ..Default::default()
+        };
+        assert_eq!(
+            entry.to_string_with_options(&options),
+            concat!(
+                "#: src/first/module.rs:10\n",
+                "#: src/second/module.rs:20\n",
+                "#: src/third/module.rs:30\n",
+                "#: README.md\n",
+                "msgid \"msgid\"\n",
+                "msgstr \"\"\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn wrapped_occurrences_start_new_line() {
+        let mut entry = POEntry::from("msgid");
+        for (path, linenum) in [
+            ("src/first/module.rs", "10"),
+            ("src/second/module.rs", "20"),
+            ("src/third/module.rs", "30"),
+            ("README.md", ""),
+        ] {
+            entry.add_occurrence(path, linenum);
+        }
+
+        // the occurrence that doesn't fit starts the next line,
+        // instead of being dropped and repeating the first ones
+        assert_eq!(
+            entry.to_string_with_wrapwidth(50),
+            concat!(
+                "#: src/first/module.rs:10 src/second/module.rs:20\n",
+                "#: src/third/module.rs:30 README.md\n",
+                "msgid \"msgid\"\n",
+                "msgstr \"\"\n",
+            ),
+        );
+        assert_eq!(
+            entry.to_string_with_wrapwidth(30),
+            concat!(
+                "#: src/first/module.rs:10\n",
+                "#: src/second/module.rs:20\n",
+                "#: src/third/module.rs:30\n",
+                "#: README.md\n",
+                "msgid \"msgid\"\n",
+                "msgstr \"\"\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn occurrences_line_round_trip() {
+        // xorshift generator, seeded to be reproducible
+        let mut seed: u64 = 0x2545f4914f6cdd1d;
+        let mut next = |max: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % max) as usize
+        };
+        let path_chars: Vec<char> =
+            "abcXYZ019_-./\\,<>@ñ".chars().collect();
+
+        for _ in 0..500 {
+            let mut entry = POEntry::from("msgid");
+            for _ in 0..next(6) {
+                let mut path = (0..next(20) + 1)
+                    .map(
+                        |_| path_chars[next(path_chars.len() as u64)],
+                    )
+                    .collect::<String>();
+                path.push_str(".rs");
+                let linenum = match next(3) {
+                    0 => String::new(),
+                    _ => next(100_000).to_string(),
+                };
+                entry.occurrences.push((path, linenum));
+            }
+            // paths whose colons are not followed by line numbers
+            if next(2) == 0 {
+                entry.occurrences.push((
+                    "Balloon>>addFillStyleMenuItems:hand:from:"
+                        .to_string(),
+                    String::new(),
+                ));
+            }
+
+            let line = entry.occurrences_line();
+            assert_eq!(
+                crate::poparser::parse_occurrences(&line),
+                entry.occurrences,
+                "{}",
+                line,
+            );
+        }
+    }
+
+    #[test]
+    fn multiline_style() {
+        let mut entry = POEntry::from((
+            "First line\nSecond line, a bit longer than the first one\n",
+            "Primera línea\nSegunda línea",
+        ));
+
+        assert_eq!(
+            entry.to_string_with_multiline_style(
+                40,
+                MultilineStyle::Wrap
+            ),
+            concat!(
+                "msgid \"\"\n",
+                "\"First line\\nSecond line, a bit longer \"\n",
+                "\"than the first one\\n\"\n",
+                "msgstr \"Primera línea\\nSegunda línea\"\n",
+            ),
+        );
+        assert_eq!(
+            entry.to_string_with_multiline_style(
+                40,
+                MultilineStyle::PreserveLines
+            ),
+            concat!(
+                "msgid \"\"\n",
+                "\"First line\\n\"\n",
+                "\"Second line, a bit longer than the first one\\n\"\n",
+                "msgstr \"\"\n",
+                "\"Primera línea\\n\"\n",
+                "\"Segunda línea\"\n",
+            ),
+        );
+
+        // strings without newlines are wrapped as usual
+        entry.msgid = "A msgid without newlines".to_string();
+        entry.msgstr = Some("A msgstr without newlines".to_string());
+        assert_eq!(
+            entry.to_string_with_multiline_style(
+                20,
+                MultilineStyle::PreserveLines
+            ),
+            entry.to_string_with_wrapwidth(20),
+        );
     }
 
     #[test]
@@ -826,6 +1645,69 @@ mod tests {
         assert_eq!(entry.to_string(), expected);
     }
 
+    #[test]
+    fn multiline_format_wrap_lines() {
+        let mut entry = POEntry::new(0);
+        let format = |entry: &POEntry| {
+            entry.to_string_with_multiline_style(
+                78,
+                MultilineStyle::WrapLines,
+            )
+        };
+
+        // newlines are hard breaks
+        entry.msgid = concat!(
+            "A long long long long\nlong long long long\n",
+            "long long long\nlong long long long lo\nng long",
+            " msgid",
+        )
+        .to_string();
+        let expected = concat!(
+            "msgid \"\"\n",
+            "\"A long long long long\\n\"\n",
+            "\"long long long long\\n\"\n",
+            "\"long long long\\n\"\n",
+            "\"long long long long lo\\n\"\n",
+            "\"ng long msgid\"\n",
+            "msgstr \"\"\n"
+        );
+        assert_eq!(format(&entry), expected);
+
+        // long lines are wrapped between the newlines
+        entry.msgid = concat!(
+            "First line\n",
+            "A long long long long long long long long long",
+            " long long long long long long long long long line\n",
+            "Last line\n",
+        )
+        .to_string();
+        let expected = concat!(
+            "msgid \"\"\n",
+            "\"First line\\n\"\n",
+            "\"A long long long long long long long long long",
+            " long long long long long long \"\n",
+            "\"long long long line\\n\"\n",
+            "\"Last line\\n\"\n",
+            "msgstr \"\"\n"
+        );
+        assert_eq!(format(&entry), expected);
+
+        // a single trailing newline is not a hard break
+        entry.msgid = "Hello world\n".to_string();
+        assert_eq!(
+            format(&entry),
+            "msgid \"Hello world\\n\"\nmsgstr \"\"\n"
+        );
+
+        // strings without newlines are wrapped as usual
+        entry.msgid = concat!(
+            "A long long long long long long long long long long",
+            " long long long long long long long long msgid",
+        )
+        .to_string();
+        assert_eq!(format(&entry), entry.to_string());
+    }
+
     #[test]
     fn format_escapes() {
         let mut entry = POEntry::new(0);
@@ -1116,4 +1998,165 @@ mod tests {
             Ordering::Equal,
         );
     }
+
+    #[test]
+    fn preview() {
+        let mut entry =
+            POEntry::from(("Hello\nworld", "Hola\nmundo"));
+        assert_eq!(
+            entry.preview(80),
+            "Hello\\nworld -> Hola\\nmundo"
+        );
+        assert_eq!(entry.preview(7), "Hello\\…");
+        assert_eq!(entry.preview(0), "");
+
+        // combined characters are not split
+        entry.msgid = "e\u{301}e\u{301}e\u{301}".to_string();
+        entry.msgstr = None;
+        assert_eq!(entry.preview(3), "e\u{301}e\u{301}…");
+        assert_eq!(entry.preview(7), "e\u{301}e\u{301}e\u{301} -> ");
+
+        entry.set_plural("days", &["días", "días"]);
+        assert!(entry.preview(80).ends_with(" -> días"));
+    }
+
+    #[test]
+    fn add_and_remove_flags() {
+        let mut entry = POEntry::from("msgid");
+
+        entry.add_flag("c-format");
+        entry.add_flag("c-format");
+        entry.set_fuzzy(true);
+        entry.set_fuzzy(true);
+        assert_eq!(entry.flags, vec!["c-format", "fuzzy"]);
+
+        assert!(entry.remove_flag("c-format"));
+        assert!(!entry.remove_flag("c-format"));
+        assert!(!entry.remove_flag("no-c-format"));
+        assert_eq!(entry.flags, vec!["fuzzy"]);
+
+        entry.set_fuzzy(false);
+        entry.set_fuzzy(false);
+        assert!(entry.flags.is_empty());
+    }
+
+    #[test]
+    fn detect_format_flags() {
+        let mut entry = POEntry::from("%d file");
+        entry.msgid_plural = Some("%d files".to_string());
+        assert_eq!(entry.detect_format_flags(), Some("c-format"));
+        assert_eq!(entry.detect_format_flags(), None);
+        assert_eq!(entry.flags, vec!["c-format"]);
+
+        // explicit format flags are respected
+        let mut entry = POEntry::from("%s files");
+        entry.add_flag("no-c-format");
+        assert_eq!(entry.detect_format_flags(), None);
+        assert_eq!(entry.flags, vec!["no-c-format"]);
+
+        // messages with different syntaxes are ambiguous
+        let mut entry = POEntry::from("{} file");
+        entry.msgid_plural = Some("%d files".to_string());
+        assert_eq!(entry.detect_format_flags(), None);
+
+        // one of the messages can have no placeholders
+        let mut entry = POEntry::from("One file");
+        entry.msgid_plural = Some("%(n)d files".to_string());
+        assert_eq!(
+            entry.detect_format_flags(),
+            Some("python-format")
+        );
+        let mut entry = POEntry::from("{name} has one file");
+        entry.msgid_plural = Some("{name} has files".to_string());
+        assert_eq!(
+            entry.detect_format_flags(),
+            Some("python-brace-format")
+        );
+    }
+
+    #[test]
+    fn is_plural() {
+        let mut entry = POEntry::from("file");
+        assert!(!entry.is_plural());
+        assert_eq!(entry.plural_count(), 0);
+
+        entry.set_plural("files", &["fichero", "ficheros"]);
+        assert!(entry.is_plural());
+        assert_eq!(entry.plural_count(), 2);
+    }
+
+    #[test]
+    fn to_string_with_wrapwidth_zero() {
+        let msgid = "A very long message that would be wrapped \
+                     at any positive wrap width because it is \
+                     longer than the default of 78 columns";
+        let mut entry = POEntry::from((msgid, "Traducido"));
+        entry.tcomment = Some(msgid.to_string());
+        entry.occurrences = (1..30)
+            .map(|i| ("src/module.rs".to_string(), i.to_string()))
+            .collect();
+
+        let expected = format!(
+            "# {msgid}\n#: {}\nmsgid \"{msgid}\"\n\
+             msgstr \"Traducido\"\n",
+            entry
+                .formatted_occurrences()
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        assert_eq!(entry.to_string_with_wrapwidth(0), expected);
+    }
+
+    #[test]
+    fn to_string_no_wrap_flag() {
+        let msgid = "A very long message that would be wrapped \
+                     at the default wrap width because it is \
+                     longer than 78 columns\n";
+        let msgstr = "Un mensaje muy largo\ncon saltos de línea";
+        let mut entry = POEntry::from((msgid, msgstr));
+        entry.tcomment = Some("Translator comment".to_string());
+        entry.previous_msgid = Some(msgid.to_string());
+        assert!(entry.to_string().contains("msgid \"\"\n"));
+
+        entry.flags.push("no-wrap".to_string());
+        assert!(entry.no_wrap());
+        let escaped = msgid.replace('\n', "\\n");
+        let expected = format!(
+            "# Translator comment\n#, no-wrap\n\
+             #| msgid \"{escaped}\"\nmsgid \"{escaped}\"\n\
+             msgstr \"Un mensaje muy largo\\ncon saltos de línea\"\n",
+        );
+        assert_eq!(entry.to_string(), expected);
+        assert_eq!(entry.to_string_with_wrapwidth(20), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut entry = POEntry::from(("file", "fichero"));
+        entry.msgctxt = Some("noun".to_string());
+        entry.set_plural("files", &["fichero", "ficheros"]);
+        entry.comment = Some("extracted comment".to_string());
+        entry.occurrences =
+            vec![("src/main.rs".to_string(), "42".to_string())];
+        entry.flags = vec!["python-format".to_string()];
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            json["occurrences"],
+            serde_json::json!([["src/main.rs", "42"]]),
+        );
+        assert_eq!(
+            json["flags"],
+            serde_json::json!(["python-format"])
+        );
+        assert_eq!(
+            json["msgstr_plural"],
+            serde_json::json!(["fichero", "ficheros"]),
+        );
+
+        let deserialized: POEntry =
+            serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, entry);
+    }
 }
@@ -169,10 +169,24 @@ pub enum IOError {
     /// a file that is not a mo file.
     #[snafu(display("Invalid mo file, malformed or corrupted data found when {context}"))]
     CorruptedMOData { context: String },
+
+    /// The content of the MO file could not be read.
+    #[snafu(display("Error reading mo file content: {message}"))]
+    ErrorReadingContent { message: String },
 }
 
 /// Syntax errors generated when the PO parser can't parse some content.
 ///
+/// The errors found in a line point to a position in it with:
+///
+/// - `line`: number of the line, starting at 1.
+/// - `column`: number of the character in the line, starting
+///   at 1.
+/// - `offset`: index of the byte in the line, starting at 0.
+///
+/// Errors affecting a whole line point to its first non
+/// whitespace character.
+///
 /// # Examples
 ///
 /// ## Unescaped double quote found
@@ -190,7 +204,8 @@ pub enum IOError {
 ///     Err(SyntaxError::UnescapedDoubleQuoteFound {
 ///         maybe_filename: MaybeFilename::new(content, false),
 ///         line: 3,
-///         index: 11,
+///         column: 11,
+///         offset: 10,
 ///     }),
 /// );
 /// ```
@@ -211,7 +226,8 @@ pub enum IOError {
 ///     Err(SyntaxError::Custom {
 ///         maybe_filename: MaybeFilename::new(content, false),
 ///         line: 2,
-///         index: 0,
+///         column: 4,
+///         offset: 3,
 ///         message: "unknown keyword previous_message".to_string(),
 ///     }),
 /// );
@@ -223,11 +239,12 @@ pub enum SyntaxError {
     /// to escape the double quote characters. It can also happen when you
     /// are reading a file that has been saved without escaping the double
     /// quotes in po string fields.
-    #[snafu(display("Syntax error found{maybe_filename} at line {line} (index {index}): unescaped double quote found"))]
+    #[snafu(display("Syntax error found{maybe_filename} at line {line}, column {column}: unescaped double quote found"))]
     UnescapedDoubleQuoteFound {
         maybe_filename: MaybeFilename,
         line: usize,
-        index: usize,
+        column: usize,
+        offset: usize,
     },
 
     /// A generic syntax error that includes a message about what was
@@ -236,15 +253,16 @@ pub enum SyntaxError {
     /// This happens when the parser finds a syntax error that is a expected
     /// syntax error, so it includes information about the error in the `message`
     /// field
-    #[snafu(display("Syntax error found{maybe_filename} at line {line} (index {index}): {message}"))]
+    #[snafu(display("Syntax error found{maybe_filename} at line {line}, column {column}: {message}"))]
     Custom {
         maybe_filename: MaybeFilename,
         line: usize,
-        index: usize,
+        column: usize,
+        offset: usize,
         message: String,
     },
 
-    /// A generic syntax error without information about the line or the index
+    /// A generic syntax error without information about the line or the column
     #[snafu(display(
         "Syntax error found{maybe_filename}: {message}"
     ))]
@@ -260,16 +278,60 @@ pub enum SyntaxError {
     ///
     /// It can happen when you are reading a file that has been saved
     /// with strange characters in field names like `msgid` or `msgstr`.
-    #[snafu(display("Syntax error found{maybe_filename} at line {line} (index {index})"))]
+    #[snafu(display("Syntax error found{maybe_filename} at line {line}, column {column}"))]
     Generic {
         maybe_filename: MaybeFilename,
         line: usize,
-        index: usize,
+        column: usize,
+        offset: usize,
+    },
+
+    /// An entry with the same `msgid` and `msgctxt` already
+    /// exists in the file
+    ///
+    /// Returned by `POFile::append` when the option
+    /// `check_for_duplicates` is enabled.
+    #[snafu(display(
+        "Duplicated entry found with msgid {msgid:?}{}",
+        msgctxt
+            .as_ref()
+            .map(|msgctxt| format!(" and msgctxt {msgctxt:?}"))
+            .unwrap_or_default()
+    ))]
+    DuplicatedEntry {
+        msgid: String,
+        msgctxt: Option<String>,
     },
 
     /// Unknown parsing state
     #[snafu(display("Unknown state {state}"))]
     UnknownState { state: String },
+
+    /// The path of a PO file doesn't exist
+    ///
+    /// Returned by `pofile` when the string passed looks like
+    /// a path instead of the content of a file, but no file
+    /// exists at that path.
+    #[snafu(display("File not found: {path}"))]
+    FileNotFound { path: String },
+
+    /// The path of a PO file exists but can't be opened
+    ///
+    /// Returned by `pofile` when opening the file fails, for
+    /// example because of its permissions.
+    #[snafu(display("Error opening file {path}: {message}"))]
+    ErrorOpeningFile { path: String, message: String },
+
+    /// A `Plural-Forms` header value can't be parsed
+    ///
+    /// Returned by `parse_plural_forms` when the `nplurals` or
+    /// `plural` fields are missing or malformed, or when the
+    /// `plural` expression returns indexes out of the range
+    /// defined by `nplurals`.
+    #[snafu(display(
+        "Invalid Plural-Forms header {header:?}: {message}"
+    ))]
+    InvalidPluralForms { header: String, message: String },
 }
 
 /// Escaping errors generated by escaping functions.
@@ -288,4 +350,92 @@ pub enum EscapingError {
         "invalid escaped character '{character}' found in '{text}'"
     ))]
     InvalidEscapedCharacter { text: String, character: char },
+
+    #[snafu(display(
+        "malformed escape sequence '{sequence}' found in '{text}'"
+    ))]
+    MalformedEscapeSequence { text: String, sequence: String },
+
+    #[snafu(display(
+        "escape sequences don't form valid UTF-8 characters in '{text}'"
+    ))]
+    InvalidUtf8Sequence { text: String },
+}
+
+/// Errors generated checking the format strings of entries.
+///
+/// ```rust
+/// use rspolib::{errors::FormatError, POEntry};
+///
+/// let mut entry = POEntry::from(("%s has %d files", "%s tiene archivos"));
+/// entry.flags.push("c-format".to_string());
+///
+/// assert_eq!(
+///     entry.check_format(),
+///     Err(FormatError::MismatchedSpecifiers {
+///         msgid: "%s has %d files".to_string(),
+///         msgstr: "%s tiene archivos".to_string(),
+///         missing: vec!["%d".to_string()],
+///         extra: vec![],
+///     }),
+/// );
+/// ```
+#[derive(Debug, PartialEq, Snafu)]
+pub enum FormatError {
+    /// The format specifiers of a translation don't match the
+    /// ones of the message
+    ///
+    /// `missing` contains the specifiers of the message not
+    /// found in the translation and `extra` the specifiers of
+    /// the translation not found in the message.
+    #[snafu(display(
+        "format specifiers of msgstr {msgstr:?} don't match msgid {msgid:?} (missing: {missing:?}, extra: {extra:?})"
+    ))]
+    MismatchedSpecifiers {
+        msgid: String,
+        msgstr: String,
+        missing: Vec<String>,
+        extra: Vec<String>,
+    },
+}
+
+/// Errors generated converting between file types.
+///
+/// ```rust
+/// use rspolib::{errors::ConversionError, MOFile, POEntry, POFile};
+///
+/// let mut entry = POEntry::from(("msgid", "msgstr"));
+/// entry.msgstr_plural = vec!["msgstr plural".to_string()];
+/// let file = POFile::from(vec![&entry]);
+///
+/// assert_eq!(
+///     MOFile::try_from_pofile(&file),
+///     Err(ConversionError::ConflictingPluralEntry {
+///         msgid: "msgid".to_string(),
+///     }),
+/// );
+/// ```
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ConversionError {
+    /// An entry defines both a singular translation and plural
+    /// translations, or plural translations without a
+    /// `msgid_plural`, so it can't be stored unambiguously in
+    /// a MO file.
+    #[snafu(display(
+        "conflicting singular and plural translations found for msgid '{msgid}'"
+    ))]
+    ConflictingPluralEntry { msgid: String },
+
+    /// A plural entry has a number of translations different
+    /// from the number of plural forms of the file, defined by
+    /// `nplurals` in the `Plural-Forms` header or, if it is not
+    /// defined, by the first plural entry of the file.
+    #[snafu(display(
+        "expected {expected} plural forms for msgid '{msgid}', found {found}"
+    ))]
+    InconsistentPluralForms {
+        msgid: String,
+        expected: usize,
+        found: usize,
+    },
 }
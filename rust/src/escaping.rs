@@ -2,6 +2,10 @@ use crate::errors::EscapingError;
 use std::borrow::Cow;
 
 /// Escape characters in a PO string field
+///
+/// The characters with a C escape sequence, like `\n` or
+/// `\a`, are escaped with it and the rest of ASCII control
+/// characters as octal sequences, like `\033`.
 pub fn escape(text: &str) -> Cow<'_, str> {
     let mut ret: String = String::with_capacity(text.len());
     for char in text.chars() {
@@ -10,59 +14,100 @@ pub fn escape(text: &str) -> Cow<'_, str> {
             '\n' => ret.push_str(r#"\n"#),
             '\r' => ret.push_str(r#"\r"#),
             '\t' => ret.push_str(r#"\t"#),
-            '\u{11}' => ret.push_str(r#"\v"#),
+            '\u{7}' => ret.push_str(r#"\a"#),
             '\u{8}' => ret.push_str(r#"\b"#),
-            '\u{12}' => ret.push_str(r#"\f"#),
+            '\u{b}' => ret.push_str(r#"\v"#),
+            '\u{c}' => ret.push_str(r#"\f"#),
             '\\' => ret.push_str(r#"\\"#),
+            c if c.is_ascii_control() => {
+                ret.push_str(&format!("\\{:03o}", c as u8));
+            }
             c => ret.push(c),
         }
     }
     ret.into()
 }
 
-struct EscapedStringInterpreter<'a> {
-    characters: std::str::Chars<'a>,
-}
+/// Unescape characters in a PO string field
+///
+/// Besides the escape sequences of the characters escaped by
+/// [escape], octal (`\NNN`) and hexadecimal (`\xHH`) sequences
+/// are decoded as bytes, so multibyte characters can be
+/// escaped as UTF-8 sequences, like `\303\251` for `é`.
+pub fn unescape(text: &str) -> Result<Cow<'_, str>, EscapingError> {
+    if !text.contains('\\') {
+        return Ok(text.into());
+    }
 
-impl<'a> Iterator for EscapedStringInterpreter<'a> {
-    type Item = Result<char, EscapingError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.characters.next().map(|c| match c {
-            '\\' => match self.characters.next() {
-                None => Err(EscapingError::EscapeAtEndOfString {
-                    text: self.characters.as_str().to_string(),
-                }),
-                Some('"') => Ok('"'),
-                Some('n') => Ok('\n'),
-                Some('r') => Ok('\r'),
-                Some('t') => Ok('\t'),
-                Some('b') => Ok('\u{8}'),
-                Some('v') => Ok('\u{11}'),
-                Some('f') => Ok('\u{12}'),
-                Some('\\') => Ok('\\'),
-                Some(c) => {
-                    Err(EscapingError::InvalidEscapedCharacter {
-                        text: self.characters.as_str().to_string(),
-                        character: c,
-                    })
+    let mut ret: Vec<u8> = Vec::with_capacity(text.len());
+    let mut characters = text.chars().peekable();
+    while let Some(c) = characters.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            ret.extend(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        let byte = match characters.next() {
+            None => {
+                return Err(EscapingError::EscapeAtEndOfString {
+                    text: text.to_string(),
+                })
+            }
+            Some('"') => b'"',
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('a') => 0x7,
+            Some('b') => 0x8,
+            Some('v') => 0xb,
+            Some('f') => 0xc,
+            Some('\\') => b'\\',
+            Some(c @ '0'..='7') => {
+                let mut sequence = c.to_string();
+                while sequence.len() < 3 {
+                    match characters.next_if(|c| c.is_digit(8)) {
+                        Some(c) => sequence.push(c),
+                        None => break,
+                    }
+                }
+                u8::from_str_radix(&sequence, 8).map_err(|_| {
+                    EscapingError::MalformedEscapeSequence {
+                        text: text.to_string(),
+                        sequence: format!("\\{sequence}"),
+                    }
+                })?
+            }
+            Some('x') => {
+                let mut sequence = String::new();
+                while sequence.len() < 2 {
+                    match characters.next_if(char::is_ascii_hexdigit)
+                    {
+                        Some(c) => sequence.push(c),
+                        None => break,
+                    }
                 }
-            },
-            c => Ok(c),
-        })
+                u8::from_str_radix(&sequence, 16).map_err(|_| {
+                    EscapingError::MalformedEscapeSequence {
+                        text: text.to_string(),
+                        sequence: format!("\\x{sequence}"),
+                    }
+                })?
+            }
+            Some(c) => {
+                return Err(EscapingError::InvalidEscapedCharacter {
+                    text: text.to_string(),
+                    character: c,
+                })
+            }
+        };
+        ret.push(byte);
     }
-}
 
-/// Unescape characters in a PO string field
-pub fn unescape(text: &str) -> Result<Cow<'_, str>, EscapingError> {
-    if text.contains('\\') {
-        (EscapedStringInterpreter {
-            characters: text.chars(),
-        })
-        .collect()
-    } else {
-        Ok(text.into())
-    }
+    String::from_utf8(ret).map(Cow::Owned).map_err(|_| {
+        EscapingError::InvalidUtf8Sequence {
+            text: text.to_string(),
+        }
+    })
 }
 
 #[cfg(test)]
@@ -120,6 +165,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn c_escapes_round_trip() -> Result<(), EscapingError> {
+        for (text, escaped) in [
+            ("\u{7}", r"\a"),
+            ("\u{8}", r"\b"),
+            ("\u{c}", r"\f"),
+            ("\u{b}", r"\v"),
+            ("\0", r"\000"),
+            ("\u{1b}[1m", r"\033[1m"),
+            ("\u{7f}", r"\177"),
+            ("é ñ", "é ñ"),
+        ] {
+            assert_eq!(escape(text), escaped);
+            assert_eq!(unescape(escaped)?, text);
+            assert_eq!(unescape(&escape(text))?, text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unescape_octal_and_hex() -> Result<(), EscapingError> {
+        assert_eq!(unescape(r"\0")?, "\0");
+        assert_eq!(unescape(r"\7")?, "\u{7}");
+        assert_eq!(unescape(r"\101\1012")?, "AA2");
+        assert_eq!(unescape(r"\x41\x4a\x414")?, "AJA4");
+        assert_eq!(unescape(r"\x9 ")?, "\t ");
+        // UTF-8 sequences
+        assert_eq!(unescape(r"caf\303\251")?, "café");
+        assert_eq!(unescape(r"caf\xc3\xa9")?, "café");
+        Ok(())
+    }
+
+    #[test]
+    fn unescape_malformed_sequences() {
+        assert_eq!(
+            unescape(r"foo \xZ"),
+            Err(EscapingError::MalformedEscapeSequence {
+                text: r"foo \xZ".to_string(),
+                sequence: r"\x".to_string(),
+            })
+        );
+        assert_eq!(
+            unescape(r"\400"),
+            Err(EscapingError::MalformedEscapeSequence {
+                text: r"\400".to_string(),
+                sequence: r"\400".to_string(),
+            })
+        );
+        assert_eq!(
+            unescape(r"caf\303"),
+            Err(EscapingError::InvalidUtf8Sequence {
+                text: r"caf\303".to_string(),
+            })
+        );
+        assert_eq!(
+            unescape(r"\q"),
+            Err(EscapingError::InvalidEscapedCharacter {
+                text: r"\q".to_string(),
+                character: 'q',
+            })
+        );
+        assert_eq!(
+            unescape("foo\\"),
+            Err(EscapingError::EscapeAtEndOfString {
+                text: "foo\\".to_string(),
+            })
+        );
+    }
+
     #[test]
     fn test_unescape_all() -> Result<(), EscapingError> {
         let (expected, escapes) = ESCAPES_EXPECTED;
@@ -5,11 +5,14 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 use natord::compare as compare_natural_order;
 
+use crate::charset;
+use crate::entry::{MultilineStyle, OccurrencesStyle};
+
 const METADATA_KEYS_ORDER: [&str; 11] = [
     "Project-Id-Version",
     "Report-Msgid-Bugs-To",
@@ -27,25 +30,25 @@ const METADATA_KEYS_ORDER: [&str; 11] = [
 /// Save file as a PO file with the `save_as_pofile` method
 pub trait SaveAsPOFile {
     /// Save the file as a PO file to the given path
-    fn save_as_pofile(&self, path: &str)
+    fn save_as_pofile(&self, path: &str) -> io::Result<()>
     where
         Self: fmt::Display,
     {
-        let mut file = File::create(path).unwrap();
-        file.write_all(self.to_string().as_bytes()).ok();
+        let mut file = File::create(path)?;
+        file.write_all(self.to_string().as_bytes())
     }
 }
 
 /// Save file with the `save` method
 pub trait Save {
     /// Save the file to the given path
-    fn save(&self, path: &str);
+    fn save(&self, path: &str) -> io::Result<()>;
 }
 
 /// Save file as a MO file with the `save_as_mofile` method
 pub trait SaveAsMOFile {
     /// Save the file as a MO file to the given path
-    fn save_as_mofile(&self, path: &str);
+    fn save_as_mofile(&self, path: &str) -> io::Result<()>;
 }
 
 /// Provides functions to convert to MO files content as bytes
@@ -57,11 +60,11 @@ pub trait SaveAsMOFile {
 ///   big endian byte order.
 pub trait AsBytes {
     /// Return the content as bytes
-    fn as_bytes(&self) -> Cow<[u8]>;
+    fn as_bytes(&self) -> Cow<'_, [u8]>;
     /// Return the content as bytes in little endian encoding
-    fn as_bytes_le(&self) -> Cow<[u8]>;
+    fn as_bytes_le(&self) -> Cow<'_, [u8]>;
     /// Return the content as bytes in big endian encoding
-    fn as_bytes_be(&self) -> Cow<[u8]>;
+    fn as_bytes_be(&self) -> Cow<'_, [u8]>;
 }
 
 /// File options struct passed when creating a new PO or MO file
@@ -70,6 +73,7 @@ pub trait AsBytes {
 ///
 /// ```rust
 /// use std::fs;
+/// use std::path::{Path, PathBuf};
 /// use rspolib::FileOptions;
 ///
 /// // From path
@@ -85,15 +89,54 @@ pub trait AsBytes {
 /// // From bytes
 /// let bytes = fs::read("tests-data/obsoletes.po").unwrap();
 /// let opts = FileOptions::from(bytes);
+///
+/// // From std paths
+/// let opts = FileOptions::from(Path::new("tests-data/all.po"));
+/// assert_eq!(opts.path_or_content, "tests-data/all.po");
+/// let opts = FileOptions::from(PathBuf::from("tests-data/all.mo"));
+/// assert_eq!(opts.path_or_content, "tests-data/all.mo");
+///
+/// // Keep the metadata keys in the order they are parsed
+/// let opts = FileOptions {
+///     sort_metadata: false,
+///     ..FileOptions::from("tests-data/metadata.po")
+/// };
 /// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct FileOptions {
     /// Path or content to the file
+    ///
+    /// The path or content is always copied, so the options
+    /// don't borrow from the value they are created from.
+    /// Paths that are not valid UTF-8 are converted lossily.
     pub path_or_content: String,
-    /// Wrap width for the PO file, used when converted as a string
+    /// Wrap width for the PO file, used when converted as a string.
+    /// A width of `0` disables wrapping.
     pub wrapwidth: usize,
     /// Content as bytes, used by MO files when the content is passed as bytes
     pub byte_content: Option<Vec<u8>>,
+    /// Whether to sort the metadata keys when the file is formatted.
+    ///
+    /// If `false`, the metadata keys are formatted in the order
+    /// they were parsed and new keys are appended at the end.
+    pub sort_metadata: bool,
+    /// Order of the metadata keys used when
+    /// [FileOptions::sort_metadata] is `true`.
+    ///
+    /// Keys not included are placed after the listed ones,
+    /// sorted in natural order. If `None`, the standard keys of
+    /// gettext are placed first, in the order used by
+    /// `msginit`.
+    pub metadata_keys_order: Option<Vec<String>>,
+    /// Style used to format the strings that contain newlines.
+    /// See [MultilineStyle].
+    pub multiline_style: MultilineStyle,
+    /// Style used to format the occurrences of the entries.
+    /// See [OccurrencesStyle].
+    pub occurrences_style: OccurrencesStyle,
+    /// Whether to check that there are no entries with the same
+    /// `msgid` and `msgctxt` when appending entries to PO files.
+    pub check_for_duplicates: bool,
 }
 
 impl Default for FileOptions {
@@ -102,15 +145,125 @@ impl Default for FileOptions {
             path_or_content: "".to_string(),
             wrapwidth: 78,
             byte_content: None,
+            sort_metadata: true,
+            metadata_keys_order: None,
+            multiline_style: MultilineStyle::Wrap,
+            occurrences_style: OccurrencesStyle::Wrap,
+            check_for_duplicates: false,
         }
     }
 }
 
+impl FileOptions {
+    /// Returns a builder to create options with chainable
+    /// setters, starting from the default options
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, FileOptions};
+    ///
+    /// let options = FileOptions::builder()
+    ///     .path("tests-data/all.po")
+    ///     .wrapwidth(50)
+    ///     .check_for_duplicates(true)
+    ///     .build();
+    /// assert_eq!(options.path_or_content, "tests-data/all.po");
+    /// assert_eq!(options.wrapwidth, 50);
+    /// assert!(options.check_for_duplicates);
+    ///
+    /// let file = pofile(options).unwrap();
+    /// assert_eq!(file.options.wrapwidth, 50);
+    /// ```
+    pub fn builder() -> FileOptionsBuilder {
+        FileOptionsBuilder::default()
+    }
+}
+
+/// Builder of [FileOptions], created with
+/// [FileOptions::builder]
+///
+/// Each setter defines the field of the same name of
+/// [FileOptions].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileOptionsBuilder {
+    options: FileOptions,
+}
+
+impl FileOptionsBuilder {
+    /// Sets the path of the file
+    ///
+    /// Paths that are not valid UTF-8 are converted lossily.
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.options.path_or_content =
+            path.as_ref().to_string_lossy().into_owned();
+        self
+    }
+
+    /// Sets the content of the file
+    pub fn content(mut self, content: &str) -> Self {
+        self.options.path_or_content = content.to_string();
+        self
+    }
+
+    pub fn wrapwidth(mut self, wrapwidth: usize) -> Self {
+        self.options.wrapwidth = wrapwidth;
+        self
+    }
+
+    pub fn byte_content(mut self, byte_content: Vec<u8>) -> Self {
+        self.options.byte_content = Some(byte_content);
+        self
+    }
+
+    pub fn sort_metadata(mut self, sort_metadata: bool) -> Self {
+        self.options.sort_metadata = sort_metadata;
+        self
+    }
+
+    pub fn metadata_keys_order(mut self, keys: Vec<String>) -> Self {
+        self.options.metadata_keys_order = Some(keys);
+        self
+    }
+
+    pub fn multiline_style(mut self, style: MultilineStyle) -> Self {
+        self.options.multiline_style = style;
+        self
+    }
+
+    pub fn occurrences_style(
+        mut self,
+        style: OccurrencesStyle,
+    ) -> Self {
+        self.options.occurrences_style = style;
+        self
+    }
+
+    pub fn check_for_duplicates(mut self, check: bool) -> Self {
+        self.options.check_for_duplicates = check;
+        self
+    }
+
+    /// Returns the options
+    pub fn build(self) -> FileOptions {
+        self.options
+    }
+}
+
+impl From<FileOptionsBuilder> for FileOptions {
+    fn from(builder: FileOptionsBuilder) -> Self {
+        builder.build()
+    }
+}
+
 impl From<&FileOptions> for FileOptions {
     fn from(options: &Self) -> Self {
         Self {
             path_or_content: options.path_or_content.clone(),
             wrapwidth: options.wrapwidth,
+            sort_metadata: options.sort_metadata,
+            metadata_keys_order: options.metadata_keys_order.clone(),
+            multiline_style: options.multiline_style,
+            occurrences_style: options.occurrences_style,
+            check_for_duplicates: options.check_for_duplicates,
             ..Default::default()
         }
     }
@@ -147,9 +300,9 @@ impl From<Vec<u8>> for FileOptions {
 impl From<(Vec<u8>, usize)> for FileOptions {
     fn from((byte_content, wrapwidth): (Vec<u8>, usize)) -> Self {
         Self {
-            path_or_content: "".to_string(),
             wrapwidth,
             byte_content: Some(byte_content),
+            ..Default::default()
         }
     }
 }
@@ -157,27 +310,79 @@ impl From<(Vec<u8>, usize)> for FileOptions {
 impl From<&Path> for FileOptions {
     fn from(path: &Path) -> Self {
         Self {
-            path_or_content: path.to_str().unwrap().to_string(),
+            path_or_content: path.to_string_lossy().into_owned(),
             ..Default::default()
         }
     }
 }
 
+impl From<PathBuf> for FileOptions {
+    fn from(path: PathBuf) -> Self {
+        Self::from(path.as_path())
+    }
+}
+
+/// Converts the metadata to the msgstr of the header entry
+///
+/// Following the gettext convention, every field, including
+/// the last one, is terminated by a newline.
+///
+/// If `keys_order` is passed, the keys are formatted in that
+/// order, followed by the rest of keys sorted in natural order.
+/// Otherwise, the standard keys are placed first.
+///
+/// Files are always written as UTF-8, so the charset of the
+/// `Content-Type` field is formatted as UTF-8.
 fn metadata_hashmap_to_msgstr(
     metadata: &HashMap<String, String>,
+    keys_order: Option<&[String]>,
 ) -> String {
-    let ordered_map = metadata_hashmap_to_ordered(metadata);
-    let mut parts: Vec<String> =
-        Vec::with_capacity(ordered_map.len());
+    let ordered_map = match keys_order {
+        Some(keys_order) => {
+            metadata_hashmap_to_keys_order(metadata, keys_order)
+        }
+        None => metadata_hashmap_to_ordered(metadata),
+    };
+    let mut msgstr = String::new();
     for (key, value) in ordered_map {
-        let mut msgstr =
-            String::with_capacity(key.len() + value.len() + 2);
+        let value = match key.as_str() {
+            "Content-Type" => charset::utf8_content_type(&value),
+            _ => Cow::Borrowed(value.as_str()),
+        };
+        let value = value.trim_end_matches('\n');
+        msgstr.reserve(key.len() + value.len() + 3);
         msgstr.push_str(&key);
         msgstr.push_str(": ");
-        msgstr.push_str(&value);
-        parts.push(msgstr);
+        msgstr.push_str(value);
+        msgstr.push('\n');
     }
-    parts.join("\n")
+    msgstr
+}
+
+fn metadata_hashmap_to_keys_order(
+    metadata: &HashMap<String, String>,
+    keys_order: &[String],
+) -> Vec<(String, String)> {
+    let mut ret: Vec<(String, String)> =
+        Vec::with_capacity(metadata.len());
+    for key in keys_order {
+        if let Some(value) = metadata.get(key) {
+            ret.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    // keys not found in the order are appended at the end
+    let mut metadata_keys = metadata
+        .keys()
+        .filter(|key| !keys_order.contains(key))
+        .collect::<Vec<&String>>();
+    metadata_keys.sort_by(|&a, &b| compare_natural_order(a, b));
+    for key in metadata_keys {
+        let value = metadata.get(key).unwrap();
+        ret.push((key.to_string(), value.to_string()));
+    }
+
+    ret
 }
 
 fn metadata_hashmap_to_ordered(
@@ -216,11 +421,30 @@ mod tests {
             wrapwidth: 50,
             path_or_content: "foobar".to_string(),
             byte_content: None,
+            sort_metadata: false,
+            metadata_keys_order: Some(vec!["Language".to_string()]),
+            multiline_style: MultilineStyle::PreserveLines,
+            occurrences_style: OccurrencesStyle::OnePerLine,
+            check_for_duplicates: true,
         };
 
         let options_from_options = FileOptions::from(&options);
         assert_eq!(options_from_options.wrapwidth, 50);
         assert_eq!(options_from_options.path_or_content, "foobar");
+        assert!(!options_from_options.sort_metadata);
+        assert_eq!(
+            options_from_options.metadata_keys_order,
+            Some(vec!["Language".to_string()])
+        );
+        assert_eq!(
+            options_from_options.multiline_style,
+            MultilineStyle::PreserveLines
+        );
+        assert_eq!(
+            options_from_options.occurrences_style,
+            OccurrencesStyle::OnePerLine
+        );
+        assert!(options_from_options.check_for_duplicates);
 
         // FileOptions from &str
         let options_from_str = FileOptions::from("foobar");
@@ -235,5 +459,84 @@ mod tests {
             options_from_str_and_usize.path_or_content,
             "foobar"
         );
+
+        // FileOptions from &Path and PathBuf
+        let options_from_path =
+            FileOptions::from(Path::new("tests-data/all.po"));
+        assert_eq!(
+            options_from_path.path_or_content,
+            "tests-data/all.po"
+        );
+        let options_from_path_buf =
+            FileOptions::from(PathBuf::from("tests-data/all.mo"));
+        assert_eq!(
+            options_from_path_buf.path_or_content,
+            "tests-data/all.mo"
+        );
+    }
+
+    #[test]
+    fn options_builder() {
+        assert_eq!(
+            FileOptions::builder().build(),
+            FileOptions::default()
+        );
+
+        let options = FileOptions::builder()
+            .path(Path::new("tests-data/all.mo"))
+            .wrapwidth(0)
+            .byte_content(vec![1, 2])
+            .sort_metadata(false)
+            .metadata_keys_order(vec!["Language".to_string()])
+            .multiline_style(MultilineStyle::WrapLines)
+            .occurrences_style(OccurrencesStyle::OnePerLine)
+            .check_for_duplicates(true)
+            .build();
+        assert_eq!(
+            options,
+            FileOptions {
+                path_or_content: "tests-data/all.mo".to_string(),
+                wrapwidth: 0,
+                byte_content: Some(vec![1, 2]),
+                sort_metadata: false,
+                metadata_keys_order: Some(vec![
+                    "Language".to_string()
+                ]),
+                multiline_style: MultilineStyle::WrapLines,
+                occurrences_style: OccurrencesStyle::OnePerLine,
+                check_for_duplicates: true,
+            }
+        );
+
+        let builder = FileOptions::builder().content("msgid \"\"");
+        assert_eq!(
+            FileOptions::from(builder).path_or_content,
+            "msgid \"\""
+        );
+    }
+
+    #[test]
+    fn metadata_msgstr_newlines() {
+        let metadata = HashMap::from([
+            ("Language".to_string(), "es".to_string()),
+            ("MIME-Version".to_string(), "1.0\n".to_string()),
+            ("X-Generator".to_string(), "".to_string()),
+        ]);
+
+        assert_eq!(
+            metadata_hashmap_to_msgstr(&metadata, None),
+            "Language: es\nMIME-Version: 1.0\nX-Generator: \n",
+        );
+        assert_eq!(
+            metadata_hashmap_to_msgstr(&HashMap::new(), None),
+            ""
+        );
+
+        let keys_order =
+            vec!["X-Generator".to_string(), "Language".to_string()];
+        assert_eq!(
+            metadata_hashmap_to_msgstr(&metadata, Some(&keys_order)),
+            "X-Generator: \nLanguage: es\nMIME-Version: 1.0\n",
+        );
     }
 }
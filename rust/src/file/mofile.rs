@@ -2,18 +2,23 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Cursor, Read, Write};
 use std::path::Path;
 
 use crate::entry::{
-    mo_metadata_entry_to_string, MOEntry, MsgidEotMsgctxt,
+    maybe_msgid_msgctxt_eot_split, mo_metadata_entry_to_string,
+    MOEntry, MsgidEotMsgctxt, Translated,
 };
-use crate::errors::IOError;
+use crate::errors::{ConversionError, IOError};
 use crate::file::{
     metadata_hashmap_to_msgstr, pofile::POFile, AsBytes, FileOptions,
     Save, SaveAsMOFile, SaveAsPOFile,
 };
-use crate::moparser::{MOFileParser, MAGIC, MAGIC_SWAPPED};
+use crate::moparser::{
+    MOFileParser, MoRevision, MAGIC, MAGIC_SWAPPED,
+};
+use crate::plurals::{self, PluralExpression};
+use crate::traits::SeekRead;
 
 fn empty_msgctxt_predicate(_: &MOEntry, _: &str) -> bool {
     true
@@ -73,19 +78,60 @@ where
     Ok(parser.file)
 }
 
+/// Positions of the entries of a file and its `plural`
+/// expression, built by [MOFile::build_index]
+#[derive(Clone, Debug)]
+struct Index {
+    /// Number of entries when the index was built
+    len: usize,
+    /// First position of each [MsgidEotMsgctxt] key
+    by_msgid_eot_msgctxt: HashMap<String, usize>,
+    /// `Plural-Forms` header when the index was built
+    plural_forms: Option<String>,
+    /// Parsed `plural` expression of the header, if valid
+    plural: Option<PluralExpression>,
+}
+
+/// Optional [Index] of a file
+///
+/// Ignored when comparing files.
+#[derive(Clone, Debug, Default)]
+struct EntriesIndex(Option<Index>);
+
+impl PartialEq for EntriesIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
 /// MO file
 #[derive(Clone, Debug, PartialEq)]
 pub struct MOFile {
     /// Magic number, either [MAGIC] or [MAGIC_SWAPPED]
     pub magic_number: Option<u32>,
-    /// Version number, either 0 or 1
-    pub version: Option<u32>,
+    /// Revision number, defined for parsed files
+    pub version: Option<MoRevision>,
     /// Metadata as a hash map
     pub metadata: HashMap<String, String>,
+    /// Order in which the metadata keys have been parsed.
+    ///
+    /// Used to format the metadata when the option
+    /// [FileOptions::sort_metadata] is `false`.
+    pub metadata_order: Vec<String>,
+    /// Name of the encoding of the parsed file, like `UTF-8`.
+    ///
+    /// Detected from the `charset` of the `Content-Type`
+    /// header, the messages are transcoded to UTF-8. Defaults
+    /// to `UTF-8`. Files are always written as UTF-8, declaring
+    /// it as the charset of the `Content-Type` header.
+    pub encoding: String,
     /// Message entries
     pub entries: Vec<MOEntry>,
     /// File options. See [FileOptions].
     pub options: FileOptions,
+    /// Index used by the gettext functions, like
+    /// [MOFile::gettext]
+    index: EntriesIndex,
 }
 
 impl MOFile {
@@ -95,8 +141,114 @@ impl MOFile {
             magic_number: None,
             version: None,
             metadata: HashMap::new(),
+            metadata_order: Vec::new(),
+            encoding: "UTF-8".to_string(),
             entries: Vec::new(),
+            index: EntriesIndex::default(),
+        }
+    }
+
+    /// Parses a MO file from a reader
+    ///
+    /// As MO files are read by offsets, the whole content is
+    /// read into memory before parsing it. The content of
+    /// [FileOptions] is ignored, only the rest of the options
+    /// are used.
+    ///
+    /// ```rust
+    /// use rspolib::{FileOptions, MOFile};
+    ///
+    /// let reader = std::fs::File::open("tests-data/all.mo").unwrap();
+    /// let file =
+    ///     MOFile::from_reader(reader, FileOptions::default()).unwrap();
+    /// assert_eq!(file.entries.len(), 7);
+    /// ```
+    pub fn from_reader<R: Read>(
+        mut reader: R,
+        options: FileOptions,
+    ) -> Result<MOFile, IOError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(|err| {
+            IOError::ErrorReadingContent {
+                message: err.to_string(),
+            }
+        })?;
+        let mut parser = MOFileParser::with_handle(
+            options,
+            Box::new(Cursor::new(content)),
+        );
+        parser.parse()?;
+        Ok(parser.file)
+    }
+
+    /// Parses a MO file from a reader that can seek
+    ///
+    /// Unlike [MOFile::from_reader], the content is not read in
+    /// memory at once: the parser seeks to the tables of the
+    /// file and to each string, reading only what it needs.
+    /// Useful for large files. Wrap files in a
+    /// [BufReader](std::io::BufReader) to avoid reading them
+    /// in small chunks. The content of [FileOptions] is
+    /// ignored, only the rest of the options are used.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// use rspolib::{FileOptions, MOFile};
+    ///
+    /// let reader = BufReader::new(File::open("tests-data/all.mo").unwrap());
+    /// let file =
+    ///     MOFile::from_seek_read(reader, FileOptions::default()).unwrap();
+    /// assert_eq!(file.entries.len(), 7);
+    /// ```
+    pub fn from_seek_read<R: SeekRead>(
+        reader: R,
+        options: FileOptions,
+    ) -> Result<MOFile, IOError> {
+        let mut parser =
+            MOFileParser::with_handle(options, Box::new(reader));
+        parser.parse()?;
+        Ok(parser.file)
+    }
+
+    /// Converts a PO file to a MO file, failing on conflicting entries
+    ///
+    /// Works like `MOFile::from(&POFile)` but returns an error if
+    /// some translated entry defines a non empty `msgstr` along with
+    /// `msgstr_plural` translations, or `msgstr_plural` translations
+    /// without a `msgid_plural`, instead of writing an ambiguous
+    /// entry to the MO file.
+    ///
+    /// Like all the conversions from PO to MO files, the conversion
+    /// is lossy: only translated entries are included and the header,
+    /// comments, occurrences, flags and previous fields are lost.
+    pub fn try_from_pofile(
+        file: &POFile,
+    ) -> Result<MOFile, ConversionError> {
+        for entry in file.translated_entries() {
+            let has_msgstr =
+                entry.msgstr.as_ref().is_some_and(|m| !m.is_empty());
+            let has_msgstr_plural = !entry.msgstr_plural.is_empty();
+            if has_msgstr_plural && (has_msgstr || !entry.is_plural())
+            {
+                return Err(
+                    ConversionError::ConflictingPluralEntry {
+                        msgid: entry.msgid.clone(),
+                    },
+                );
+            }
         }
+        Ok(MOFile::from(file))
+    }
+
+    /// Returns the revision number of the file
+    ///
+    /// Files that have not been parsed from a MO file have
+    /// no version defined, so [MoRevision::Rev0] is returned
+    /// for them.
+    pub fn revision(&self) -> MoRevision {
+        self.version.unwrap_or_default()
     }
 
     /// Returns the metadata as a [MOEntry]
@@ -104,8 +256,15 @@ impl MOFile {
         let mut entry =
             MOEntry::new("".to_string(), None, None, vec![], None);
         if !self.metadata.is_empty() {
-            entry.msgstr =
-                Some(metadata_hashmap_to_msgstr(&self.metadata))
+            entry.msgstr = Some(metadata_hashmap_to_msgstr(
+                &self.metadata,
+                match self.options.sort_metadata {
+                    true => {
+                        self.options.metadata_keys_order.as_deref()
+                    }
+                    false => Some(&self.metadata_order),
+                },
+            ))
         }
 
         entry
@@ -170,27 +329,503 @@ impl MOFile {
         })
     }
 
+    /// Builds an index of the entries for fast translations
+    ///
+    /// Once built, the gettext functions like [MOFile::gettext]
+    /// look up the entries in constant time instead of
+    /// scanning them, and [MOFile::ngettext] doesn't parse the
+    /// `Plural-Forms` header on each call. Useful when the file
+    /// is used as a translation catalog at runtime.
+    ///
+    /// Editing the public fields in place is not detected, so
+    /// the index should be built again after modifying the
+    /// entries. If the number of entries or the `Plural-Forms`
+    /// header change, the functions fall back to scanning the
+    /// entries and parsing the header.
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// let mut file = mofile("tests-data/all.mo").unwrap();
+    /// file.build_index();
+    /// assert_eq!(file.pgettext("abbrev. month", "Jan."), "Ene.");
+    /// ```
+    pub fn build_index(&mut self) {
+        let mut by_msgid_eot_msgctxt =
+            HashMap::with_capacity(self.entries.len());
+        for (position, entry) in self.entries.iter().enumerate() {
+            by_msgid_eot_msgctxt
+                .entry(entry.msgid_eot_msgctxt())
+                .or_insert(position);
+        }
+        let plural_forms = self.metadata.get("Plural-Forms").cloned();
+        self.index.0 = Some(Index {
+            len: self.entries.len(),
+            by_msgid_eot_msgctxt,
+            plural: plural_forms
+                .as_deref()
+                .and_then(PluralExpression::parse),
+            plural_forms,
+        });
+    }
+
+    /// Returns the index if the number of entries didn't
+    /// change since it was built
+    fn valid_index(&self) -> Option<&Index> {
+        self.index
+            .0
+            .as_ref()
+            .filter(|index| index.len == self.entries.len())
+    }
+
+    /// Translates a message
+    ///
+    /// Returns the translation of the entry with the `msgid`
+    /// and without context, or the `msgid` itself if there is
+    /// no such entry or it is not translated. Uses the index
+    /// built by [MOFile::build_index], if any.
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// assert_eq!(
+    ///     file.gettext("The number of days must be between {min_days} and {max_days}."),
+    ///     "El número de días debe estar entre {min_days} y {max_days}.",
+    /// );
+    /// assert_eq!(file.gettext("Unknown"), "Unknown");
+    /// ```
+    pub fn gettext<'a>(&'a self, msgid: &'a str) -> &'a str {
+        self.translate(None, msgid)
+    }
+
+    /// Translates a message in a context
+    ///
+    /// Like [MOFile::gettext], but the entry must have the
+    /// given `msgctxt`.
+    pub fn pgettext<'a>(
+        &'a self,
+        msgctxt: &str,
+        msgid: &'a str,
+    ) -> &'a str {
+        self.translate(Some(msgctxt), msgid)
+    }
+
+    /// Translates a message with plural forms for a number
+    ///
+    /// The plural form is selected evaluating the `plural`
+    /// expression of the `Plural-Forms` metadata field, using
+    /// `plural=(n != 1)` if it is not defined or it can't be
+    /// evaluated. If there is no entry with the `msgid` and
+    /// without context or the selected form is not
+    /// translated, returns `msgid` when `n` is 1 and
+    /// `msgid_plural` otherwise.
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// let msgid = "Ensure that there are no more than %(max)s digit in total.";
+    /// let msgid_plural = "Ensure that there are no more than %(max)s digits in total.";
+    /// assert_eq!(
+    ///     file.ngettext(msgid, msgid_plural, 3),
+    ///     "Asegúrese de que no haya más de %(max)s dígitos en total.",
+    /// );
+    /// assert_eq!(file.ngettext("apple", "apples", 3), "apples");
+    /// ```
+    pub fn ngettext<'a>(
+        &'a self,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.translate_plural(None, msgid, msgid_plural, n)
+    }
+
+    /// Translates a message with plural forms in a context
+    ///
+    /// Like [MOFile::ngettext], but the entry must have the
+    /// given `msgctxt`.
+    pub fn npgettext<'a>(
+        &'a self,
+        msgctxt: &str,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.translate_plural(Some(msgctxt), msgid, msgid_plural, n)
+    }
+
+    fn lookup(
+        &self,
+        msgctxt: Option<&str>,
+        msgid: &str,
+    ) -> Option<&MOEntry> {
+        let matches = |e: &MOEntry| {
+            e.msgid == msgid && e.msgctxt.as_deref() == msgctxt
+        };
+        if let Some(index) = self.valid_index() {
+            let key = maybe_msgid_msgctxt_eot_split(
+                msgid,
+                &msgctxt.map(str::to_string),
+            );
+            let entry = index
+                .by_msgid_eot_msgctxt
+                .get(key.as_ref())
+                .map(|position| &self.entries[*position]);
+            if entry.is_none_or(matches) {
+                return entry;
+            }
+        }
+        self.entries.iter().find(|e| matches(e))
+    }
+
+    /// Returns the index of the plural form to use for `n`
+    ///
+    /// Falls back to `plural=(n != 1)` if the file has no
+    /// valid `Plural-Forms` header.
+    fn plural_index(&self, n: u64) -> usize {
+        let plural_forms = self.metadata.get("Plural-Forms");
+        match self.valid_index().filter(|index| {
+            index.plural_forms.as_ref() == plural_forms
+        }) {
+            Some(index) => index
+                .plural
+                .as_ref()
+                .and_then(|plural| plural.index(n)),
+            None => plural_forms.and_then(|plural_forms| {
+                plurals::plural_index(plural_forms, n)
+            }),
+        }
+        .unwrap_or(usize::from(n != 1))
+    }
+
+    fn translate<'a>(
+        &'a self,
+        msgctxt: Option<&str>,
+        msgid: &'a str,
+    ) -> &'a str {
+        self.lookup(msgctxt, msgid)
+            .and_then(|entry| entry.msgstr.as_deref())
+            .filter(|msgstr| !msgstr.is_empty())
+            .unwrap_or(msgid)
+    }
+
+    fn translate_plural<'a>(
+        &'a self,
+        msgctxt: Option<&str>,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        let index = self.plural_index(n);
+        self.lookup(msgctxt, msgid)
+            .and_then(|entry| entry.msgstr_plural.get(index))
+            .filter(|msgstr| !msgstr.is_empty())
+            .map(String::as_str)
+            .unwrap_or(if n == 1 { msgid } else { msgid_plural })
+    }
+
+    /// Returns the entries that are not a metadata entry
+    ///
+    /// The metadata is stored in [MOFile::metadata] when a file
+    /// is parsed, but an entry with an empty `msgid` and no
+    /// `msgctxt` could be added to the entries of a file
+    /// built manually.
+    ///
+    /// ```rust
+    /// use rspolib::{mofile, MOEntry};
+    ///
+    /// let mut file = mofile("tests-data/all.mo").unwrap();
+    /// let entries_len = file.entries.len();
+    /// file.entries.push(MOEntry::from(""));
+    /// assert_eq!(file.without_header_entries().len(), entries_len);
+    /// ```
+    pub fn without_header_entries(&self) -> Vec<&MOEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.msgid.is_empty() || e.msgctxt.is_some())
+            .collect()
+    }
+
+    /// Returns references to the translated entries of the file
+    ///
+    /// Header entries are not included. MO files usually only
+    /// store translated messages, so this is mainly meaningful
+    /// when the file was compiled from a partially translated
+    /// PO file.
+    pub fn translated_entries(&self) -> Vec<&MOEntry> {
+        self.without_header_entries()
+            .into_iter()
+            .filter(|entry| entry.translated())
+            .collect()
+    }
+
+    /// Returns references to the untranslated entries of the
+    /// file
+    ///
+    /// Header entries are not included. See
+    /// [MOFile::translated_entries].
+    pub fn untranslated_entries(&self) -> Vec<&MOEntry> {
+        self.without_header_entries()
+            .into_iter()
+            .filter(|entry| !entry.translated())
+            .collect()
+    }
+
+    /// Returns the percent of the entries translated in the file
+    ///
+    /// Header entries are not taken into account. See
+    /// [MOFile::translated_entries].
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// assert_eq!(file.percent_translated(), 100.0);
+    /// ```
+    pub fn percent_translated(&self) -> f32 {
+        let entries = self.without_header_entries();
+        if entries.is_empty() {
+            return 0.0;
+        }
+        let translated =
+            entries.iter().filter(|entry| entry.translated()).count();
+        (translated as f32 / entries.len() as f32) * 100.0
+    }
+
     /// Remove an entry from the file
     pub fn remove(&mut self, entry: &MOEntry) {
         self.entries.retain(|e| e != entry);
     }
 
     /// Remove the first entry that has the same msgid
-    pub fn remove_by_msgid(&mut self, msgid: &str) {
-        self.entries.retain(|e| e.msgid != msgid);
+    ///
+    /// Returns `true` if an entry has been removed.
+    pub fn remove_by_msgid(&mut self, msgid: &str) -> bool {
+        self.remove_first(|e| e.msgid == msgid)
     }
 
     /// Remove the first entry that has the same msgid and msgctxt
+    ///
+    /// An empty `msgctxt` matches entries without context.
+    /// Returns `true` if an entry has been removed.
     pub fn remove_by_msgid_msgctxt(
         &mut self,
         msgid: &str,
         msgctxt: &str,
-    ) {
-        self.entries.retain(|e| {
-            e.msgid != msgid
-                || e.msgctxt.as_ref().unwrap_or(&"".to_string())
-                    != msgctxt
-        });
+    ) -> bool {
+        self.remove_first(|e| {
+            e.msgid == msgid
+                && e.msgctxt.as_deref().unwrap_or("") == msgctxt
+        })
+    }
+
+    fn remove_first<F>(&mut self, predicate: F) -> bool
+    where
+        F: Fn(&MOEntry) -> bool,
+    {
+        match self.entries.iter().position(predicate) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Append the catalog of other MO file to this one
+    ///
+    /// Entries are matched by `msgid` and `msgctxt`. When an
+    /// entry is found in both files, the current one is kept
+    /// unless it is untranslated and the entry of `other` is
+    /// translated, in which case it is replaced. Entries not
+    /// found in the current file are appended in the same
+    /// order that they have in `other`.
+    ///
+    /// Metadata fields of `other` are only added if they are
+    /// not already defined in the current file.
+    ///
+    /// ```rust
+    /// use rspolib::{MOEntry, MOFile};
+    ///
+    /// let mut entry_1 = MOEntry::from("msgid 1");
+    /// entry_1.msgstr = Some("msgstr 1".to_string());
+    /// let mut entry_2 = MOEntry::from("msgid 2");
+    /// entry_2.msgstr = Some("msgstr 2".to_string());
+    ///
+    /// let mut file = MOFile::from(vec![&entry_1]);
+    /// file.append_catalog(&MOFile::from(vec![&entry_2]));
+    /// assert_eq!(file.entries.len(), 2);
+    /// ```
+    pub fn append_catalog(&mut self, other: &MOFile) {
+        let mut indexes: HashMap<String, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.msgid_eot_msgctxt(), i))
+            .collect();
+
+        for other_entry in &other.entries {
+            let key = other_entry.msgid_eot_msgctxt();
+            match indexes.get(&key) {
+                Some(&i) => {
+                    if !self.entries[i].translated()
+                        && other_entry.translated()
+                    {
+                        self.entries[i] = other_entry.clone();
+                    }
+                }
+                None => {
+                    indexes.insert(key, self.entries.len());
+                    self.entries.push(other_entry.clone());
+                }
+            }
+        }
+
+        for (key, value) in &other.metadata {
+            if !self.metadata.contains_key(key) {
+                self.metadata.insert(key.clone(), value.clone());
+                self.metadata_order.push(key.clone());
+            }
+        }
+    }
+
+    /// Returns `true` if both files contain the same translations
+    ///
+    /// Unlike the `==` operator, the order in which the entries
+    /// are stored is ignored and they are matched by `msgid`
+    /// and `msgctxt`. The metadata fields `POT-Creation-Date`
+    /// and `PO-Revision-Date` are ignored too, so two files
+    /// compiled at different times from the same sources are
+    /// considered equal although their bytes differ.
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// // compiled by msgfmt and polib
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// let other = mofile("tests-data/all-polib.mo").unwrap();
+    /// assert!(file.content_eq(&other));
+    /// ```
+    pub fn content_eq(&self, other: &MOFile) -> bool {
+        const IGNORED_METADATA_KEYS: [&str; 2] =
+            ["POT-Creation-Date", "PO-Revision-Date"];
+
+        let metadata = |file: &MOFile| {
+            file.metadata
+                .iter()
+                .filter(|(key, _)| {
+                    !IGNORED_METADATA_KEYS.contains(&key.as_str())
+                })
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<HashMap<String, String>>()
+        };
+        if metadata(self) != metadata(other) {
+            return false;
+        }
+
+        if self.entries.len() != other.entries.len() {
+            return false;
+        }
+        let other_entries: HashMap<String, &MOEntry> = other
+            .entries
+            .iter()
+            .map(|e| (e.msgid_eot_msgctxt(), e))
+            .collect();
+        self.entries.iter().all(|entry| {
+            match other_entries.get(&entry.msgid_eot_msgctxt()) {
+                Some(other_entry) => {
+                    entry.msgstr.as_deref().unwrap_or("")
+                        == other_entry.msgstr.as_deref().unwrap_or("")
+                        && entry.msgid_plural
+                            == other_entry.msgid_plural
+                        && entry.msgstr_plural
+                            == other_entry.msgstr_plural
+                }
+                None => false,
+            }
+        })
+    }
+
+    /// Checks that all the plural entries have the same
+    /// number of translations
+    ///
+    /// The expected number of plural forms is defined by
+    /// `nplurals` in the `Plural-Forms` metadata field. If it
+    /// is not defined, the number of translations of the first
+    /// plural entry is used.
+    pub fn check_plural_forms(&self) -> Result<(), ConversionError> {
+        let mut expected = self
+            .metadata
+            .get("Plural-Forms")
+            .and_then(|plural_forms| plurals::nplurals(plural_forms));
+        for entry in &self.entries {
+            if !entry.is_plural() {
+                continue;
+            }
+            let found = entry.plural_count();
+            match expected {
+                Some(expected) if expected != found => {
+                    return Err(
+                        ConversionError::InconsistentPluralForms {
+                            msgid: entry.msgid.clone(),
+                            expected,
+                            found,
+                        },
+                    );
+                }
+                Some(_) => {}
+                None => expected = Some(found),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the file as bytes checking first that
+    /// the plural entries are consistent
+    ///
+    /// See [MOFile::check_plural_forms].
+    ///
+    /// ```rust
+    /// use rspolib::mofile;
+    ///
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// assert!(file.try_as_bytes().is_ok());
+    /// ```
+    pub fn try_as_bytes(
+        &self,
+    ) -> Result<Cow<'_, [u8]>, ConversionError> {
+        self.check_plural_forms()?;
+        Ok(self.as_bytes())
+    }
+
+    /// Saves the file to the given path with the given magic
+    /// number and revision number, checking first that the
+    /// plural entries are consistent
+    pub(crate) fn save_with(
+        &self,
+        path: &str,
+        magic_number: u32,
+        revision_number: u32,
+    ) -> io::Result<()> {
+        self.check_plural_forms().map_err(|err| {
+            io::Error::new(io::ErrorKind::InvalidData, err)
+        })?;
+        let mut file = File::create(path)?;
+        file.write_all(
+            &self.as_bytes_with(magic_number, revision_number),
+        )
+    }
+
+    /// Sorts the entries by their `msgctxt` + `EOT` + `msgid`
+    /// keys, which is the order of the strings table of the
+    /// binary representation
+    ///
+    /// The sort is stable, so entries with the same key keep
+    /// their relative order.
+    pub fn sort(&mut self) {
+        self.entries.sort_by_cached_key(|e| e.msgid_eot_msgctxt());
     }
 
     /// Returns the entry as a bytes vector
@@ -213,13 +848,45 @@ impl MOFile {
     ///
     /// let file = mofile("tests-data/all.mo").unwrap();
     /// let bytes = file.as_bytes_with(rspolib::MAGIC_SWAPPED, 1);
-    /// assert_eq!(bytes.len(), 1327);
+    /// assert_eq!(bytes.len(), 1328);
     /// ```
     pub fn as_bytes_with(
         &self,
         magic_number: u32,
         revision_number: u32,
-    ) -> Cow<[u8]> {
+    ) -> Cow<'_, [u8]> {
+        self.as_bytes_with_hash(magic_number, revision_number, false)
+    }
+
+    /// Returns the entry as a bytes vector, optionally including
+    /// a hash table
+    ///
+    /// Like [MOFile::as_bytes_with], but if `generate_hash` is
+    /// `true` the hash table used by the gettext runtime to
+    /// look up the messages is written after the string
+    /// tables, as `msgfmt` does. The table uses open
+    /// addressing with double hashing and the `hashpjw`
+    /// function of gettext, and its size is the next prime
+    /// number from 4/3 of the number of strings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rspolib::{mofile, MAGIC};
+    ///
+    /// let file = mofile("tests-data/all.mo").unwrap();
+    /// let bytes = file.as_bytes_with_hash(MAGIC, 0, true);
+    /// let hash_table_size = u32::from_le_bytes(
+    ///     bytes[20..24].try_into().unwrap(),
+    /// );
+    /// assert_eq!(hash_table_size, 11);
+    /// ```
+    pub fn as_bytes_with_hash(
+        &self,
+        magic_number: u32,
+        revision_number: u32,
+        generate_hash: bool,
+    ) -> Cow<'_, [u8]> {
         let metadata_entry = self.metadata_as_entry();
 
         // Select byte order based on magic number
@@ -228,14 +895,21 @@ impl MOFile {
             _ => u32::to_le_bytes,
         };
 
+        // The strings are emitted sorted by their keys, as msgfmt
+        // does, so the output doesn't depend on the order of the
+        // entries. The metadata entry is always the first one.
+        let mut sorted_entries: Vec<&MOEntry> =
+            self.entries.iter().collect();
+        sorted_entries.sort_by_cached_key(|e| e.msgid_eot_msgctxt());
         let mut entries: Vec<&MOEntry> = vec![&metadata_entry];
-        entries.extend(&self.entries);
-        entries.sort_unstable_by(|a, b| {
-            a.msgid_eot_msgctxt().cmp(&b.msgid_eot_msgctxt())
-        });
+        entries.extend(sorted_entries);
         let entries_length = entries.len();
 
         let mut offsets: Vec<(usize, usize, usize, usize)> = vec![];
+        let mut hash_table = match generate_hash {
+            true => HashTable::new(entries_length),
+            false => HashTable::default(),
+        };
 
         let mut ids = "".to_string();
         let mut strs = "".to_string();
@@ -272,6 +946,11 @@ impl MOFile {
                 }
             }
 
+            hash_table.insert(
+                // the lookup key doesn't include the plural form
+                msgid.split('\u{0}').next().unwrap_or_default(),
+                offsets.len(),
+            );
             offsets.push((
                 ids.len(),
                 msgid.len(),
@@ -285,8 +964,10 @@ impl MOFile {
             strs.push('\u{0}');
         }
 
-        // The header is 7 32-bit unsigned integers.
-        let keystart = 7 * 4 + 16 * entries_length;
+        // The header is 7 32-bit unsigned integers, followed
+        // by the string tables and the hash table.
+        let hash_table_offset = 7 * 4 + 16 * entries_length;
+        let keystart = hash_table_offset + 4 * hash_table.len();
         // and the values start after the keys
         let valuestart = keystart + ids.len();
 
@@ -309,9 +990,8 @@ impl MOFile {
             final_offsets.extend(bytes_reader(o as u32));
         }
 
-        let mut output: Vec<u8> = Vec::with_capacity(
-            7 * 4 + 8 * entries_length + ids.len() + strs.len(),
-        );
+        let mut output: Vec<u8> =
+            Vec::with_capacity(keystart + ids.len() + strs.len());
         // magic number
         output.extend(bytes_reader(MAGIC));
         // version
@@ -324,17 +1004,102 @@ impl MOFile {
         output.extend(bytes_reader(
             7 * 4 + (entries_length as u32) * 8,
         ));
-        // size and offset of hash table, we don't use hash tables
-        output.extend([0, 0, 0, 0]);
-        output.extend(bytes_reader(keystart as u32));
+        // size and offset of hash table
+        output.extend(bytes_reader(hash_table.len() as u32));
+        output.extend(bytes_reader(hash_table_offset as u32));
 
         output.extend(final_offsets);
+        for index in hash_table.slots {
+            output.extend(bytes_reader(index));
+        }
         output.extend(ids.as_bytes());
         output.extend(strs.as_bytes());
         output.into()
     }
 }
 
+/// Hash table of the strings of MO files, as generated by
+/// GNU gettext `msgfmt`
+///
+/// Each slot contains the index of a string plus one, or 0 if
+/// it's empty.
+#[derive(Default)]
+struct HashTable {
+    slots: Vec<u32>,
+}
+
+impl HashTable {
+    /// Creates an empty table for `n_strings` strings
+    ///
+    /// The size is computed like `msgfmt` does, which creates
+    /// tables of at least 11 slots.
+    fn new(n_strings: usize) -> Self {
+        let size = next_prime((n_strings * 4 / 3).max(10));
+        Self {
+            slots: vec![0; size],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Inserts the index of a string, resolving collisions
+    /// with double hashing like `msgfmt` does
+    ///
+    /// Does nothing if the table has no slots.
+    fn insert(&mut self, key: &str, index: usize) {
+        let size = self.slots.len() as u32;
+        if size == 0 {
+            return;
+        }
+        let hash = hash_string(key);
+        let increment = 1 + hash % (size - 2);
+        let mut slot = hash % size;
+        while self.slots[slot as usize] != 0 {
+            slot = match slot >= size - increment {
+                true => slot - (size - increment),
+                false => slot + increment,
+            };
+        }
+        self.slots[slot as usize] = index as u32 + 1;
+    }
+}
+
+/// The `hashpjw` function used by gettext to hash the keys
+fn hash_string(key: &str) -> u32 {
+    let mut hash: u32 = 0;
+    for byte in key.bytes() {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xf000_0000;
+        if high != 0 {
+            hash ^= high >> 24;
+            hash ^= high;
+        }
+    }
+    hash
+}
+
+/// Returns the next prime number from `seed`, as computed by
+/// gettext, which doesn't consider 3 a prime number
+fn next_prime(seed: usize) -> usize {
+    let is_prime = |n: usize| {
+        let mut divisor = 3;
+        let mut square = divisor * divisor;
+        while square < n && !n.is_multiple_of(divisor) {
+            divisor += 1;
+            square += 4 * divisor;
+            divisor += 1;
+        }
+        !n.is_multiple_of(divisor)
+    };
+    let mut candidate = seed | 1;
+    while !is_prime(candidate) {
+        candidate += 2;
+    }
+    candidate
+}
+
 impl fmt::Display for MOFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut ret = String::from("#\n");
@@ -357,44 +1122,78 @@ impl SaveAsPOFile for MOFile {}
 
 impl Save for MOFile {
     /// Save the MOFile to a file at the given path
-    fn save(&self, path: &str) {
-        let mut file = File::create(path).unwrap();
-        file.write_all(&self.as_bytes()).ok();
+    ///
+    /// The plural entries are checked first with
+    /// [MOFile::check_plural_forms], returning an error of
+    /// kind [io::ErrorKind::InvalidData] without writing the
+    /// file if they are not consistent.
+    fn save(&self, path: &str) -> io::Result<()> {
+        self.save_with(path, MAGIC, 0)
     }
 }
 
 impl SaveAsMOFile for MOFile {
     /// Save the MOFile to a file at the given path
-    fn save_as_mofile(&self, path: &str) {
-        self.save(path);
+    fn save_as_mofile(&self, path: &str) -> io::Result<()> {
+        self.save(path)
     }
 }
 
 impl AsBytes for MOFile {
     /// Return the MOFile as a vector of bytes in little endian
-    fn as_bytes(&self) -> Cow<[u8]> {
+    ///
+    /// The plural entries are not checked, use
+    /// [MOFile::try_as_bytes] to do it.
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
         self.as_bytes_with(MAGIC, 0)
     }
 
     /// Return the MOFile as a vector of bytes in little endian
-    fn as_bytes_le(&self) -> Cow<[u8]> {
+    fn as_bytes_le(&self) -> Cow<'_, [u8]> {
         self.as_bytes_with(MAGIC, 0)
     }
 
     /// Return the MOFile as a vector of bytes in big endian
-    fn as_bytes_be(&self) -> Cow<[u8]> {
+    fn as_bytes_be(&self) -> Cow<'_, [u8]> {
         self.as_bytes_with(MAGIC_SWAPPED, 0)
     }
 }
 
 impl From<&POFile> for MOFile {
+    /// Generates a [MOFile] from a reference to a [POFile]
+    ///
+    /// The conversion is lossy: only translated entries are
+    /// included and the header, comments, occurrences, flags
+    /// and previous fields are lost. Use [MOFile::try_from_pofile]
+    /// to reject entries with conflicting translations.
     fn from(file: &POFile) -> MOFile {
         let mut new_file = MOFile::new(file.options.clone());
         new_file.metadata = file.metadata.clone();
+        new_file.metadata_order = file.metadata_order.clone();
+        new_file.encoding = file.encoding.clone();
+        new_file.entries =
+            file.iter_translated().map(MOEntry::from).collect();
+        new_file
+    }
+}
+
+impl From<POFile> for MOFile {
+    /// Generates a [MOFile] from a [POFile], consuming it
+    ///
+    /// The conversion is lossy in the same way that the
+    /// conversion from a reference to a [POFile] is, but
+    /// the metadata and the strings of the translated
+    /// entries are moved instead of cloned.
+    fn from(file: POFile) -> MOFile {
+        let mut new_file = MOFile::new(file.options);
+        new_file.metadata = file.metadata;
+        new_file.metadata_order = file.metadata_order;
+        new_file.encoding = file.encoding;
         new_file.entries = file
-            .translated_entries()
-            .iter()
-            .map(|e| MOEntry::from(*e))
+            .entries
+            .into_iter()
+            .filter(Translated::translated)
+            .map(MOEntry::from)
             .collect();
         new_file
     }
@@ -419,7 +1218,7 @@ impl From<&Path> for MOFile {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pofile;
+    use crate::{pofile, POEntry};
     use std::fs;
     use std::io::Read;
     use std::path::Path;
@@ -433,6 +1232,18 @@ mod tests {
         assert_eq!(file.entries.len(), 7);
     }
 
+    #[test]
+    fn mofile_revision() {
+        let file = mofile("tests-data/all.mo").unwrap();
+        assert_eq!(file.version, Some(MoRevision::Rev0));
+        assert_eq!(file.revision(), MoRevision::Rev0);
+
+        let mut file = MOFile::new("".into());
+        assert_eq!(file.revision(), MoRevision::Rev0);
+        file.version = Some(MoRevision::Rev1);
+        assert_eq!(file.revision(), MoRevision::Rev1);
+    }
+
     #[test]
     fn mofile_metadata_as_entry() {
         // File with metadata
@@ -450,7 +1261,7 @@ mod tests {
         let entry = file.metadata_as_entry();
 
         assert_eq!(entry.msgid, "");
-        assert_eq!(entry.msgstr.is_none(), true);
+        assert!(entry.msgstr.is_none());
     }
 
     #[test]
@@ -466,6 +1277,172 @@ mod tests {
         assert_eq!(mo_file.metadata.len(), po_file.metadata.len());
     }
 
+    #[test]
+    fn mofile_from_owned_pofile() {
+        let po_file = pofile("tests-data/all.po").unwrap();
+        let mo_file_from_ref = MOFile::from(&po_file);
+        let mo_file = MOFile::from(po_file);
+
+        assert_eq!(mo_file, mo_file_from_ref);
+    }
+
+    #[test]
+    fn mofile_try_from_pofile() {
+        let po_file = pofile("tests-data/all.po").unwrap();
+        assert_eq!(
+            MOFile::try_from_pofile(&po_file),
+            Ok(MOFile::from(&po_file)),
+        );
+
+        // msgstr_plural without msgid_plural
+        let mut entry = POEntry::from("msgid");
+        entry.msgstr_plural = vec!["msgstr plural".to_string()];
+        let po_file = POFile::from(vec![&entry]);
+        assert_eq!(
+            MOFile::try_from_pofile(&po_file),
+            Err(ConversionError::ConflictingPluralEntry {
+                msgid: "msgid".to_string(),
+            }),
+        );
+
+        // msgstr and msgstr_plural
+        entry.msgid_plural = Some("msgid plural".to_string());
+        entry.msgstr = Some("msgstr".to_string());
+        let po_file = POFile::from(vec![&entry]);
+        assert!(MOFile::try_from_pofile(&po_file).is_err());
+
+        // only msgstr_plural is valid
+        entry.msgstr = None;
+        let po_file = POFile::from(vec![&entry]);
+        assert!(MOFile::try_from_pofile(&po_file).is_ok());
+    }
+
+    #[test]
+    fn try_as_bytes() {
+        let plural_entry = |msgid: &str, forms: usize| {
+            MOEntry::new(
+                msgid.to_string(),
+                None,
+                Some(format!("{} plural", msgid)),
+                (0..forms).map(|i| format!("form {}", i)).collect(),
+                None,
+            )
+        };
+
+        // inconsistent between entries
+        let mut file = MOFile::from(vec![
+            &plural_entry("file", 2),
+            &MOEntry::from("singular"),
+            &plural_entry("folder", 3),
+        ]);
+        assert_eq!(
+            file.try_as_bytes(),
+            Err(ConversionError::InconsistentPluralForms {
+                msgid: "folder".to_string(),
+                expected: 2,
+                found: 3,
+            }),
+        );
+
+        // inconsistent with the header
+        file.metadata.insert(
+            "Plural-Forms".to_string(),
+            "nplurals=3; plural=(n == 1 ? 0 : n == 2 ? 1 : 2);"
+                .to_string(),
+        );
+        assert_eq!(
+            file.try_as_bytes(),
+            Err(ConversionError::InconsistentPluralForms {
+                msgid: "file".to_string(),
+                expected: 3,
+                found: 2,
+            }),
+        );
+
+        // inconsistent files are not saved
+        let path = "tests-data/tests/inconsistent-plurals.mo";
+        let err = file.save(path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("expected 3 plural forms"));
+        assert!(!Path::new(path).exists());
+
+        file.entries[0] = plural_entry("file", 3);
+        assert_eq!(file.try_as_bytes().unwrap(), file.as_bytes());
+        file.save(path).unwrap();
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn mofile_from_reader() {
+        let path = "tests-data/all.mo";
+        let file = MOFile::from_reader(
+            fs::File::open(path).unwrap(),
+            FileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(file.entries, mofile(path).unwrap().entries);
+        assert_eq!(file.metadata, mofile(path).unwrap().metadata);
+
+        // not a MO file
+        assert_eq!(
+            MOFile::from_reader(&b"foo"[..], FileOptions::default()),
+            Err(IOError::ErrorReadingMagicNumber {}),
+        );
+    }
+
+    #[test]
+    fn mofile_from_seek_read() {
+        let path = "tests-data/all.mo";
+        let file = MOFile::from_seek_read(
+            io::BufReader::new(fs::File::open(path).unwrap()),
+            FileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(file.entries, mofile(path).unwrap().entries);
+        assert_eq!(file.metadata, mofile(path).unwrap().metadata);
+
+        let content = fs::read(path).unwrap();
+        let file = MOFile::from_seek_read(
+            Cursor::new(&content),
+            FileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(file.entries, mofile(path).unwrap().entries);
+
+        // not a MO file
+        assert_eq!(
+            MOFile::from_seek_read(
+                Cursor::new(b"foo"),
+                FileOptions::default()
+            ),
+            Err(IOError::ErrorReadingMagicNumber {}),
+        );
+    }
+
+    #[test]
+    fn mofile_encoding() {
+        let file = mofile("tests-data/all.mo").unwrap();
+        assert_eq!(file.encoding, "UTF-8");
+
+        let file = mofile("tests-data/latin1.mo").unwrap();
+        assert_eq!(file.encoding, "windows-1252");
+        assert_eq!(file.metadata["Last-Translator"], "José Muñoz");
+        assert_eq!(
+            file.find_by_msgid("Spain").unwrap().msgstr,
+            Some("España".to_string()),
+        );
+    }
+
+    #[test]
+    fn mofile_from_path_buf() {
+        let path = std::path::PathBuf::from("tests-data/all.mo");
+        let file = mofile(path).unwrap();
+        assert_eq!(
+            file.entries,
+            mofile("tests-data/all.mo").unwrap().entries
+        );
+    }
+
     #[test]
     fn mofile_from_std_path() {
         let file = MOFile::from(Path::new("tests-data/all.mo"));
@@ -515,6 +1492,238 @@ mod tests {
         assert!(polib_file_as_bytes.len() < buffer.len());
     }
 
+    #[test]
+    fn mofile_as_bytes_with_hash() {
+        // generated by msgfmt from the PO file
+        let buffer = fs::read("tests-data/hash-table.mo").unwrap();
+        let msgfmt_file = mofile(buffer.clone()).unwrap();
+        assert_eq!(
+            msgfmt_file.as_bytes_with_hash(MAGIC, 0, true),
+            buffer
+        );
+
+        let file = MOFile::from(
+            &pofile("tests-data/hash-table.po").unwrap(),
+        );
+        assert_eq!(file.as_bytes_with_hash(MAGIC, 0, true), buffer);
+
+        // without hash table
+        let bytes = file.as_bytes_with_hash(MAGIC, 0, false);
+        assert_eq!(bytes, file.as_bytes());
+        assert_eq!(&bytes[20..24], &[0, 0, 0, 0]);
+        let parsed = mofile(bytes.to_vec()).unwrap();
+        assert_eq!(parsed.entries, msgfmt_file.entries);
+
+        // big endian
+        let bytes = file.as_bytes_with_hash(MAGIC_SWAPPED, 0, true);
+        let parsed = mofile(bytes.to_vec()).unwrap();
+        assert_eq!(parsed.entries, msgfmt_file.entries);
+    }
+
+    #[test]
+    fn hash_table_size() {
+        // sizes of the tables generated by msgfmt
+        for (n_strings, size) in [
+            (1, 11),
+            (8, 11),
+            (9, 13),
+            (11, 17),
+            (31, 41),
+            (201, 269),
+        ] {
+            assert_eq!(HashTable::new(n_strings).len(), size);
+        }
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let file = mofile("tests-data/all.mo").unwrap();
+        let entries = file.entries.clone();
+
+        let mut copy = file.clone();
+        copy.entries[0].msgstr = Some("changed".to_string());
+        copy.entries.pop();
+        copy.metadata
+            .insert("Language".to_string(), "fr".to_string());
+
+        assert_eq!(file.entries, entries);
+        assert_ne!(file.metadata, copy.metadata);
+        assert_eq!(copy.entries.len(), entries.len() - 1);
+    }
+
+    #[test]
+    fn translation_stats() {
+        let mut file = MOFile::new(FileOptions::default());
+        assert_eq!(file.percent_translated(), 0.0);
+
+        file.entries = vec![
+            MOEntry::new(
+                "file".to_string(),
+                Some("fichero".to_string()),
+                None,
+                vec![],
+                None,
+            ),
+            MOEntry::new(
+                "folder".to_string(),
+                None,
+                None,
+                vec![],
+                None,
+            ),
+            MOEntry::new(
+                "".to_string(),
+                Some("Language: es\n".to_string()),
+                None,
+                vec![],
+                None,
+            ),
+        ];
+        assert_eq!(file.translated_entries(), vec![&file.entries[0]]);
+        assert_eq!(
+            file.untranslated_entries(),
+            vec![&file.entries[1]]
+        );
+        assert_eq!(file.percent_translated(), 50.0);
+    }
+
+    #[test]
+    fn gettext_functions() {
+        let mut file = MOFile::new(FileOptions::default());
+        file.metadata.insert(
+            "Plural-Forms".to_string(),
+            "nplurals=3; plural=(n==1 ? 0 : n==2 ? 1 : 2);"
+                .to_string(),
+        );
+        file.entries.push(MOEntry::new(
+            "file".to_string(),
+            Some("fichero".to_string()),
+            None,
+            vec![],
+            None,
+        ));
+        file.entries.push(MOEntry::new(
+            "file".to_string(),
+            Some("archivo".to_string()),
+            None,
+            vec![],
+            Some("noun".to_string()),
+        ));
+        file.entries.push(MOEntry::new(
+            "day".to_string(),
+            None,
+            Some("days".to_string()),
+            vec![
+                "día".to_string(),
+                "par".to_string(),
+                "".to_string(),
+            ],
+            None,
+        ));
+        file.entries.push(MOEntry::new(
+            "day".to_string(),
+            None,
+            Some("days".to_string()),
+            vec![
+                "d1".to_string(),
+                "d2".to_string(),
+                "d3".to_string(),
+            ],
+            Some("short".to_string()),
+        ));
+
+        for indexed in [false, true] {
+            if indexed {
+                file.build_index();
+            }
+
+            assert_eq!(file.gettext("file"), "fichero");
+            assert_eq!(file.gettext("folder"), "folder");
+            assert_eq!(file.pgettext("noun", "file"), "archivo");
+            assert_eq!(file.pgettext("verb", "file"), "file");
+
+            assert_eq!(file.ngettext("day", "days", 1), "día");
+            assert_eq!(file.ngettext("day", "days", 2), "par");
+            // untranslated plural form
+            assert_eq!(file.ngettext("day", "days", 5), "days");
+            assert_eq!(file.ngettext("week", "weeks", 1), "week");
+            assert_eq!(file.ngettext("week", "weeks", 0), "weeks");
+            assert_eq!(
+                file.npgettext("short", "day", "days", 7),
+                "d3"
+            );
+            assert_eq!(
+                file.npgettext("long", "day", "days", 7),
+                "days"
+            );
+        }
+
+        // entries added after building the index are found
+        file.entries.push(MOEntry::new(
+            "folder".to_string(),
+            Some("carpeta".to_string()),
+            None,
+            vec![],
+            None,
+        ));
+        assert_eq!(file.gettext("folder"), "carpeta");
+
+        // without Plural-Forms, `plural=(n != 1)` is used
+        file.metadata.clear();
+        assert_eq!(file.npgettext("short", "day", "days", 1), "d1");
+        assert_eq!(file.npgettext("short", "day", "days", 7), "d2");
+        file.build_index();
+        assert_eq!(file.npgettext("short", "day", "days", 7), "d2");
+    }
+
+    #[test]
+    fn mofile_without_header_entries() {
+        let mut file = mofile("tests-data/all.mo").unwrap();
+        assert!(file.entries.iter().all(|e| !e.msgid.is_empty()));
+        let entries_len = file.entries.len();
+
+        // a header entry added manually
+        let mut header_entry = MOEntry::from("");
+        header_entry.msgstr = Some("X-Extra: yes\n".to_string());
+        file.entries.push(header_entry);
+        assert_eq!(file.without_header_entries().len(), entries_len);
+
+        // it is parsed as metadata, not as an entry
+        let parsed = mofile(file.as_bytes().into_owned()).unwrap();
+        assert_eq!(parsed.entries.len(), entries_len);
+        assert!(parsed.entries.iter().all(|e| !e.msgid.is_empty()));
+        assert_eq!(parsed.metadata["X-Extra"], "yes");
+        assert_eq!(
+            parsed.metadata["Content-Type"],
+            file.metadata["Content-Type"]
+        );
+    }
+
+    #[test]
+    fn mofile_sort() {
+        let mut file = mofile("tests-data/all.mo").unwrap();
+        let bytes = file.as_bytes().into_owned();
+
+        // the binary output doesn't depend on the order
+        file.entries.reverse();
+        assert_eq!(file.as_bytes(), bytes);
+        file.entries.swap(0, 3);
+        assert_eq!(file.as_bytes(), bytes);
+
+        file.sort();
+        let keys = file
+            .entries
+            .iter()
+            .map(|e| e.msgid_eot_msgctxt())
+            .collect::<Vec<String>>();
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+
+        // the metadata is parsed first from the output
+        let parsed = mofile(bytes).unwrap();
+        assert_eq!(parsed.metadata, file.metadata);
+        assert_eq!(parsed.entries, file.entries);
+    }
+
     #[test]
     fn mofile_save_as_pofile() {
         let tmpdir = "tests-data/tests";
@@ -525,7 +1734,7 @@ mod tests {
 
         let tmp_path = Path::new(&tmpdir).join("all.po");
         let tmp_path_str = tmp_path.to_str().unwrap();
-        file.save_as_pofile(tmp_path_str);
+        file.save_as_pofile(tmp_path_str).unwrap();
 
         assert_eq!(
             file_as_string,
@@ -548,9 +1757,9 @@ mod tests {
             Path::new(&tmpdir).join(format!("{}.mo", basename));
         let tmp_path_str = tmp_path.to_str().unwrap();
         if save_method_name == "save" {
-            file.save(tmp_path_str);
+            file.save(tmp_path_str).unwrap();
         } else {
-            file.save_as_mofile(tmp_path_str);
+            file.save_as_mofile(tmp_path_str).unwrap();
         }
 
         // exists
@@ -624,7 +1833,8 @@ mod tests {
         assert_eq!(file.entries.len(), 2);
 
         // remove by msgid
-        file.remove_by_msgid("msgid 2");
+        assert!(file.remove_by_msgid("msgid 2"));
+        assert!(!file.remove_by_msgid("msgid 2"));
         assert_eq!(file.entries.len(), 1);
         assert_eq!(file.entries[0].msgid, "msgid 1");
 
@@ -674,4 +1884,97 @@ mod tests {
             "msgstr 2",
         );
     }
+
+    #[test]
+    fn append_catalog() {
+        let mut entry_1 = MOEntry::from("msgid 1");
+        entry_1.msgstr = Some("msgstr 1".to_string());
+        let mut entry_2 = MOEntry::from("msgid 2");
+        entry_2.msgstr = Some("".to_string());
+
+        let mut file = MOFile::from(vec![&entry_1, &entry_2]);
+        file.metadata
+            .insert("Language".to_string(), "es".to_string());
+
+        let mut other_entry_1 = MOEntry::from("msgid 1");
+        other_entry_1.msgstr = Some("other msgstr 1".to_string());
+        let mut other_entry_2 = MOEntry::from("msgid 2");
+        other_entry_2.msgstr = Some("other msgstr 2".to_string());
+        let mut other_entry_3 = MOEntry::from("msgid 2");
+        other_entry_3.msgctxt = Some("msgctxt 3".to_string());
+        other_entry_3.msgstr = Some("other msgstr 3".to_string());
+
+        let mut other = MOFile::from(vec![
+            &other_entry_1,
+            &other_entry_2,
+            &other_entry_3,
+        ]);
+        other
+            .metadata
+            .insert("Language".to_string(), "fr".to_string());
+        other
+            .metadata
+            .insert("MIME-Version".to_string(), "1.0".to_string());
+
+        file.append_catalog(&other);
+
+        assert_eq!(file.entries.len(), 3);
+        // translated entries are kept
+        assert_eq!(
+            file.entries[0].msgstr.as_ref().unwrap(),
+            "msgstr 1"
+        );
+        // untranslated entries are replaced by translated ones
+        assert_eq!(
+            file.entries[1].msgstr.as_ref().unwrap(),
+            "other msgstr 2"
+        );
+        // new entries are appended
+        assert_eq!(
+            file.entries[2].msgctxt.as_ref().unwrap(),
+            "msgctxt 3"
+        );
+
+        assert_eq!(file.metadata.len(), 2);
+        assert_eq!(file.metadata["Language"], "es");
+        assert_eq!(file.metadata["MIME-Version"], "1.0");
+    }
+
+    #[test]
+    fn content_eq() {
+        // same content, different byte layout
+        let file = mofile("tests-data/all.mo").unwrap();
+        let polib_file = mofile("tests-data/all-polib.mo").unwrap();
+        assert_ne!(
+            fs::read("tests-data/all.mo").unwrap(),
+            fs::read("tests-data/all-polib.mo").unwrap(),
+        );
+        assert!(file.content_eq(&polib_file));
+
+        // the order of the entries is ignored
+        let mut reversed = file.clone();
+        reversed.entries.reverse();
+        reversed.metadata.insert(
+            "PO-Revision-Date".to_string(),
+            "2000-01-01 00:00+0000".to_string(),
+        );
+        assert!(file.content_eq(&reversed));
+        assert!(reversed.content_eq(&file));
+
+        // but not the translations
+        let mut other = file.clone();
+        other.entries[0].msgstr = Some("changed".to_string());
+        assert!(!file.content_eq(&other));
+
+        // nor other metadata
+        let mut other = file.clone();
+        other
+            .metadata
+            .insert("Language".to_string(), "xx".to_string());
+        assert!(!file.content_eq(&other));
+
+        let mut other = file.clone();
+        other.entries.pop();
+        assert!(!file.content_eq(&other));
+    }
 }
@@ -1,19 +1,36 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
 use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use natord::compare as compare_natural_order;
+#[cfg(feature = "encoding")]
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::entry::{
-    po_metadata_entry_to_string, POEntry, Translated,
+    maybe_msgid_msgctxt_eot_split, po_metadata_entry_to_string,
+    MsgidEotMsgctxt, MultilineStyle, POEntry, Translated,
 };
+#[cfg(any(feature = "serde", feature = "csv"))]
+use crate::errors::MaybeFilename;
 use crate::errors::SyntaxError;
 use crate::file::{
     metadata_hashmap_to_msgstr, mofile::MOFile, AsBytes, FileOptions,
-    Save, SaveAsMOFile, SaveAsPOFile,
+    Save, SaveAsMOFile, SaveAsPOFile, METADATA_KEYS_ORDER,
 };
-use crate::moparser::{MAGIC, MAGIC_SWAPPED};
+use crate::mojibake;
+use crate::moparser::{Endianness, MAGIC, MAGIC_SWAPPED};
+use crate::plurals;
 use crate::poparser::POFileParser;
-use crate::traits::Merge;
+use crate::similarity::levenshtein_ratio;
+use crate::traits::{IntoPOEntry, Merge};
+use crate::validation::{validate_entry, ValidationWarning};
+use crate::xliff;
 
 fn empty_msgctxt_predicate(_: &POEntry, _: &str) -> bool {
     true
@@ -62,6 +79,288 @@ fn by_previous_msgctxt_predicate(
         == value
 }
 
+/// Field of the entries to search by with
+/// [POFile::find_by_field]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByField {
+    /// The `msgid` of the entries
+    Msgid,
+    /// The `msgstr` of the entries
+    Msgstr,
+    /// The `msgctxt` of the entries
+    Msgctxt,
+    /// The `msgid_plural` of the entries
+    MsgidPlural,
+    /// The `previous_msgid` of the entries
+    PreviousMsgid,
+    /// The `previous_msgid_plural` of the entries
+    PreviousMsgidPlural,
+    /// The `previous_msgctxt` of the entries
+    PreviousMsgctxt,
+}
+
+impl ByField {
+    /// Returns the field with the name accepted by [POFile::find]
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "msgid" => Some(Self::Msgid),
+            "msgstr" => Some(Self::Msgstr),
+            "msgctxt" => Some(Self::Msgctxt),
+            "msgid_plural" => Some(Self::MsgidPlural),
+            "previous_msgid" => Some(Self::PreviousMsgid),
+            "previous_msgid_plural" => {
+                Some(Self::PreviousMsgidPlural)
+            }
+            "previous_msgctxt" => Some(Self::PreviousMsgctxt),
+            _ => None,
+        }
+    }
+
+    fn predicate(self) -> fn(&POEntry, &str) -> bool {
+        match self {
+            Self::Msgid => by_msgid_predicate,
+            Self::Msgstr => by_msgstr_predicate,
+            Self::Msgctxt => by_msgctxt_predicate,
+            Self::MsgidPlural => by_msgid_plural_predicate,
+            Self::PreviousMsgid => by_previous_msgid_predicate,
+            Self::PreviousMsgidPlural => {
+                by_previous_msgid_plural_predicate
+            }
+            Self::PreviousMsgctxt => by_previous_msgctxt_predicate,
+        }
+    }
+}
+
+/// Contributor of a PO file, like a translator or a team
+///
+/// Parsed from values with the form `Name <email>`, as used
+/// in the `Last-Translator` and `Language-Team` metadata
+/// fields and in the `Translators:` block of headers.
+///
+/// ```rust
+/// use rspolib::Contributor;
+///
+/// let contributor =
+///     Contributor::parse("John Doe <john@example.com>").unwrap();
+/// assert_eq!(contributor.name, "John Doe");
+/// assert_eq!(contributor.email.unwrap(), "john@example.com");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contributor {
+    /// Name of the contributor
+    pub name: String,
+    /// Email of the contributor, if any
+    pub email: Option<String>,
+}
+
+impl Contributor {
+    /// Parse a contributor from a `Name <email>` value
+    ///
+    /// Anything after the email or after the first comma
+    /// if there is no email, like the years of contribution
+    /// in `Name <email>, 2020, 2021.`, is ignored. Returns
+    /// `None` if the value is empty.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, email) = match value.split_once('<') {
+            Some((name, rest)) => {
+                let email =
+                    rest.split('>').next().unwrap_or("").trim();
+                (name, Some(email.to_string()))
+            }
+            None => (value.split(',').next().unwrap_or(""), None),
+        };
+        let name = name.trim().trim_end_matches(',').trim();
+        if name.is_empty() && email.is_none() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            email,
+        })
+    }
+}
+
+/// Standard metadata fields of a new translation file
+///
+/// Used by [POFile::init_header]. The charset defaults to
+/// `UTF-8` and the plural forms to the ones of languages
+/// with one singular form, like English.
+///
+/// ```rust
+/// use rspolib::HeaderInit;
+///
+/// let init = HeaderInit {
+///     project: "foo 1.0".to_string(),
+///     language: "es".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(init.charset, "UTF-8");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderInit {
+    /// Name and version of the project, for `Project-Id-Version`
+    pub project: String,
+    /// Language code, like `es` or `pt_BR`, for `Language`
+    pub language: String,
+    /// Translator as `Name <email>`, for `Last-Translator`
+    pub last_translator: String,
+    /// Value of the `Plural-Forms` field
+    pub plural_forms: String,
+    /// Charset used in the `Content-Type` field
+    ///
+    /// Files are always written as UTF-8, so other charsets
+    /// are only kept in [POFile::metadata].
+    pub charset: String,
+}
+
+impl Default for HeaderInit {
+    fn default() -> Self {
+        Self {
+            project: String::new(),
+            language: String::new(),
+            last_translator: String::new(),
+            plural_forms: "nplurals=2; plural=(n != 1);".to_string(),
+            charset: "UTF-8".to_string(),
+        }
+    }
+}
+
+/// Options to merge PO files with [POFile::merge_with]
+///
+/// The default options are the ones used by `merge`: new
+/// entries are added, missing entries are marked as obsolete
+/// and existing translations are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Whether the translations of the entries found in both
+    /// files are replaced by the ones of the other file. If
+    /// `false`, only the untranslated entries take them.
+    pub overwrite_translations: bool,
+    /// Whether the entries not found in the other file are
+    /// marked as obsolete.
+    pub mark_obsolete: bool,
+    /// Whether the entries of the other file not found in this
+    /// one are added.
+    pub add_new: bool,
+    /// Whether the comments, occurrences and flags of the
+    /// entries found in both files are merged with
+    /// [POEntry::merge_comments]. If `false`, they are
+    /// replaced by the ones of the other file.
+    pub merge_comments: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            overwrite_translations: false,
+            mark_obsolete: true,
+            add_new: true,
+            merge_comments: false,
+        }
+    }
+}
+
+/// Number of entries changed by [POFile::merge_in_place] and
+/// [POFile::merge_with]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    /// Entries of the other file added to this one
+    pub added: usize,
+    /// Entries found in both files, updated with the fields of
+    /// the other file
+    pub updated: usize,
+    /// Entries not found in the other file, marked as obsolete
+    pub obsoleted: usize,
+}
+
+/// Entries changed or in conflict when merging files with
+/// [POFile::merge_report]
+///
+/// The entries are referenced by their `msgid` and `msgctxt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// `msgid` and `msgctxt` of the entries of the other file
+    /// added to this one
+    pub added: Vec<(String, Option<String>)>,
+    /// Untranslated entries that took the translation of the
+    /// other file
+    pub updated: Vec<TranslationChange>,
+    /// Entries translated differently in both files, which
+    /// keep the translation of this file
+    pub conflicts: Vec<TranslationChange>,
+}
+
+/// Translations of an entry found in both files merged by
+/// [POFile::merge_report]
+///
+/// The translations are the `msgstr` of the entries or, for
+/// plural entries, the `msgstr` of each plural form.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TranslationChange {
+    /// `msgid` of the entry
+    pub msgid: String,
+    /// `msgctxt` of the entry, if any
+    pub msgctxt: Option<String>,
+    /// Translation of this file
+    pub old_msgstr: Vec<String>,
+    /// Translation of the other file
+    pub new_msgstr: Vec<String>,
+}
+
+/// Number of entries, words and characters of a file, returned
+/// by [POFile::word_count]
+///
+/// Obsolete entries are not taken into account. Words are
+/// delimited following the Unicode word boundaries rules and
+/// characters are counted as grapheme clusters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordCount {
+    /// Entries of the file
+    pub entries: usize,
+    /// Translated entries
+    pub translated_entries: usize,
+    /// Words of the `msgid` and `msgid_plural` of the entries
+    pub source_words: usize,
+    /// Words of the translations of the translated entries
+    pub translated_words: usize,
+    /// Characters of the `msgid` and `msgid_plural` of the
+    /// entries
+    pub source_chars: usize,
+    /// Characters of the translations of the translated
+    /// entries
+    pub translated_chars: usize,
+}
+
+/// Summary of the translation state of a file, returned by
+/// [POFile::statistics]
+///
+/// The entries are counted in only one of the categories
+/// `translated`, `untranslated`, `fuzzy` and `obsolete`, so
+/// they add up to `total`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// Entries of the file, including the obsolete ones
+    pub total: usize,
+    /// Translated entries, see [POFile::translated_count]
+    pub translated: usize,
+    /// Untranslated entries that are neither fuzzy nor
+    /// obsolete. Unlike [POFile::untranslated_count], which
+    /// includes them.
+    pub untranslated: usize,
+    /// Fuzzy entries, see [POFile::fuzzy_count]
+    pub fuzzy: usize,
+    /// Obsolete entries, see [POFile::obsolete_count]
+    pub obsolete: usize,
+    /// Percent of translated entries, see
+    /// [POFile::percent_translated]
+    pub percent_translated: f32,
+    /// Words of the messages, see [POFile::source_word_count]
+    pub source_words: usize,
+    /// Words of the translations, see
+    /// [POFile::translated_word_count]
+    pub translated_words: usize,
+}
+
 /// PO files factory function.
 ///
 /// It takes an argument that could be either:
@@ -71,6 +370,16 @@ fn by_previous_msgctxt_predicate(
 /// * The content of a PO file as bytes.
 /// * A [FileOptions] struct.
 ///
+/// Strings are read as paths if a file exists at them.
+/// Otherwise, if they look like a path, the error
+/// [SyntaxError::FileNotFound] is returned. A string looks
+/// like a path when it is a single line without double quotes
+/// that doesn't start with `#` and contains a path separator,
+/// ends with the extension `.po` or `.pot` or is the path of
+/// a directory. The rest of strings are parsed as content.
+/// Files that exist but can't be opened return the error
+/// [SyntaxError::ErrorOpeningFile].
+///
 /// # Examples
 ///
 /// ## Open from path
@@ -81,6 +390,19 @@ fn by_previous_msgctxt_predicate(
 /// let file = pofile("tests-data/obsoletes.po").unwrap();
 /// ```
 ///
+/// ## Missing file
+///
+/// ```rust
+/// use rspolib::{errors::SyntaxError, pofile};
+///
+/// assert_eq!(
+///     pofile("tests-data/missing.po"),
+///     Err(SyntaxError::FileNotFound {
+///         path: "tests-data/missing.po".to_string(),
+///     }),
+/// );
+/// ```
+///
 /// ## Open from content
 ///
 /// ```rust
@@ -141,22 +463,190 @@ where
     Ok(parser.file)
 }
 
+/// PO file parser that collects all the syntax errors instead
+/// of stopping at the first one
+///
+/// Accepts the same options as [pofile]. Returns the file with
+/// the entries parsed successfully along with the errors
+/// found. After an error, the lines until the next blank line
+/// are skipped, so each entry with errors is discarded, while
+/// the rest of the file is parsed.
+///
+/// ```rust
+/// use rspolib::pofile_lenient;
+///
+/// let content = r#"#
+/// msgid ""
+/// msgstr ""
+///
+/// msgid "A message"
+/// msgstr "Un mensaje
+///
+/// msgid "Another message"
+/// msgstr "Otro mensaje"
+/// "#;
+///
+/// let (file, errors) = pofile_lenient(content);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(file.entries.len(), 1);
+/// assert_eq!(file.entries[0].msgid, "Another message");
+/// ```
+pub fn pofile_lenient<Opt>(options: Opt) -> (POFile, Vec<SyntaxError>)
+where
+    Opt: Into<FileOptions>,
+{
+    let mut parser = POFileParser::new(options.into()).lenient();
+    let result = parser.parse();
+    let mut errors = parser.errors.take().unwrap_or_default();
+    if let Err(err) = result {
+        errors.push(err);
+    }
+    (parser.file, errors)
+}
+
+/// Number of translated and obsolete entries, along with the
+/// total number of entries when they were counted
+#[derive(Clone, Copy, Debug)]
+struct EntriesCount {
+    total: usize,
+    translated: usize,
+    obsolete: usize,
+}
+
+/// Cached [EntriesCount] of a file
+///
+/// Ignored when comparing files.
+#[derive(Debug, Default)]
+struct TranslatedCount(Mutex<Option<EntriesCount>>);
+
+impl TranslatedCount {
+    fn lock(&self) -> MutexGuard<'_, Option<EntriesCount>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn invalidate(&mut self) {
+        *self.0.get_mut().unwrap_or_else(|err| err.into_inner()) =
+            None;
+    }
+}
+
+impl Clone for TranslatedCount {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(*self.lock()))
+    }
+}
+
+impl PartialEq for TranslatedCount {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Header of a parsed file as it was written
+///
+/// Used to format the header verbatim while [POFile::header]
+/// is not modified. Ignored when comparing files.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub(crate) struct RawHeader(Option<(String, String)>);
+
+impl RawHeader {
+    pub(crate) fn new(header: &str, raw: String) -> Self {
+        Self(Some((header.to_string(), raw)))
+    }
+
+    /// Returns the header as it was written if it is `header`
+    fn get(&self, header: &str) -> Option<&str> {
+        match &self.0 {
+            Some((parsed, raw)) if parsed == header => Some(raw),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for RawHeader {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Positions of the entries of a file, built by
+/// [POFile::build_index]
+#[derive(Clone, Debug)]
+struct Index {
+    /// Number of entries when the index was built
+    len: usize,
+    /// First position of each `msgid`, with any context
+    by_msgid: HashMap<String, usize>,
+    /// First position of each [MsgidEotMsgctxt] key
+    by_msgid_eot_msgctxt: HashMap<String, usize>,
+}
+
+/// Optional [Index] of a file
+///
+/// Ignored when comparing files.
+#[derive(Clone, Debug, Default)]
+struct EntriesIndex(Option<Index>);
+
+impl PartialEq for EntriesIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
 /// PO file
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct POFile {
     /// Entries of the file.
     pub entries: Vec<POEntry>,
     /// Header of the file, if any. Optionally defined
     /// in PO files before the first entry.
+    ///
+    /// The header of a parsed file is written as it was,
+    /// including its whitespace, until it is modified.
     pub header: Option<String>,
+    /// Comments at the end of the file, after the last
+    /// entry, if any.
+    pub trailing_comments: Option<String>,
     /// First optional field of PO files that describes
     /// the metadata of the file stored as a hash map.
     pub metadata: HashMap<String, String>,
+    /// Order in which the metadata keys have been parsed.
+    ///
+    /// Used to format the metadata when the option
+    /// [FileOptions::sort_metadata] is `false`.
+    pub metadata_order: Vec<String>,
     /// Whether the metadata is marked with the `fuzzy`
     /// flag or not.
     pub metadata_is_fuzzy: bool,
+    /// Name of the encoding of the parsed file, like `UTF-8`.
+    ///
+    /// Detected from the `charset` of the `Content-Type`
+    /// header when reading files and readers, whose content is
+    /// transcoded to UTF-8. Defaults to `UTF-8`. Files are
+    /// always written as UTF-8, declaring it as the charset
+    /// of the `Content-Type` header.
+    pub encoding: String,
     /// Options defined for the file. See [FileOptions].
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub options: FileOptions,
+    /// Cache used by [POFile::percent_translated]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    translated_count: TranslatedCount,
+    /// Index used by [POFile::find_by_msgid] and
+    /// [POFile::find_by_msgid_msgctxt]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: EntriesIndex,
+    /// Header as it was parsed, see [RawHeader]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) raw_header: RawHeader,
 }
 
 impl POFile {
@@ -164,755 +654,5200 @@ impl POFile {
         Self {
             options,
             header: None,
+            trailing_comments: None,
             metadata: HashMap::new(),
+            metadata_order: Vec::new(),
             metadata_is_fuzzy: false,
+            encoding: "UTF-8".to_string(),
             entries: Vec::new(),
+            translated_count: TranslatedCount::default(),
+            index: EntriesIndex::default(),
+            raw_header: RawHeader::default(),
         }
     }
 
-    /// Remove an entry from the file
-    pub fn remove(&mut self, entry: &POEntry) {
-        self.entries.retain(|e| e != entry);
+    /// Creates a file with the given metadata and the default
+    /// options
+    ///
+    /// The standard metadata fields are formatted in their
+    /// canonical order, followed by the rest of fields.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use rspolib::POFile;
+    ///
+    /// let file = POFile::with_metadata(HashMap::from([
+    ///     ("Language".to_string(), "es".to_string()),
+    ///     ("Project-Id-Version".to_string(), "foo 1.0".to_string()),
+    /// ]));
+    /// assert_eq!(
+    ///     file.metadata_as_entry().msgstr.unwrap(),
+    ///     "Project-Id-Version: foo 1.0\nLanguage: es\n",
+    /// );
+    /// ```
+    pub fn with_metadata(metadata: HashMap<String, String>) -> Self {
+        let mut file = Self::new(FileOptions::default());
+        file.metadata_order = METADATA_KEYS_ORDER
+            .iter()
+            .filter(|key| metadata.contains_key(**key))
+            .map(|key| key.to_string())
+            .collect();
+        file.metadata = metadata;
+        file
     }
 
-    /// Remove the first entry that has the same msgid
-    pub fn remove_by_msgid(&mut self, msgid: &str) {
-        self.entries.retain(|e| e.msgid != msgid);
+    /// Creates a file with the default options and the
+    /// metadata fields required by most tools
+    ///
+    /// The fields are `MIME-Version: 1.0`,
+    /// `Content-Type: text/plain; charset=UTF-8` and
+    /// `Content-Transfer-Encoding: 8bit`. Use
+    /// [POFile::init_header] to define all the standard fields.
+    ///
+    /// ```rust
+    /// use rspolib::POFile;
+    ///
+    /// let file = POFile::new_empty();
+    /// assert!(file.entries.is_empty());
+    /// assert_eq!(file.metadata["Content-Transfer-Encoding"], "8bit");
+    /// ```
+    pub fn new_empty() -> Self {
+        Self::with_metadata(HashMap::from([
+            ("MIME-Version".to_string(), "1.0".to_string()),
+            (
+                "Content-Type".to_string(),
+                "text/plain; charset=UTF-8".to_string(),
+            ),
+            (
+                "Content-Transfer-Encoding".to_string(),
+                "8bit".to_string(),
+            ),
+        ]))
     }
 
-    /// Remove the first entry that has the same msgid and msgctxt
-    pub fn remove_by_msgid_msgctxt(
-        &mut self,
-        msgid: &str,
-        msgctxt: &str,
-    ) {
-        self.entries.retain(|e| {
-            e.msgid != msgid
-                || e.msgctxt.as_ref().unwrap_or(&"".to_string())
-                    != msgctxt
-        });
+    /// Parses a PO file from a reader
+    ///
+    /// The content is parsed line by line as it is read, so
+    /// it is never loaded completely in memory. The content of
+    /// [FileOptions] is ignored, only the rest of the options
+    /// are used. The content is transcoded to UTF-8 from the
+    /// charset declared in its header, see [POFile::encoding].
+    ///
+    /// ```rust
+    /// use rspolib::{FileOptions, POFile};
+    ///
+    /// let reader = std::fs::File::open("tests-data/all.po").unwrap();
+    /// let file =
+    ///     POFile::from_reader(reader, FileOptions::default()).unwrap();
+    /// assert_eq!(file.entries.len(), 9);
+    /// ```
+    pub fn from_reader<R: Read>(
+        mut reader: R,
+        options: FileOptions,
+    ) -> Result<POFile, SyntaxError> {
+        let mut parser = POFileParser::new(options);
+        parser.parse_reader(&mut reader)?;
+        Ok(parser.file)
     }
 
-    /// Find entries by a given field and value
-    ///
-    /// The field defined in the `by` argument can be one of:
+    /// Parses a PO file from a reader, naming the content
     ///
-    /// * `msgid`
-    /// * `msgstr`
-    /// * `msgctxt`
-    /// * `msgid_plural`
-    /// * `previous_msgid`
-    /// * `previous_msgid_plural`
-    /// * `previous_msgctxt`
+    /// Works like [POFile::from_reader], but the errors are
+    /// reported as found in the file `path`, which is stored
+    /// in the `path_or_content` field of the options. The path
+    /// is not read, so it doesn't need to exist.
     ///
-    /// Passing the optional `msgctxt` argument the entry
-    /// will also must match with the given context.
+    /// ```rust
+    /// use rspolib::{FileOptions, POFile};
     ///
-    /// If `include_obsolete_entries` is set to `true` the
-    /// search will include obsolete entries.
-    pub fn find(
-        &self,
-        value: &str,
-        by: &str,
-        msgctxt: Option<&str>,
-        include_obsolete_entries: bool,
-    ) -> Vec<&POEntry> {
-        let mut entries: Vec<&POEntry> = Vec::new();
+    /// let content = "msgid \"Hello\"\nmsgstr \"Ho\"la\"\n";
+    /// let err = POFile::from_reader_with_path(
+    ///     content.as_bytes(),
+    ///     "locale/es/app.po",
+    ///     FileOptions::default(),
+    /// )
+    /// .unwrap_err();
+    /// assert!(err.to_string().contains("in file locale/es/app.po"));
+    /// ```
+    pub fn from_reader_with_path<R: Read>(
+        mut reader: R,
+        path: &str,
+        options: FileOptions,
+    ) -> Result<POFile, SyntaxError> {
+        let mut parser = POFileParser::new(FileOptions {
+            path_or_content: path.to_string(),
+            ..options
+        });
+        parser.content_is_path = true;
+        parser.parse_reader(&mut reader)?;
+        Ok(parser.file)
+    }
 
-        let msgctxt_predicate: &dyn Fn(&POEntry, &str) -> bool =
-            match msgctxt {
-                Some(_) => &msgctxt_predicate,
-                None => &empty_msgctxt_predicate,
-            };
+    /// Creates a new empty PO template (POT) file
+    ///
+    /// Templates don't contain translations, so their
+    /// header is never marked as fuzzy.
+    pub fn new_pot(options: FileOptions) -> Self {
+        let mut file = Self::new(options);
+        file.metadata_is_fuzzy = false;
+        file
+    }
 
-        let by_predicate: &dyn Fn(&POEntry, &str) -> bool = match by {
-            "msgid" => &by_msgid_predicate,
-            "msgstr" => &by_msgstr_predicate,
-            "msgctxt" => &by_msgctxt_predicate,
-            "msgid_plural" => &by_msgid_plural_predicate,
-            "previous_msgid" => &by_previous_msgid_predicate,
-            "previous_msgid_plural" => {
-                &by_previous_msgid_plural_predicate
-            }
-            "previous_msgctxt" => &by_previous_msgctxt_predicate,
-            _ => &|_: &POEntry, _: &str| false,
-        };
+    /// Returns a PO template (POT) version of the file
+    ///
+    /// The template keeps the header, metadata, comments,
+    /// occurrences and flags of the non obsolete entries.
+    /// Obsolete entries are not included and, in the rest
+    /// of entries:
+    ///
+    /// * `msgstr` is emptied, or set to `None` for plural
+    ///   entries.
+    /// * `msgstr_plural` is emptied for non plural entries.
+    ///   Plural entries get empty translations for their
+    ///   plural forms, at least two.
+    /// * The `fuzzy` flag is removed.
+    /// * `previous_msgctxt`, `previous_msgid` and
+    ///   `previous_msgid_plural` are removed.
+    ///
+    /// The `fuzzy` flag of the header is removed too.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/fuzzy-header.po").unwrap();
+    /// assert!(file.metadata_is_fuzzy);
+    ///
+    /// let template = file.as_pot();
+    /// assert!(!template.metadata_is_fuzzy);
+    /// assert!(template.is_template());
+    /// ```
+    pub fn as_pot(&self) -> Self {
+        let mut file = Self::new_pot(self.options.clone());
+        file.header = self.header.clone();
+        file.trailing_comments = self.trailing_comments.clone();
+        file.metadata = self.metadata.clone();
+        file.metadata_order = self.metadata_order.clone();
+        file.encoding = self.encoding.clone();
 
         for entry in &self.entries {
-            if !include_obsolete_entries && entry.obsolete {
+            if entry.obsolete {
                 continue;
             }
-            if by_predicate(entry, value)
-                && msgctxt_predicate(entry, msgctxt.unwrap_or(""))
-            {
-                entries.push(entry);
+            let mut template_entry = entry.clone();
+            if template_entry.is_plural() {
+                template_entry.msgstr = None;
+                template_entry.msgstr_plural =
+                    vec!["".to_string(); entry.plural_count().max(2)];
+            } else {
+                template_entry.msgstr = Some("".to_string());
+                template_entry.msgstr_plural = vec![];
             }
+            template_entry.set_fuzzy(false);
+            template_entry.previous_msgctxt = None;
+            template_entry.previous_msgid = None;
+            template_entry.previous_msgid_plural = None;
+            file.entries.push(template_entry);
         }
-        entries
+        file
     }
 
-    /// Find an entry by his msgid
-    pub fn find_by_msgid(&self, msgid: &str) -> Option<POEntry> {
-        self.entries.iter().find(|e| e.msgid == msgid).cloned()
+    /// Returns a template of the file, without translations
+    ///
+    /// Alias of [POFile::as_pot]. The obsolete entries are
+    /// dropped and, in the rest, the fields cleared are:
+    ///
+    /// * `msgstr`, emptied, or `None` for plural entries.
+    /// * `msgstr_plural`, emptied for non plural entries and
+    ///   filled with empty strings for plural ones.
+    /// * The `fuzzy` flag.
+    /// * `previous_msgctxt`, `previous_msgid` and
+    ///   `previous_msgid_plural`.
+    ///
+    /// The `fuzzy` flag of the header is cleared as well. The
+    /// header, metadata, comments, occurrences and the rest
+    /// of flags are kept.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let template = file.to_template();
+    /// assert!(template.is_template());
+    /// assert_eq!(template.metadata, file.metadata);
+    /// ```
+    pub fn to_template(&self) -> Self {
+        self.as_pot()
     }
 
-    /// Find an entry by msgid and msgctxt
-    pub fn find_by_msgid_msgctxt(
-        &self,
-        msgid: &str,
-        msgctxt: &str,
-    ) -> Option<POEntry> {
-        self.entries
-            .iter()
-            .find(|e| {
-                e.msgid == msgid
-                    && e.msgctxt.as_ref().unwrap_or(&"".to_string())
-                        == msgctxt
-            })
-            .cloned()
+    /// Whether the file looks like a PO template (POT)
+    ///
+    /// Templates don't contain translations nor obsolete
+    /// entries. The metadata is not taken into account because
+    /// templates created with [POFile::as_pot] keep the one
+    /// of the translated file, like its `PO-Revision-Date`.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// assert!(!file.is_template());
+    /// assert!(file.as_pot().is_template());
+    /// ```
+    pub fn is_template(&self) -> bool {
+        !self.entries.iter().any(|entry| {
+            entry.obsolete
+                || entry
+                    .msgstr
+                    .as_ref()
+                    .is_some_and(|m| !m.is_empty())
+                || entry.msgstr_plural.iter().any(|m| !m.is_empty())
+        })
     }
 
-    /// Returns the percent of the entries translated in the file
-    pub fn percent_translated(&self) -> f32 {
-        let translated = self.translated_entries().len();
-        let total = self.entries.len();
-        if total == 0 {
-            0.0
-        } else {
-            (translated as f32 / total as f32) * 100.0
-        }
+    /// Sort the entries by `msgctxt` and `msgid`
+    ///
+    /// Both fields are compared using natural order and entries
+    /// without `msgctxt` are placed first. Obsolete entries are
+    /// placed at the end. The sort is stable, so entries with
+    /// the same keys keep their relative order.
+    ///
+    /// ```rust
+    /// use rspolib::{POEntry, POFile};
+    ///
+    /// let mut file = POFile::from(vec![
+    ///     &POEntry::from("item 10"),
+    ///     &POEntry::from("item 9"),
+    /// ]);
+    /// file.sort();
+    /// assert_eq!(file.entries[0].msgid, "item 9");
+    /// assert_eq!(file.entries[1].msgid, "item 10");
+    /// ```
+    pub fn sort(&mut self) {
+        self.sort_by(|a, b| {
+            compare_natural_order(
+                a.msgctxt.as_deref().unwrap_or(""),
+                b.msgctxt.as_deref().unwrap_or(""),
+            )
+            .then_with(|| compare_natural_order(&a.msgid, &b.msgid))
+        });
     }
 
-    /// Returns references to the translated entries of the file
-    pub fn translated_entries(&self) -> Vec<&POEntry> {
-        let mut entries: Vec<&POEntry> = Vec::new();
-        for entry in &self.entries {
-            if entry.translated() {
-                entries.push(entry);
-            }
+    /// Sort the entries with a comparator function
+    ///
+    /// Like [POFile::sort], obsolete entries are placed
+    /// at the end and the sort is stable.
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: Fn(&POEntry, &POEntry) -> Ordering,
+    {
+        self.entries.sort_by(|a, b| {
+            a.obsolete.cmp(&b.obsolete).then_with(|| compare(a, b))
+        });
+        self.mark_dirty();
+    }
+
+    /// Formats the file minimizing the changes between versions
+    ///
+    /// Useful to store PO files in version control systems.
+    /// The output is normalized as follows:
+    ///
+    /// - The `POT-Creation-Date` and `PO-Revision-Date` metadata
+    ///   fields are omitted.
+    /// - The metadata fields are sorted, like when the option
+    ///   [FileOptions::sort_metadata] is `true`.
+    /// - The entries are sorted with [POFile::sort].
+    /// - The strings are wrapped at 78 characters, including
+    ///   those with newlines, ignoring
+    ///   [FileOptions::multiline_style].
+    ///
+    /// The file itself is not modified.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// assert!(!file.to_string_vcs().contains("PO-Revision-Date"));
+    /// ```
+    pub fn to_string_vcs(&self) -> String {
+        let mut file = self.clone();
+        file.metadata.remove("POT-Creation-Date");
+        file.metadata.remove("PO-Revision-Date");
+        file.options.sort_metadata = true;
+        file.options.multiline_style = MultilineStyle::Wrap;
+        file.options.wrapwidth = 78;
+        file.sort();
+        file.to_string()
+    }
+
+    /// Converts the file to a string wrapping the entries at
+    /// the given width instead of [FileOptions::wrapwidth]
+    ///
+    /// A width of `0` disables wrapping. The options of the
+    /// file are not modified.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let unwrapped = file.to_string_with_wrapwidth(0);
+    /// assert!(unwrapped.lines().count() <= file.to_string().lines().count());
+    /// assert_eq!(file.options.wrapwidth, 78);
+    /// ```
+    pub fn to_string_with_wrapwidth(
+        &self,
+        wrapwidth: usize,
+    ) -> String {
+        let header = self.header_to_string();
+        let mut ret = String::with_capacity(
+            header.len() + self.estimated_entries_len(),
+        );
+        ret.push_str(&header);
+
+        // Metadata should not include spaces after values
+        ret.push_str(&po_metadata_entry_to_string(
+            &self.metadata_as_entry(),
+            self.metadata_is_fuzzy,
+        ));
+        ret.push('\n');
+
+        // obsolete entries are placed at the end
+        for obsolete in [false, true] {
+            for entry in &self.entries {
+                if entry.obsolete == obsolete {
+                    ret.push_str(
+                        &self.entry_to_string(entry, wrapwidth),
+                    );
+                    ret.push('\n');
+                }
+            }
         }
-        entries
+        ret.pop();
+        if let Some(ref comments) = self.trailing_comments {
+            ret.push('\n');
+            ret.push_str(&comments_to_string(comments));
+        }
+        ret
     }
 
-    /// Returns references to the untranslated entries of the file
-    pub fn untranslated_entries(&self) -> Vec<&POEntry> {
-        let mut entries: Vec<&POEntry> = Vec::new();
-        for entry in &self.entries {
-            if !entry.translated() {
-                entries.push(entry);
+    /// Saves the file as a PO file to the given path with its
+    /// strings normalized to the NFC form of Unicode
+    ///
+    /// All the strings of the entries, the header, the metadata
+    /// and the trailing comments are composed, so files edited
+    /// with tools that write decomposed characters (NFD) are
+    /// saved in the form used by most tools. This changes the
+    /// bytes of the output but not its content. The file is
+    /// always written as UTF-8 without a byte order mark, which
+    /// is removed when parsing. The file itself is not modified.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, POEntry};
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// file.entries.push(POEntry::from("Cafe\u{301}"));
+    /// let path = "tests-data/docs/save_normalized.po";
+    /// file.save_normalized(path).unwrap();
+    ///
+    /// let saved = pofile(path).unwrap();
+    /// assert!(saved.find_by_msgid("Caf\u{e9}").is_some());
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn save_normalized(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(self.nfc_normalized().to_string().as_bytes())
+    }
+
+    /// Returns a copy of the file with its strings normalized
+    /// to the NFC form
+    #[cfg(feature = "encoding")]
+    fn nfc_normalized(&self) -> POFile {
+        let nfc = |text: &mut String| {
+            *text = text.nfc().collect::<String>();
+        };
+        let nfc_option = |text: &mut Option<String>| {
+            if let Some(text) = text {
+                nfc(text);
+            }
+        };
+
+        let mut file = self.clone();
+        nfc_option(&mut file.header);
+        nfc_option(&mut file.trailing_comments);
+        file.metadata = file
+            .metadata
+            .into_iter()
+            .map(|(mut key, mut value)| {
+                nfc(&mut key);
+                nfc(&mut value);
+                (key, value)
+            })
+            .collect();
+        file.metadata_order.iter_mut().for_each(nfc);
+        for entry in &mut file.entries {
+            nfc(&mut entry.msgid);
+            nfc_option(&mut entry.msgstr);
+            nfc_option(&mut entry.msgid_plural);
+            entry.msgstr_plural.iter_mut().for_each(nfc);
+            nfc_option(&mut entry.msgctxt);
+            nfc_option(&mut entry.comment);
+            nfc_option(&mut entry.tcomment);
+            for (path, linenum) in &mut entry.occurrences {
+                nfc(path);
+                nfc(linenum);
             }
+            entry.flags.iter_mut().for_each(nfc);
+            nfc_option(&mut entry.previous_msgid);
+            nfc_option(&mut entry.previous_msgid_plural);
+            nfc_option(&mut entry.previous_msgctxt);
         }
-        entries
+        file.mark_dirty();
+        file
     }
 
-    /// Returns references to the obsolete entries of the file
-    pub fn obsolete_entries(&self) -> Vec<&POEntry> {
-        let mut entries: Vec<&POEntry> = Vec::new();
-        for entry in &self.entries {
-            if entry.obsolete {
-                entries.push(entry);
+    /// Saves the file as a MO file to the given path with the
+    /// given byte order and revision number
+    ///
+    /// [SaveAsMOFile::save_as_mofile] writes little endian
+    /// files with the revision number 0. The revision number
+    /// is not checked, see [MOFile::as_bytes_with], but the
+    /// plural entries are, like [MOFile::check_plural_forms]
+    /// does, returning an error of kind
+    /// [io::ErrorKind::InvalidData] if they are not consistent.
+    ///
+    /// ```rust
+    /// use rspolib::{
+    ///     mofile, pofile, Endianness, MoRevision, MAGIC_SWAPPED,
+    /// };
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let path = "tests-data/docs/save_as_mofile_with.mo";
+    /// file.save_as_mofile_with(path, Endianness::Big, 1).unwrap();
+    ///
+    /// let saved = mofile(path).unwrap();
+    /// assert_eq!(saved.magic_number, Some(MAGIC_SWAPPED));
+    /// assert_eq!(saved.revision(), MoRevision::Rev1);
+    /// ```
+    pub fn save_as_mofile_with(
+        &self,
+        path: &str,
+        endianness: Endianness,
+        revision: u32,
+    ) -> io::Result<()> {
+        MOFile::from(self).save_with(
+            path,
+            endianness.into(),
+            revision,
+        )
+    }
+
+    /// Saves the file as a PO file to the given path wrapping
+    /// the entries at the given width
+    ///
+    /// See [POFile::to_string_with_wrapwidth].
+    pub fn save_with_wrapwidth(
+        &self,
+        path: &str,
+        wrapwidth: usize,
+    ) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(
+            self.to_string_with_wrapwidth(wrapwidth).as_bytes(),
+        )
+    }
+
+    /// Converts the file to JSON
+    ///
+    /// The JSON object contains the `header`, the `metadata`
+    /// and the `entries` of the file, along with the state of
+    /// each entry, so it can be converted back to the same
+    /// file with [POFile::from_json]. Options are not included.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, POFile};
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let json = file.to_json();
+    /// let file_from_json = POFile::from_json(&json).unwrap();
+    /// assert_eq!(file_from_json.entries, file.entries);
+    /// assert_eq!(file_from_json.to_string(), file.to_string());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        // all the keys are strings, so the serialization can't fail
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Creates a file from the JSON generated by [POFile::to_json]
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<POFile, SyntaxError> {
+        serde_json::from_str(json).map_err(|err| {
+            SyntaxError::BasicCustom {
+                maybe_filename: MaybeFilename::new(json, false),
+                message: format!("invalid JSON: {}", err),
+            }
+        })
+    }
+
+    /// Converts the translations of the file to CSV
+    ///
+    /// The first row contains the names of the columns:
+    /// `msgctxt`, `msgid` and `msgstr`. If the file contains
+    /// plural entries, the columns `msgid_plural` and
+    /// `msgstr[0]`, `msgstr[1]`... are added, as many as
+    /// plural forms have the entry with more of them. Each
+    /// non obsolete entry is written in a row, leaving empty
+    /// the cells of the fields that it doesn't define.
+    ///
+    /// Requires the `csv` feature.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let csv = file.to_csv();
+    /// assert!(csv.starts_with("msgctxt,msgid,msgstr,msgid_plural,msgstr[0]"));
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn to_csv(&self) -> String {
+        let nplurals = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_plural())
+            .map(|entry| entry.plural_count().max(1))
+            .max();
+
+        let mut columns = vec![
+            "msgctxt".to_string(),
+            "msgid".to_string(),
+            "msgstr".to_string(),
+        ];
+        if let Some(nplurals) = nplurals {
+            columns.push("msgid_plural".to_string());
+            columns.extend(
+                (0..nplurals).map(|i| format!("msgstr[{}]", i)),
+            );
+        }
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        // writing to a vector can't fail
+        writer.write_record(&columns).unwrap();
+        for entry in self.entries.iter().filter(|e| !e.obsolete) {
+            let mut record = vec![
+                entry.msgctxt.as_deref().unwrap_or(""),
+                &entry.msgid,
+                entry.msgstr.as_deref().unwrap_or(""),
+            ];
+            if let Some(nplurals) = nplurals {
+                record.push(
+                    entry.msgid_plural.as_deref().unwrap_or(""),
+                );
+                for i in 0..nplurals {
+                    record.push(
+                        entry
+                            .msgstr_plural
+                            .get(i)
+                            .map_or("", String::as_str),
+                    );
+                }
             }
+            writer.write_record(&record).unwrap();
         }
-        entries
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
     }
 
-    /// Returns references to the fuzzy entries of the file
-    pub fn fuzzy_entries(&self) -> Vec<&POEntry> {
-        let mut entries: Vec<&POEntry> = Vec::new();
-        for entry in &self.entries {
-            if entry.fuzzy() && !entry.obsolete {
-                entries.push(entry);
+    /// Creates a file adding to a template the translations
+    /// of a CSV like the one generated by [POFile::to_csv]
+    ///
+    /// The rows are matched with the non obsolete entries of
+    /// the template by their `msgctxt` and `msgid` columns,
+    /// an empty `msgctxt` matching entries without context.
+    /// Only the `msgstr` and `msgstr_plural` fields of the
+    /// matched entries are updated, from the `msgstr` and
+    /// `msgstr[N]` columns respectively. Rows not found in
+    /// the template are ignored.
+    ///
+    /// Requires the `csv` feature.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, POFile};
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let template = file.as_pot();
+    /// let translated = POFile::from_csv(&file.to_csv(), &template).unwrap();
+    /// assert_eq!(
+    ///     translated.find_by_msgid("Afrikaans").unwrap().msgstr.unwrap(),
+    ///     "Africano",
+    /// );
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn from_csv(
+        s: &str,
+        template: &POFile,
+    ) -> Result<POFile, SyntaxError> {
+        let error = |message: String| SyntaxError::BasicCustom {
+            maybe_filename: MaybeFilename::new(s, false),
+            message,
+        };
+
+        let mut reader = csv::Reader::from_reader(s.as_bytes());
+        let headers = reader
+            .headers()
+            .map_err(|err| error(format!("invalid CSV: {}", err)))?
+            .clone();
+        let column =
+            |name: &str| headers.iter().position(|h| h == name);
+        let msgid_column = column("msgid").ok_or_else(|| {
+            error("invalid CSV: msgid column not found".to_string())
+        })?;
+        let msgctxt_column = column("msgctxt");
+        let msgstr_column = column("msgstr");
+        let mut plural_columns = vec![];
+        while let Some(index) =
+            column(&format!("msgstr[{}]", plural_columns.len()))
+        {
+            plural_columns.push(index);
+        }
+
+        let mut file = template.clone();
+        for record in reader.records() {
+            let record = record.map_err(|err| {
+                error(format!("invalid CSV: {}", err))
+            })?;
+            let cell = |index: usize| record.get(index).unwrap_or("");
+
+            let msgid = cell(msgid_column);
+            let msgctxt = msgctxt_column
+                .map(cell)
+                .filter(|msgctxt| !msgctxt.is_empty());
+            let Some(entry) = file.entries.iter_mut().find(|entry| {
+                !entry.obsolete
+                    && entry.msgid == msgid
+                    && entry.msgctxt.as_deref() == msgctxt
+            }) else {
+                continue;
+            };
+
+            if entry.is_plural() {
+                if entry.plural_count() < plural_columns.len() {
+                    entry
+                        .msgstr_plural
+                        .resize(plural_columns.len(), String::new());
+                }
+                for (i, index) in plural_columns.iter().enumerate() {
+                    entry.msgstr_plural[i] = cell(*index).to_string();
+                }
+            } else if let Some(index) = msgstr_column {
+                entry.msgstr = Some(cell(index).to_string());
             }
         }
-        entries
+        file.mark_dirty();
+        Ok(file)
     }
 
-    /// Returns the metadata of the file as an entry.
+    /// Converts the translations of the file to XLIFF 1.2
     ///
-    /// This method is not really useful because the
-    /// ``to_string()`` version will not be guaranteed to be
-    /// correct.
+    /// Each non obsolete entry is written as a `<trans-unit>`
+    /// with the `msgid` as `<source>` and the `msgstr` as
+    /// `<target>`, omitted for untranslated entries. Fuzzy
+    /// entries are not approved and their target is marked as
+    /// `needs-review-translation`. The `msgctxt` is written in
+    /// a `<context-group>` and the comments as `<note>`s.
     ///
-    /// If you want to manipulate the metadata, change
-    /// the content of the field `metadata` in the file.
+    /// Plural entries are written as a `<group>` with a
+    /// `<trans-unit>` for each plural form, the first with the
+    /// `msgid` as source and the rest with the `msgid_plural`.
     ///
-    /// If you still want to render a metadata entry as
-    /// a string, use the function [po_metadata_entry_to_string]:
+    /// Characters not allowed in XML documents, like most
+    /// control characters, are omitted.
     ///
     /// ```rust
-    /// use rspolib::{
-    ///     pofile,
-    ///     po_metadata_entry_to_string,
-    /// };
+    /// use rspolib::pofile;
     ///
-    /// let file = pofile("tests-data/metadata.po").unwrap();
-    /// let entry = file.metadata_as_entry();
-    /// let entry_str = po_metadata_entry_to_string(&entry, true);
-    /// assert!(entry_str.starts_with("#, fuzzy\nmsgid \"\""));
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let xliff = file.to_xliff("en", "es");
+    /// assert!(xliff.contains("<source>Afrikaans</source>"));
+    /// assert!(xliff.contains(r#"<target state="translated">Africano</target>"#));
     /// ```
-    pub fn metadata_as_entry(&self) -> POEntry {
-        let mut entry = POEntry::new(0);
-        if self.metadata_is_fuzzy {
-            entry.flags.push("fuzzy".to_string());
-        }
+    pub fn to_xliff(
+        &self,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> String {
+        let original = match Path::new(&self.options.path_or_content)
+            .is_file()
+        {
+            true => self.options.path_or_content.as_str(),
+            false => "messages.po",
+        };
 
-        if !self.metadata.is_empty() {
-            entry.msgstr =
-                Some(metadata_hashmap_to_msgstr(&self.metadata))
+        xliff::to_xliff(
+            &self.entries,
+            original,
+            source_lang,
+            target_lang,
+        )
+    }
+
+    /// Appends an entry to the file
+    ///
+    /// If the option [FileOptions::check_for_duplicates] is
+    /// enabled and the file already contains an entry with the
+    /// same `msgid` and `msgctxt`, the entry is not appended
+    /// and an error is returned.
+    ///
+    /// ```rust
+    /// use rspolib::{FileOptions, POEntry, POFile};
+    ///
+    /// let mut file = POFile::new(FileOptions {
+    ///     check_for_duplicates: true,
+    ///     ..Default::default()
+    /// });
+    /// assert!(file.append(POEntry::from("Hello")).is_ok());
+    /// assert!(file.append(POEntry::from("Hello")).is_err());
+    /// assert_eq!(file.entries.len(), 1);
+    /// ```
+    pub fn append(
+        &mut self,
+        entry: POEntry,
+    ) -> Result<(), SyntaxError> {
+        if self.options.check_for_duplicates
+            && self.entries.iter().any(|e| {
+                e.msgid == entry.msgid && e.msgctxt == entry.msgctxt
+            })
+        {
+            return Err(SyntaxError::DuplicatedEntry {
+                msgid: entry.msgid,
+                msgctxt: entry.msgctxt,
+            });
         }
+        self.entries.push(entry);
+        self.mark_dirty();
+        Ok(())
+    }
 
-        entry
+    /// Appends the entries converted from an iterator
+    ///
+    /// Duplicated entries are not checked, even if the option
+    /// [FileOptions::check_for_duplicates] is enabled.
+    /// See [IntoPOEntry].
+    ///
+    /// ```rust
+    /// use rspolib::{FileOptions, POFile};
+    ///
+    /// let mut file = POFile::new(FileOptions::default());
+    /// file.extend_from(vec![("Hello", "Hola"), ("Bye", "Adiós")]);
+    /// assert_eq!(file.entries.len(), 2);
+    /// ```
+    pub fn extend_from<I, T>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoPOEntry,
+    {
+        self.entries
+            .extend(iter.into_iter().map(IntoPOEntry::into_poentry));
+        self.mark_dirty();
     }
-}
 
-impl fmt::Display for POFile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut ret: String = match self.header {
-            Some(ref header) => {
-                if header.is_empty() {
-                    "#\n".to_string()
-                } else {
-                    let mut header_repr = String::new();
-                    for line in header.lines() {
-                        if line.is_empty() {
-                            header_repr.push_str("#\n");
-                        } else {
-                            header_repr.reserve(line.len() + 3);
-                            header_repr.push_str("# ");
-                            header_repr.push_str(line);
-                            header_repr.push('\n');
-                        }
-                    }
-                    header_repr
+    /// Merge another PO file into this one
+    ///
+    /// The entries are matched by `msgid` and `msgctxt`. The
+    /// entries found in both files are updated with the fields
+    /// of the other file, like comments, occurrences and flags.
+    /// What happens with the translations and the entries found
+    /// in only one of the files is defined by `options`.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, MergeOptions};
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// let template = pofile("tests-data/all.po").unwrap().as_pot();
+    /// file.merge_with(template, MergeOptions::default());
+    /// assert_eq!(
+    ///     file.translated_entries().len(),
+    ///     pofile("tests-data/all.po").unwrap().translated_entries().len(),
+    /// );
+    /// ```
+    pub fn merge_with(
+        &mut self,
+        other: POFile,
+        options: MergeOptions,
+    ) -> MergeStats {
+        self.merge_entries(
+            other.entries.into_iter().map(Cow::Owned),
+            options,
+        )
+    }
+
+    /// Merge another PO file into this one with the default
+    /// [MergeOptions], returning what has changed
+    ///
+    /// The entries of this file are updated in place and the
+    /// entries of `other` are cloned when needed. See
+    /// [POFile::merge_with].
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, MergeStats};
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// let template = pofile("tests-data/all.po").unwrap().as_pot();
+    /// let stats = file.merge_in_place(&template);
+    /// assert_eq!(
+    ///     stats,
+    ///     MergeStats {
+    ///         added: 0,
+    ///         updated: template.entries.len(),
+    ///         obsoleted: 0,
+    ///     },
+    /// );
+    /// ```
+    pub fn merge_in_place(&mut self, other: &POFile) -> MergeStats {
+        self.merge_entries(
+            other.entries.iter().map(Cow::Borrowed),
+            MergeOptions::default(),
+        )
+    }
+
+    fn merge_entries<'a>(
+        &mut self,
+        other_entries: impl Iterator<Item = Cow<'a, POEntry>>,
+        options: MergeOptions,
+    ) -> MergeStats {
+        let mut stats = MergeStats::default();
+        let mut found = vec![false; self.entries.len()];
+        for other_entry in other_entries {
+            let index = self.entries.iter().position(|e| {
+                e.msgid == other_entry.msgid
+                    && e.msgctxt == other_entry.msgctxt
+            });
+            let Some(index) = index else {
+                if options.add_new {
+                    let mut entry = POEntry::new(0);
+                    entry.merge(other_entry.into_owned());
+                    self.entries.push(entry);
+                    stats.added += 1;
+                }
+                continue;
+            };
+            if let Some(found) = found.get_mut(index) {
+                *found = true;
+            }
+
+            let entry = &mut self.entries[index];
+            let previous =
+                options.merge_comments.then(|| entry.clone());
+            let has_translations = entry
+                .msgstr
+                .as_ref()
+                .is_some_and(|msgstr| !msgstr.is_empty())
+                || entry.msgstr_plural.iter().any(|m| !m.is_empty());
+            let kept_translations = (!options.overwrite_translations
+                && has_translations)
+                .then(|| {
+                    (
+                        entry.msgstr.take(),
+                        std::mem::take(&mut entry.msgstr_plural),
+                        entry.fuzzy(),
+                    )
+                });
+            entry.merge(other_entry.into_owned());
+            if let Some(mut previous) = previous {
+                previous.merge_comments(entry);
+                entry.comment = previous.comment;
+                entry.tcomment = previous.tcomment;
+                entry.occurrences = previous.occurrences;
+                entry.flags = previous.flags;
+            }
+            if let Some((msgstr, msgstr_plural, fuzzy)) =
+                kept_translations
+            {
+                entry.msgstr = msgstr;
+                entry.msgstr_plural = msgstr_plural;
+                if fuzzy {
+                    entry.set_fuzzy(true);
                 }
             }
-            None => "#\n".to_string(),
-        };
+            stats.updated += 1;
+        }
+
+        if options.mark_obsolete {
+            for (entry, found) in self.entries.iter_mut().zip(found) {
+                if !found && !entry.obsolete {
+                    entry.obsolete = true;
+                    stats.obsoleted += 1;
+                }
+            }
+        }
+        self.mark_dirty();
+        stats
+    }
+
+    /// Merge the translations of another PO file into this
+    /// one, reporting the entries changed and in conflict
+    ///
+    /// Meant to combine partial catalogs, the non obsolete
+    /// entries of `other` are matched by `msgid` and `msgctxt`:
+    ///
+    /// - Entries not found in this file are added.
+    /// - Untranslated entries take the translation of `other`,
+    ///   including its `fuzzy` flag.
+    /// - Entries translated differently in both files are
+    ///   reported as conflicts and keep their translation.
+    ///
+    /// The obsolete entries of this file are not matched, so
+    /// the entries of `other` with the same key are added.
+    ///
+    /// ```rust
+    /// use rspolib::{POEntry, POFile};
+    ///
+    /// let mut file = POFile::from(vec![
+    ///     &POEntry::from(("Open", "Abrir")),
+    ///     &POEntry::from("Save"),
+    /// ]);
+    /// let other = POFile::from(vec![
+    ///     &POEntry::from(("Open", "Abre")),
+    ///     &POEntry::from(("Save", "Guardar")),
+    ///     &POEntry::from(("Close", "Cerrar")),
+    /// ]);
+    /// let report = file.merge_report(&other);
+    /// assert_eq!(report.added, vec![("Close".to_string(), None)]);
+    /// assert_eq!(report.updated[0].new_msgstr, vec!["Guardar"]);
+    /// assert_eq!(report.conflicts[0].old_msgstr, vec!["Abrir"]);
+    /// assert_eq!(report.conflicts[0].new_msgstr, vec!["Abre"]);
+    /// ```
+    pub fn merge_report(&mut self, other: &POFile) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut positions: HashMap<(String, Option<String>), usize> =
+            HashMap::with_capacity(self.entries.len());
+        for (position, entry) in self.entries.iter().enumerate() {
+            if entry.obsolete {
+                continue;
+            }
+            positions
+                .entry((entry.msgid.clone(), entry.msgctxt.clone()))
+                .or_insert(position);
+        }
+
+        for other_entry in other.entries.iter() {
+            if other_entry.obsolete {
+                continue;
+            }
+            let key = (
+                other_entry.msgid.clone(),
+                other_entry.msgctxt.clone(),
+            );
+            let Some(&position) = positions.get(&key) else {
+                positions.insert(key.clone(), self.entries.len());
+                self.entries.push(other_entry.clone());
+                report.added.push(key);
+                continue;
+            };
+
+            let entry = &mut self.entries[position];
+            let old_msgstr = translations(entry);
+            let new_msgstr = translations(other_entry);
+            let new_is_empty =
+                new_msgstr.iter().all(String::is_empty);
+            if new_is_empty || old_msgstr == new_msgstr {
+                continue;
+            }
+            let change = TranslationChange {
+                msgid: key.0,
+                msgctxt: key.1,
+                old_msgstr,
+                new_msgstr,
+            };
+            if !change.old_msgstr.iter().all(String::is_empty) {
+                report.conflicts.push(change);
+                continue;
+            }
+            entry.msgstr = other_entry.msgstr.clone();
+            entry.msgstr_plural = other_entry.msgstr_plural.clone();
+            if other_entry.fuzzy() {
+                entry.set_fuzzy(true);
+            }
+            report.updated.push(change);
+        }
+        self.mark_dirty();
+        report
+    }
+
+    /// Remove an entry from the file
+    ///
+    /// Entries are matched with [POEntry::semantic_eq], so
+    /// their line numbers and occurrences are ignored.
+    pub fn remove(&mut self, entry: &POEntry) {
+        self.entries.retain(|e| !e.semantic_eq(entry));
+        self.mark_dirty();
+    }
+
+    /// Remove the first entry that has the same msgid
+    ///
+    /// Returns `true` if an entry has been removed.
+    pub fn remove_by_msgid(&mut self, msgid: &str) -> bool {
+        self.remove_first(|e| e.msgid == msgid)
+    }
+
+    /// Remove the first entry that has the same msgid and msgctxt
+    ///
+    /// An empty `msgctxt` matches entries without context.
+    /// Returns `true` if an entry has been removed.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// let entries_len = file.entries.len();
+    /// assert!(file.remove_by_msgid_msgctxt("Jan.", "abbrev. month"));
+    /// assert!(!file.remove_by_msgid_msgctxt("Jan.", "abbrev. month"));
+    /// assert_eq!(file.entries.len(), entries_len - 1);
+    /// ```
+    pub fn remove_by_msgid_msgctxt(
+        &mut self,
+        msgid: &str,
+        msgctxt: &str,
+    ) -> bool {
+        self.remove_first(|e| {
+            e.msgid == msgid
+                && e.msgctxt.as_deref().unwrap_or("") == msgctxt
+        })
+    }
+
+    /// Removes all the obsolete entries
+    ///
+    /// Returns the number of entries removed.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/obsoletes.po").unwrap();
+    /// let obsoletes = file.obsolete_entries().len();
+    /// assert_eq!(file.compact(), obsoletes);
+    /// assert!(file.obsolete_entries().is_empty());
+    /// ```
+    pub fn compact(&mut self) -> usize {
+        self.remove_all(|e| e.obsolete)
+    }
+
+    /// Removes the entries that are not translated, keeping the
+    /// obsolete ones
+    ///
+    /// Fuzzy entries and plural entries with some empty
+    /// translation are removed too, as they are not included
+    /// in MO files. Use [POFile::compact] to remove the
+    /// obsolete entries. Returns the number of entries removed.
+    pub fn remove_untranslated(&mut self) -> usize {
+        self.remove_all(|e| !e.obsolete && !e.translated())
+    }
+
+    /// Merges the entries with the same `msgid` and `msgctxt`
+    ///
+    /// The first entry of each `msgid` and `msgctxt` is kept,
+    /// taking the occurrences and flags of its duplicates,
+    /// which are removed. The `fuzzy` flag is not taken, as
+    /// it refers to the discarded translations. Entries with different `msgctxt` are
+    /// not duplicates, and neither are an obsolete entry and a
+    /// non obsolete one. Returns the number of entries removed.
+    ///
+    /// Unlike [FileOptions::check_for_duplicates], which
+    /// prevents adding duplicates, this method cleans up a
+    /// file that already contains them.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile(concat!(
+    ///     "#: a.rs:1\n",
+    ///     "msgid \"Hello\"\n",
+    ///     "msgstr \"Hola\"\n",
+    ///     "\n",
+    ///     "#: b.rs:2\n",
+    ///     "msgid \"Hello\"\n",
+    ///     "msgstr \"Buenas\"\n",
+    /// ))
+    /// .unwrap();
+    /// assert_eq!(file.dedup(), 1);
+    /// assert_eq!(file.entries.len(), 1);
+    /// assert_eq!(file.entries[0].msgstr.as_deref(), Some("Hola"));
+    /// assert_eq!(file.entries[0].occurrences.len(), 2);
+    /// ```
+    pub fn dedup(&mut self) -> usize {
+        let len = self.entries.len();
+        let mut positions: HashMap<
+            (String, Option<String>, bool),
+            usize,
+        > = HashMap::with_capacity(len);
+        let mut entries: Vec<POEntry> = Vec::with_capacity(len);
+        for entry in std::mem::take(&mut self.entries) {
+            let key = (
+                entry.msgid.clone(),
+                entry.msgctxt.clone(),
+                entry.obsolete,
+            );
+            let Some(&position) = positions.get(&key) else {
+                positions.insert(key, entries.len());
+                entries.push(entry);
+                continue;
+            };
+            let first = &mut entries[position];
+            for (path, linenum) in &entry.occurrences {
+                first.add_occurrence(path, linenum);
+            }
+            // fuzzy describes the translation of the duplicate,
+            // which is discarded
+            for flag in entry.flags.iter().filter(|f| *f != "fuzzy") {
+                first.add_flag(flag);
+            }
+        }
+        self.entries = entries;
+
+        let removed = len - self.entries.len();
+        if removed > 0 {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    /// Removes all the entries that match the predicate,
+    /// returning how many have been removed
+    ///
+    /// Entries with an empty `msgid` and without `msgctxt`
+    /// are never removed because they define the metadata.
+    fn remove_all<F>(&mut self, predicate: F) -> usize
+    where
+        F: Fn(&POEntry) -> bool,
+    {
+        let len = self.entries.len();
+        self.entries.retain(|e| {
+            (e.msgid.is_empty() && e.msgctxt.is_none())
+                || !predicate(e)
+        });
+        let removed = len - self.entries.len();
+        if removed > 0 {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    fn remove_first<F>(&mut self, predicate: F) -> bool
+    where
+        F: Fn(&POEntry) -> bool,
+    {
+        match self.entries.iter().position(predicate) {
+            Some(index) => {
+                self.entries.remove(index);
+                self.mark_dirty();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Invalidates the cached number of translated entries
+    /// and the index built by [POFile::build_index]
+    ///
+    /// The methods of the file that modify its entries, like
+    /// [POFile::entries_mut], already invalidate them, but the
+    /// entries can be edited directly through the public
+    /// `entries` field. Call this method after doing it so
+    /// [POFile::percent_translated] is computed again and the
+    /// lookups don't use a stale index.
+    pub fn mark_dirty(&mut self) {
+        self.translated_count.invalidate();
+        self.index.0 = None;
+    }
+
+    /// Returns the entries of the file to modify them
+    ///
+    /// Unlike editing the public `entries` field, this drops
+    /// the cached data derived from the entries, like
+    /// [POFile::mark_dirty] does.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// file.build_index();
+    /// file.entries_mut()[0].msgid = "Changed".to_string();
+    /// assert!(file.find_by_msgid("Changed").is_some());
+    /// ```
+    pub fn entries_mut(&mut self) -> &mut Vec<POEntry> {
+        self.mark_dirty();
+        &mut self.entries
+    }
+
+    /// Builds an index of the entries for fast lookups
+    ///
+    /// Once built, [POFile::find_by_msgid] and
+    /// [POFile::find_by_msgid_msgctxt] look up the entries in
+    /// constant time instead of scanning them. The index is
+    /// dropped when the entries are modified through the
+    /// methods of the file, [POFile::entries_mut] or
+    /// [POFile::mark_dirty], falling back to the scan until it
+    /// is built again.
+    ///
+    /// Editing the public `entries` field in place is not
+    /// detected, so the lookups could return stale results
+    /// until [POFile::mark_dirty] is called.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/all.po").unwrap();
+    /// file.build_index();
+    /// let entry = file.find_by_msgid_msgctxt("Jan.", "abbrev. month");
+    /// assert_eq!(entry.unwrap().msgstr.as_deref(), Some("Ene."));
+    /// ```
+    pub fn build_index(&mut self) {
+        let mut by_msgid = HashMap::with_capacity(self.entries.len());
+        let mut by_msgid_eot_msgctxt =
+            HashMap::with_capacity(self.entries.len());
+        for (position, entry) in self.entries.iter().enumerate() {
+            by_msgid.entry(entry.msgid.clone()).or_insert(position);
+            by_msgid_eot_msgctxt
+                .entry(entry.msgid_eot_msgctxt())
+                .or_insert(position);
+        }
+        self.index.0 = Some(Index {
+            len: self.entries.len(),
+            by_msgid,
+            by_msgid_eot_msgctxt,
+        });
+    }
+
+    /// Returns the index if the number of entries didn't
+    /// change since it was built
+    fn valid_index(&self) -> Option<&Index> {
+        self.index
+            .0
+            .as_ref()
+            .filter(|index| index.len == self.entries.len())
+    }
+
+    /// Find entries by a given field and value
+    ///
+    /// The field defined in the `by` argument can be one of:
+    ///
+    /// * `msgid`
+    /// * `msgstr`
+    /// * `msgctxt`
+    /// * `msgid_plural`
+    /// * `previous_msgid`
+    /// * `previous_msgid_plural`
+    /// * `previous_msgctxt`
+    ///
+    /// Passing the optional `msgctxt` argument the entry
+    /// will also must match with the given context.
+    ///
+    /// If `include_obsolete_entries` is set to `true` the
+    /// search will include obsolete entries.
+    ///
+    /// See [POFile::find_by_field] to select the field with
+    /// a [ByField].
+    pub fn find(
+        &self,
+        value: &str,
+        by: &str,
+        msgctxt: Option<&str>,
+        include_obsolete_entries: bool,
+    ) -> Vec<&POEntry> {
+        match ByField::from_name(by) {
+            Some(by) => self.find_entries(
+                value,
+                by,
+                msgctxt,
+                include_obsolete_entries,
+            ),
+            None => vec![],
+        }
+    }
+
+    /// Find the non obsolete entries by a given field and value
+    ///
+    /// Passing the optional `msgctxt` argument the entries
+    /// must also match with the given context.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, ByField};
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let entries = file.find_by_field("Ene.", ByField::Msgstr, None);
+    /// assert_eq!(entries[0].msgid, "Jan.");
+    /// ```
+    pub fn find_by_field(
+        &self,
+        value: &str,
+        by: ByField,
+        msgctxt: Option<&str>,
+    ) -> Vec<&POEntry> {
+        self.find_entries(value, by, msgctxt, false)
+    }
+
+    fn find_entries(
+        &self,
+        value: &str,
+        by: ByField,
+        msgctxt: Option<&str>,
+        include_obsolete_entries: bool,
+    ) -> Vec<&POEntry> {
+        let mut entries: Vec<&POEntry> = Vec::new();
+
+        let msgctxt_predicate: &dyn Fn(&POEntry, &str) -> bool =
+            match msgctxt {
+                Some(_) => &msgctxt_predicate,
+                None => &empty_msgctxt_predicate,
+            };
+        let by_predicate = by.predicate();
+
+        for entry in &self.entries {
+            if !include_obsolete_entries && entry.obsolete {
+                continue;
+            }
+            if by_predicate(entry, value)
+                && msgctxt_predicate(entry, msgctxt.unwrap_or(""))
+            {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+
+    /// Find an entry by his msgid
+    ///
+    /// Uses the index built by [POFile::build_index], if any.
+    pub fn find_by_msgid(&self, msgid: &str) -> Option<POEntry> {
+        if let Some(index) = self.valid_index() {
+            let entry = index
+                .by_msgid
+                .get(msgid)
+                .map(|position| &self.entries[*position]);
+            if entry.is_none_or(|e| e.msgid == msgid) {
+                return entry.cloned();
+            }
+        }
+        self.entries.iter().find(|e| e.msgid == msgid).cloned()
+    }
+
+    /// Find an entry by msgid and msgctxt
+    ///
+    /// An empty `msgctxt` also matches the entries without
+    /// context. Uses the index built by [POFile::build_index],
+    /// if any.
+    pub fn find_by_msgid_msgctxt(
+        &self,
+        msgid: &str,
+        msgctxt: &str,
+    ) -> Option<POEntry> {
+        if let Some(index) = self.valid_index() {
+            let mut positions = vec![index.by_msgid_eot_msgctxt.get(
+                maybe_msgid_msgctxt_eot_split(
+                    msgid,
+                    &Some(msgctxt.to_string()),
+                )
+                .as_ref(),
+            )];
+            if msgctxt.is_empty() {
+                positions.push(index.by_msgid_eot_msgctxt.get(msgid));
+            }
+            let entry = positions
+                .into_iter()
+                .flatten()
+                .min()
+                .map(|position| &self.entries[*position]);
+            if entry.is_none_or(|e| {
+                e.msgid == msgid
+                    && e.msgctxt.as_deref().unwrap_or("") == msgctxt
+            }) {
+                return entry.cloned();
+            }
+        }
+        self.entries
+            .iter()
+            .find(|e| {
+                e.msgid == msgid
+                    && e.msgctxt.as_ref().unwrap_or(&"".to_string())
+                        == msgctxt
+            })
+            .cloned()
+    }
+
+    /// Returns the percent of the entries translated in the file
+    ///
+    /// Obsolete entries are not taken into account, like polib
+    /// does. The number of translated entries is cached until
+    /// the entries are modified through the methods of the
+    /// file, like [POFile::entries_mut]. Edits made directly in
+    /// the public `entries` field are not detected, call
+    /// [POFile::mark_dirty] after them.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/2-translated-entries.po").unwrap();
+    /// assert_eq!(file.percent_translated(), 40.0);
+    /// ```
+    pub fn percent_translated(&self) -> f32 {
+        let count = self.entries_count();
+        let total = count.total - count.obsolete;
+        if total == 0 {
+            return 0.0;
+        }
+        (count.translated as f32 / total as f32) * 100.0
+    }
+
+    /// Returns the percent of the entries translated in the
+    /// file, counting obsolete entries as untranslated
+    ///
+    /// This was the behaviour of [POFile::percent_translated]
+    /// before obsolete entries were excluded from it.
+    pub fn percent_translated_including_obsolete(&self) -> f32 {
+        let count = self.entries_count();
+        if count.total == 0 {
+            return 0.0;
+        }
+        (count.translated as f32 / count.total as f32) * 100.0
+    }
+
+    /// Checks the entries of the file for common problems
+    ///
+    /// Returns a warning for each problem found in the
+    /// non obsolete entries:
+    ///
+    /// - [`Fuzzy`](crate::ValidationCategory::Fuzzy): the entry is fuzzy.
+    /// - [`IncompleteTranslation`](crate::ValidationCategory::IncompleteTranslation): some
+    ///   plural forms are translated but others are empty.
+    /// - [`PlaceholderMismatch`](crate::ValidationCategory::PlaceholderMismatch): the
+    ///   `printf` (`%s`) or brace (`{name}`) placeholders of
+    ///   the translation don't match the ones of the msgid.
+    /// - [`PluralFormsMismatch`](crate::ValidationCategory::PluralFormsMismatch): the number
+    ///   of plural forms doesn't match the `nplurals` of the
+    ///   `Plural-Forms` header.
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, ValidationCategory};
+    ///
+    /// let file = pofile("tests-data/validation.po").unwrap();
+    /// let warnings = file.validate();
+    /// assert_eq!(warnings[0].category, ValidationCategory::Fuzzy);
+    /// assert_eq!(
+    ///     warnings[0].to_string(),
+    ///     "line 9: entry \"Goodbye\" is fuzzy",
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let nplurals = self
+            .metadata
+            .get("Plural-Forms")
+            .and_then(|value| plurals::nplurals(value));
+        self.entries
+            .iter()
+            .filter(|entry| !entry.obsolete)
+            .flat_map(|entry| validate_entry(entry, nplurals))
+            .collect()
+    }
+
+    /// Returns the number of entries, words and characters of
+    /// the file, useful to estimate the translation effort
+    ///
+    /// See [WordCount].
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, WordCount};
+    ///
+    /// let file = pofile("tests-data/2-translated-entries.po").unwrap();
+    /// assert_eq!(
+    ///     file.word_count(),
+    ///     WordCount {
+    ///         entries: 5,
+    ///         translated_entries: 2,
+    ///         source_words: 10,
+    ///         translated_words: 4,
+    ///         source_chars: 35,
+    ///         translated_chars: 16,
+    ///     },
+    /// );
+    /// ```
+    pub fn word_count(&self) -> WordCount {
+        let mut count = WordCount::default();
+        for entry in self.entries.iter().filter(|e| !e.obsolete) {
+            count_words(&mut count, entry);
+        }
+        count
+    }
+
+    /// Returns the counts of entries and words of the file
+    ///
+    /// Computed in a single pass over the entries, so it's
+    /// cheaper than calling the methods for each count.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/2-translated-entries.po").unwrap();
+    /// let stats = file.statistics();
+    /// assert_eq!(stats.translated, 2);
+    /// assert_eq!(stats.untranslated, 3);
+    /// assert_eq!(stats.percent_translated, 40.0);
+    /// assert_eq!(stats.source_words, 10);
+    /// ```
+    pub fn statistics(&self) -> Statistics {
+        let mut stats = Statistics {
+            total: self.entries.len(),
+            ..Default::default()
+        };
+        let mut words = WordCount::default();
+        for entry in &self.entries {
+            if entry.obsolete {
+                stats.obsolete += 1;
+                continue;
+            }
+            if entry.translated() {
+                stats.translated += 1;
+            } else if entry.fuzzy() {
+                stats.fuzzy += 1;
+            } else {
+                stats.untranslated += 1;
+            }
+            count_words(&mut words, entry);
+        }
+
+        let total = stats.total - stats.obsolete;
+        if total > 0 {
+            stats.percent_translated =
+                (stats.translated as f32 / total as f32) * 100.0;
+        }
+        stats.source_words = words.source_words;
+        stats.translated_words = words.translated_words;
+        stats
+    }
+
+    /// Returns the number of words of the messages of the non
+    /// obsolete entries
+    ///
+    /// Shortcut for `word_count().source_words`. See
+    /// [POFile::word_count].
+    pub fn source_word_count(&self) -> usize {
+        self.word_count().source_words
+    }
+
+    /// Returns the number of words of the translations of the
+    /// translated entries, excluding obsolete ones
+    ///
+    /// Shortcut for `word_count().translated_words`. See
+    /// [POFile::word_count].
+    pub fn translated_word_count(&self) -> usize {
+        self.word_count().translated_words
+    }
+
+    /// Returns the number of characters of the messages of the
+    /// non obsolete entries
+    ///
+    /// Shortcut for `word_count().source_chars`. See
+    /// [POFile::word_count].
+    pub fn char_count(&self) -> usize {
+        self.word_count().source_chars
+    }
+
+    fn entries_count(&self) -> EntriesCount {
+        let total = self.entries.len();
+        let mut cache = self.translated_count.lock();
+        match *cache {
+            Some(count) if count.total == total => count,
+            _ => {
+                let count = EntriesCount {
+                    total,
+                    translated: self.iter_translated().count(),
+                    obsolete: self.iter_obsolete().count(),
+                };
+                *cache = Some(count);
+                count
+            }
+        }
+    }
+
+    /// Returns an iterator over the entries of the file
+    pub fn iter(&self) -> impl Iterator<Item = &POEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the translated entries
+    ///
+    /// Unlike [POFile::translated_entries], the entries are
+    /// not collected, so it's cheaper when only counting or
+    /// searching them.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// assert_eq!(
+    ///     file.iter_translated().count(),
+    ///     file.translated_entries().len(),
+    /// );
+    /// ```
+    pub fn iter_translated(&self) -> impl Iterator<Item = &POEntry> {
+        self.entries.iter().filter(|entry| entry.translated())
+    }
+
+    /// Returns an iterator over the untranslated entries
+    pub fn iter_untranslated(
+        &self,
+    ) -> impl Iterator<Item = &POEntry> {
+        self.entries.iter().filter(|entry| !entry.translated())
+    }
+
+    /// Returns an iterator over the obsolete entries
+    pub fn iter_obsolete(&self) -> impl Iterator<Item = &POEntry> {
+        self.entries.iter().filter(|entry| entry.obsolete)
+    }
+
+    /// Returns an iterator over the fuzzy entries, excluding
+    /// the obsolete ones
+    pub fn iter_fuzzy(&self) -> impl Iterator<Item = &POEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.fuzzy() && !entry.obsolete)
+    }
+
+    /// Returns an iterator over the entries with the given
+    /// flag, excluding the obsolete ones
+    ///
+    /// The flag must match exactly, so `c-format` does not
+    /// match `no-c-format`.
+    pub fn iter_by_flag<'a: 'b, 'b>(
+        &'a self,
+        flag: &'b str,
+    ) -> impl Iterator<Item = &'a POEntry> + 'b {
+        self.entries.iter().filter(move |entry| {
+            !entry.obsolete && entry.flags.iter().any(|f| f == flag)
+        })
+    }
+
+    /// Returns references to the translated entries of the file
+    pub fn translated_entries(&self) -> Vec<&POEntry> {
+        self.iter_translated().collect()
+    }
+
+    /// Returns references to the untranslated entries of the file
+    pub fn untranslated_entries(&self) -> Vec<&POEntry> {
+        self.iter_untranslated().collect()
+    }
+
+    /// Returns references to the obsolete entries of the file
+    pub fn obsolete_entries(&self) -> Vec<&POEntry> {
+        self.iter_obsolete().collect()
+    }
+
+    /// Returns references to the fuzzy entries of the file
+    pub fn fuzzy_entries(&self) -> Vec<&POEntry> {
+        self.iter_fuzzy().collect()
+    }
+
+    /// Returns references to the entries of the file with the
+    /// given flag
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/format-flags.po").unwrap();
+    /// let entries = file.entries_by_flag("no-wrap");
+    /// assert_eq!(entries.len(), 1);
+    /// assert_eq!(entries[0].msgid, "Unwrapped entry");
+    /// ```
+    pub fn entries_by_flag(&self, flag: &str) -> Vec<&POEntry> {
+        self.iter_by_flag(flag).collect()
+    }
+
+    /// Returns the number of translated entries of the file
+    ///
+    /// Like `translated_entries().len()`, but without
+    /// allocating a vector of references.
+    pub fn translated_count(&self) -> usize {
+        self.iter_translated().count()
+    }
+
+    /// Returns the number of untranslated entries of the file
+    pub fn untranslated_count(&self) -> usize {
+        self.iter_untranslated().count()
+    }
+
+    /// Returns the number of obsolete entries of the file
+    pub fn obsolete_count(&self) -> usize {
+        self.iter_obsolete().count()
+    }
+
+    /// Returns the number of fuzzy entries of the file,
+    /// excluding the obsolete ones
+    pub fn fuzzy_count(&self) -> usize {
+        self.iter_fuzzy().count()
+    }
+
+    /// Returns the plural translation of a message for a number
+    ///
+    /// The index of the plural form is computed evaluating the
+    /// `plural` expression of the `Plural-Forms` metadata field.
+    /// If the field is not defined or it can't be evaluated, the
+    /// first plural form is returned. Returns `None` if there is
+    /// no entry with plural forms for the `msgid`.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let msgid = "Ensure that there are no more than %(max)s digit in total.";
+    /// assert_eq!(
+    ///     file.gettext_plural(msgid, 1),
+    ///     Some("Asegúrese de que no hay más de %(max)s dígito en total."),
+    /// );
+    /// assert_eq!(
+    ///     file.gettext_plural(msgid, 3),
+    ///     Some("Asegúrese de que no haya más de %(max)s dígitos en total."),
+    /// );
+    /// ```
+    pub fn gettext_plural(
+        &self,
+        msgid: &str,
+        n: u64,
+    ) -> Option<&str> {
+        let entry = self.entries.iter().find(|e| {
+            !e.obsolete
+                && e.msgid == msgid
+                && !e.msgstr_plural.is_empty()
+        })?;
+        let index = self
+            .metadata
+            .get("Plural-Forms")
+            .and_then(|plural_forms| {
+                plurals::plural_index(plural_forms, n)
+            })
+            .filter(|index| *index < entry.plural_count())
+            .unwrap_or(0);
+        Some(&entry.msgstr_plural[index])
+    }
+
+    /// Sets up the plural forms of the file for a language
+    ///
+    /// The `Plural-Forms` metadata field is set to the plural
+    /// forms of the language, like `ru` or `pt_BR`, and the
+    /// translations of every plural entry are truncated or
+    /// padded with empty strings to the new number of plural
+    /// forms. Returns `false` without modifying the file if
+    /// the language is unknown.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/msgid-plural.po").unwrap();
+    /// assert!(file.apply_language_plurals("ja"));
+    /// assert_eq!(file.metadata["Plural-Forms"], "nplurals=1; plural=0;");
+    /// assert_eq!(file.entries[0].msgstr_plural.len(), 1);
+    /// ```
+    pub fn apply_language_plurals(&mut self, language: &str) -> bool {
+        let Some(plural_forms) =
+            plurals::language_plural_forms(language)
+        else {
+            return false;
+        };
+        let nplurals = plurals::nplurals(plural_forms).unwrap_or(1);
+
+        self.insert_metadata("Plural-Forms", plural_forms);
+        for entry in &mut self.entries {
+            if entry.is_plural() {
+                entry.msgstr_plural.resize(nplurals, String::new());
+            }
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// Sets the standard metadata fields of a new translation
+    ///
+    /// All the standard fields are defined, in their canonical
+    /// order. The dates are set to the gettext placeholder
+    /// `YEAR-MO-DA HO:MI+ZONE`, the `MIME-Version` to `1.0` and
+    /// the `Content-Transfer-Encoding` to `8bit`. Existing
+    /// non standard fields are kept.
+    ///
+    /// Returns an error, without modifying the metadata, if
+    /// [HeaderInit::plural_forms] is not valid. See
+    /// [crate::parse_plural_forms].
+    ///
+    /// ```rust
+    /// use rspolib::{FileOptions, HeaderInit, POFile};
+    ///
+    /// let mut file = POFile::new(FileOptions::default());
+    /// file.init_header(HeaderInit {
+    ///     project: "foo 1.0".to_string(),
+    ///     language: "es".to_string(),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// assert_eq!(
+    ///     file.metadata["Content-Type"],
+    ///     "text/plain; charset=UTF-8",
+    /// );
+    /// ```
+    pub fn init_header(
+        &mut self,
+        init: HeaderInit,
+    ) -> Result<(), SyntaxError> {
+        plurals::parse_plural_forms(&init.plural_forms)?;
+
+        let date = "YEAR-MO-DA HO:MI+ZONE";
+        let content_type =
+            format!("text/plain; charset={}", init.charset);
+        let fields = [
+            ("Project-Id-Version", init.project.as_str()),
+            ("Report-Msgid-Bugs-To", ""),
+            ("POT-Creation-Date", date),
+            ("PO-Revision-Date", date),
+            ("Last-Translator", init.last_translator.as_str()),
+            ("Language-Team", ""),
+            ("Language", init.language.as_str()),
+            ("MIME-Version", "1.0"),
+            ("Content-Type", content_type.as_str()),
+            ("Content-Transfer-Encoding", "8bit"),
+            ("Plural-Forms", init.plural_forms.as_str()),
+        ];
+
+        let mut metadata_order: Vec<String> = METADATA_KEYS_ORDER
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        for key in &self.metadata_order {
+            if !metadata_order.contains(key) {
+                metadata_order.push(key.clone());
+            }
+        }
+        self.metadata_order = metadata_order;
+        for (key, value) in fields {
+            self.insert_metadata(key, value);
+        }
+        Ok(())
+    }
+
+    /// Sets the order in which the metadata keys are formatted
+    ///
+    /// Keys not included in `keys` are placed after the listed
+    /// ones, sorted in natural order. This replaces the order
+    /// of the standard gettext keys, which can be useful to
+    /// reproduce the output of other tools.
+    ///
+    /// Stored in [FileOptions::metadata_keys_order], enabling
+    /// [FileOptions::sort_metadata].
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/metadata.po").unwrap();
+    /// file.set_metadata_order(vec![
+    ///     "Language".to_string(),
+    ///     "Content-Type".to_string(),
+    /// ]);
+    /// let msgstr = file.metadata_as_entry().msgstr.unwrap();
+    /// assert!(msgstr.starts_with(
+    ///     "Language: es\nContent-Type: text/plain; charset=UTF-8\n\
+    ///      Content-Transfer-Encoding: 8bit\n"
+    /// ));
+    /// ```
+    pub fn set_metadata_order(&mut self, keys: Vec<String>) {
+        self.options.sort_metadata = true;
+        self.options.metadata_keys_order = Some(keys);
+    }
+
+    /// Returns the value of a metadata field, if defined
+    pub fn metadata_get(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Sets the value of a metadata field
+    ///
+    /// New standard fields are placed in
+    /// [POFile::metadata_order] following the order in which
+    /// gettext writes them, and new non standard fields are
+    /// placed at the end. The value of `Plural-Forms` is
+    /// validated with [crate::parse_plural_forms] before
+    /// setting it.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let mut file = pofile("tests-data/metadata.po").unwrap();
+    /// file.metadata_set("Language", "fr").unwrap();
+    /// assert_eq!(file.metadata_get("Language"), Some("fr"));
+    ///
+    /// assert!(file.metadata_set("Plural-Forms", "plural=n;").is_err());
+    /// ```
+    pub fn metadata_set(
+        &mut self,
+        key: &str,
+        value: &str,
+    ) -> Result<(), SyntaxError> {
+        if key == "Plural-Forms" {
+            plurals::parse_plural_forms(value)?;
+        }
+        self.insert_metadata(key, value);
+        Ok(())
+    }
+
+    /// Removes a metadata field, returning its value if it
+    /// was defined
+    pub fn metadata_remove(&mut self, key: &str) -> Option<String> {
+        self.metadata_order.retain(|k| k != key);
+        self.metadata.remove(key)
+    }
+
+    fn insert_metadata(&mut self, key: &str, value: &str) {
+        if !self.metadata_order.iter().any(|k| k == key) {
+            let canonical_index = |key: &str| {
+                METADATA_KEYS_ORDER.iter().position(|k| *k == key)
+            };
+            let position = canonical_index(key).and_then(|index| {
+                self.metadata_order.iter().position(|k| {
+                    canonical_index(k).is_some_and(|i| i > index)
+                })
+            });
+            match position {
+                Some(position) => self
+                    .metadata_order
+                    .insert(position, key.to_string()),
+                None => self.metadata_order.push(key.to_string()),
+            }
+        }
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
+    /// Returns the contributor defined in the `Last-Translator`
+    /// metadata field, if any
+    pub fn last_translator(&self) -> Option<Contributor> {
+        Contributor::parse(self.metadata.get("Last-Translator")?)
+    }
+
+    /// Returns the contributor defined in the `Language-Team`
+    /// metadata field, if any
+    pub fn language_team(&self) -> Option<Contributor> {
+        Contributor::parse(self.metadata.get("Language-Team")?)
+    }
+
+    /// Returns the translators listed in the header
+    ///
+    /// Follows the convention of listing the translators in
+    /// the header after a `Translators:` line, one per line,
+    /// until an empty line is found:
+    ///
+    /// ```text
+    /// # Translators:
+    /// # John Doe <john@example.com>, 2020
+    /// # Jane Doe <jane@example.com>, 2021, 2022
+    /// ```
+    pub fn translators(&self) -> Vec<Contributor> {
+        let mut translators = Vec::new();
+        let header = match &self.header {
+            Some(header) => header,
+            None => return translators,
+        };
+        let mut lines = header.lines().map(|l| l.trim());
+        if lines.by_ref().any(|l| l == "Translators:") {
+            for line in lines {
+                match Contributor::parse(line) {
+                    Some(contributor) => {
+                        translators.push(contributor)
+                    }
+                    None => break,
+                }
+            }
+        }
+        translators
+    }
+
+    /// Returns a new file to be reviewed by translators
+    ///
+    /// The file contains only the fuzzy and untranslated
+    /// entries of the current one, excluding obsolete entries.
+    /// Entries are copied as is, so the previous fields like
+    /// `previous_msgid`, the comments and the occurrences are
+    /// preserved to give context to translators about what
+    /// has changed. The header and metadata are also copied.
+    pub fn review_export(&self) -> POFile {
+        self.filtered(|entry| !entry.obsolete && !entry.translated())
+    }
+
+    /// Returns a new file with the entries of the current one
+    /// not found in `other`
+    ///
+    /// Entries are matched by their `msgid` and `msgctxt`,
+    /// including obsolete ones, and copied as is. The header
+    /// and metadata of the current file are also copied.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let other = pofile("tests-data/2-translated-entries.po").unwrap();
+    /// let difference = file.difference(&other);
+    /// assert_eq!(difference.entries.len(), file.entries.len());
+    /// assert_eq!(difference.metadata, file.metadata);
+    /// assert!(file.difference(&file).entries.is_empty());
+    /// ```
+    pub fn difference(&self, other: &POFile) -> POFile {
+        let keys = other.entries_keys();
+        self.filtered(|entry| !keys.contains(&entry_key(entry)))
+    }
+
+    /// Returns a new file with the entries of the current one
+    /// also found in `other`
+    ///
+    /// Entries are matched by their `msgid` and `msgctxt`,
+    /// including obsolete ones, and copied from the current
+    /// file as is. The header and metadata of the current file
+    /// are also copied.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let mut other = file.clone();
+    /// other.entries.truncate(2);
+    /// let intersection = file.intersection(&other);
+    /// assert_eq!(intersection.entries, other.entries);
+    /// ```
+    pub fn intersection(&self, other: &POFile) -> POFile {
+        let keys = other.entries_keys();
+        self.filtered(|entry| keys.contains(&entry_key(entry)))
+    }
+
+    fn entries_keys(&self) -> HashSet<(&str, Option<&str>)> {
+        self.entries.iter().map(entry_key).collect()
+    }
+
+    /// Returns a copy of the file with only the entries for
+    /// which `keep` returns `true`
+    fn filtered(&self, keep: impl Fn(&POEntry) -> bool) -> POFile {
+        let mut file = POFile::new(self.options.clone());
+        file.header = self.header.clone();
+        file.trailing_comments = self.trailing_comments.clone();
+        file.metadata = self.metadata.clone();
+        file.metadata_order = self.metadata_order.clone();
+        file.encoding = self.encoding.clone();
+        file.metadata_is_fuzzy = self.metadata_is_fuzzy;
+        file.entries = self
+            .entries
+            .iter()
+            .filter(|e| keep(e))
+            .cloned()
+            .collect();
+        file
+    }
+
+    /// Returns references to the entries whose translations
+    /// look like double encoded UTF-8 (mojibake)
+    ///
+    /// For example, an entry with the msgstr `CafÃ©` is
+    /// returned because `Café` has been decoded as Latin-1
+    /// and encoded again as UTF-8.
+    pub fn detect_mojibake(&self) -> Vec<&POEntry> {
+        let mut entries: Vec<&POEntry> = Vec::new();
+        for entry in &self.entries {
+            let msgstr_has_mojibake = match &entry.msgstr {
+                Some(msgstr) => mojibake::repair(msgstr).is_some(),
+                None => false,
+            };
+            if msgstr_has_mojibake
+                || entry
+                    .msgstr_plural
+                    .iter()
+                    .any(|m| mojibake::repair(m).is_some())
+            {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+
+    /// Repair the translations of the entries detected by
+    /// [POFile::detect_mojibake]
+    ///
+    /// The repair is heuristic: each character of the
+    /// translation is converted back to its Latin-1 or
+    /// Windows-1252 byte and the bytes are decoded as UTF-8.
+    /// Translations that can't be decoded are left untouched,
+    /// but a legit translation that happens to be valid UTF-8
+    /// when encoded as Latin-1 would be replaced too, so review
+    /// the results before saving the file.
+    ///
+    /// ```rust
+    /// use rspolib::{POEntry, POFile};
+    ///
+    /// let entry = POEntry::from(("Coffee", "CafÃ©"));
+    /// let mut file = POFile::from(vec![&entry]);
+    /// file.repair_mojibake();
+    /// assert_eq!(file.entries[0].msgstr.as_ref().unwrap(), "Café");
+    /// ```
+    pub fn repair_mojibake(&mut self) {
+        for entry in &mut self.entries {
+            if let Some(msgstr) = &entry.msgstr {
+                if let Some(repaired) = mojibake::repair(msgstr) {
+                    entry.msgstr = Some(repaired);
+                }
+            }
+            for msgstr in &mut entry.msgstr_plural {
+                if let Some(repaired) = mojibake::repair(msgstr) {
+                    *msgstr = repaired;
+                }
+            }
+        }
+    }
+
+    /// Suggest translations for the untranslated entries from
+    /// a translation memory
+    ///
+    /// For each untranslated entry that is not fuzzy nor
+    /// obsolete, the translated entry of `memory` whose `msgid`
+    /// is the most similar according to [levenshtein_ratio] is
+    /// searched. If its similarity is at least `threshold`,
+    /// its translations are copied to the entry, which is
+    /// marked as fuzzy and gets the `msgid` of the memory
+    /// entry as `previous_msgid`, like `msgmerge` does. Only
+    /// entries with the same plurality are considered.
+    ///
+    /// Returns the number of entries with suggestions.
+    ///
+    /// ```rust
+    /// use rspolib::{POEntry, POFile};
+    ///
+    /// let memory = POFile::from(vec![&POEntry::from(("Open file", "Abrir archivo"))]);
+    /// let mut file = POFile::from(vec![&POEntry::from("Open files")]);
+    /// assert_eq!(file.suggest_from(&memory, 0.8), 1);
+    ///
+    /// let entry = &file.entries[0];
+    /// assert_eq!(entry.msgstr.as_deref(), Some("Abrir archivo"));
+    /// assert!(entry.fuzzy());
+    /// assert_eq!(entry.previous_msgid.as_deref(), Some("Open file"));
+    /// ```
+    pub fn suggest_from(
+        &mut self,
+        memory: &POFile,
+        threshold: f32,
+    ) -> usize {
+        let candidates = memory
+            .iter_translated()
+            .map(|entry| (entry, entry.msgid.chars().count()))
+            .collect::<Vec<_>>();
+
+        let mut suggested = 0;
+        for entry in &mut self.entries {
+            if entry.obsolete || entry.fuzzy() || entry.translated() {
+                continue;
+            }
+            let msgid_len = entry.msgid.chars().count();
+            let mut best: Option<(&POEntry, f32)> = None;
+            for (candidate, candidate_len) in &candidates {
+                if candidate.is_plural() != entry.is_plural() {
+                    continue;
+                }
+                // the ratio can't be higher than the length ratio
+                let max_len = msgid_len.max(*candidate_len);
+                let min_len = msgid_len.min(*candidate_len);
+                if max_len > 0
+                    && (min_len as f32 / max_len as f32) < threshold
+                {
+                    continue;
+                }
+                let ratio =
+                    levenshtein_ratio(&entry.msgid, &candidate.msgid);
+                if ratio >= threshold
+                    && best.is_none_or(|(_, best)| ratio > best)
+                {
+                    best = Some((candidate, ratio));
+                }
+            }
+
+            let Some((candidate, _)) = best else {
+                continue;
+            };
+            if entry.is_plural() {
+                entry.msgstr_plural = candidate.msgstr_plural.clone();
+            } else {
+                entry.msgstr = candidate.msgstr.clone();
+            }
+            entry.set_fuzzy(true);
+            if candidate.msgid != entry.msgid {
+                entry.previous_msgid = Some(candidate.msgid.clone());
+            }
+            suggested += 1;
+        }
+        self.mark_dirty();
+        suggested
+    }
+
+    /// Removes the comments of all the entries
+    ///
+    /// See [POEntry::strip_comments].
+    pub fn strip_all_comments(&mut self) {
+        for entry in &mut self.entries {
+            entry.strip_comments();
+        }
+    }
+
+    /// Removes the occurrences of all the entries
+    ///
+    /// Useful to avoid distributing internal paths.
+    /// See [POEntry::strip_occurrences].
+    pub fn strip_all_occurrences(&mut self) {
+        for entry in &mut self.entries {
+            entry.strip_occurrences();
+        }
+    }
+
+    /// Removes the duplicated occurrences of each entry
+    ///
+    /// Useful after merging catalogs.
+    /// See [POEntry::dedup_occurrences].
+    pub fn dedup_occurrences(&mut self) {
+        for entry in &mut self.entries {
+            entry.dedup_occurrences();
+        }
+    }
+
+    /// Returns the length in bytes of the file formatted as
+    /// a string
+    ///
+    /// The result is the same as `to_string().len()`, but the
+    /// length is computed by entries, so the whole content is
+    /// never allocated at once.
+    ///
+    /// ```rust
+    /// use rspolib::pofile;
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// assert_eq!(file.text_len(), file.to_string().len());
+    /// ```
+    pub fn text_len(&self) -> usize {
+        let mut len = self.header_to_string().len();
+        len += po_metadata_entry_to_string(
+            &self.metadata_as_entry(),
+            self.metadata_is_fuzzy,
+        )
+        .len();
+        for entry in &self.entries {
+            len += self
+                .entry_to_string(entry, self.options.wrapwidth)
+                .len()
+                + 1;
+        }
+        if let Some(ref comments) = self.trailing_comments {
+            len += comments_to_string(comments).len() + 1;
+        }
+        // the newline added after the metadata is compensated
+        // by the last newline, which is removed from the file
+        len
+    }
+
+    /// Cheap estimation of the length of the formatted
+    /// metadata and entries, used to preallocate the buffer
+    /// when formatting the file
+    ///
+    /// Only the lengths of the fields are summed plus a fixed
+    /// overhead for keywords and quotes, so the result is
+    /// usually a bit lower than the real length because of
+    /// the wrapping.
+    fn estimated_entries_len(&self) -> usize {
+        const ENTRY_OVERHEAD: usize = 32;
+
+        let metadata_len = self
+            .metadata
+            .iter()
+            .map(|(key, value)| key.len() + value.len() + 8)
+            .sum::<usize>();
+        let entries_len = self
+            .entries
+            .iter()
+            .map(|entry| {
+                ENTRY_OVERHEAD
+                    + entry.msgid.len()
+                    + entry.msgstr.as_ref().map_or(0, String::len)
+                    + entry
+                        .msgid_plural
+                        .as_ref()
+                        .map_or(0, String::len)
+                    + entry
+                        .msgstr_plural
+                        .iter()
+                        .map(|msgstr| msgstr.len() + 12)
+                        .sum::<usize>()
+                    + entry.msgctxt.as_ref().map_or(0, String::len)
+                    + entry.comment.as_ref().map_or(0, String::len)
+                    + entry.tcomment.as_ref().map_or(0, String::len)
+                    + entry
+                        .occurrences
+                        .iter()
+                        .map(|(path, line)| {
+                            path.len() + line.len() + 2
+                        })
+                        .sum::<usize>()
+                    + entry
+                        .flags
+                        .iter()
+                        .map(|flag| flag.len() + 2)
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+        metadata_len + entries_len
+    }
+
+    fn entry_to_string(
+        &self,
+        entry: &POEntry,
+        wrapwidth: usize,
+    ) -> String {
+        entry.format(
+            wrapwidth,
+            self.options.multiline_style,
+            self.options.occurrences_style,
+        )
+    }
+
+    fn header_to_string(&self) -> String {
+        match self.header {
+            Some(ref header) => match self.raw_header.get(header) {
+                Some(raw) => raw.to_string(),
+                None => comments_to_string(header),
+            },
+            None => "#\n".to_string(),
+        }
+    }
+
+    /// Returns the metadata of the file as an entry.
+    ///
+    /// This method is not really useful because the
+    /// ``to_string()`` version will not be guaranteed to be
+    /// correct.
+    ///
+    /// If you want to manipulate the metadata, change
+    /// the content of the field `metadata` in the file.
+    ///
+    /// If you still want to render a metadata entry as
+    /// a string, use the function [po_metadata_entry_to_string]:
+    ///
+    /// ```rust
+    /// use rspolib::{
+    ///     pofile,
+    ///     po_metadata_entry_to_string,
+    /// };
+    ///
+    /// let file = pofile("tests-data/metadata.po").unwrap();
+    /// let entry = file.metadata_as_entry();
+    /// let entry_str = po_metadata_entry_to_string(&entry, true);
+    /// assert!(entry_str.starts_with("#, fuzzy\nmsgid \"\""));
+    /// ```
+    pub fn metadata_as_entry(&self) -> POEntry {
+        let mut entry = POEntry::new(0);
+        if self.metadata_is_fuzzy {
+            entry.flags.push("fuzzy".to_string());
+        }
+
+        if !self.metadata.is_empty() {
+            entry.msgstr = Some(metadata_hashmap_to_msgstr(
+                &self.metadata,
+                match self.options.sort_metadata {
+                    true => {
+                        self.options.metadata_keys_order.as_deref()
+                    }
+                    false => Some(&self.metadata_order),
+                },
+            ))
+        }
+
+        entry
+    }
+}
+
+/// Adds the words and characters of an entry to a [WordCount]
+fn count_words(count: &mut WordCount, entry: &POEntry) {
+    let source = [Some(&entry.msgid), entry.msgid_plural.as_ref()];
+    let source = source.into_iter().flatten();
+    count.entries += 1;
+    count.source_words += source
+        .clone()
+        .map(|s| s.unicode_words().count())
+        .sum::<usize>();
+    count.source_chars +=
+        source.map(|s| s.graphemes(true).count()).sum::<usize>();
+    if !entry.translated() {
+        return;
+    }
+    let translations =
+        entry.msgstr.iter().chain(entry.msgstr_plural.iter());
+    count.translated_entries += 1;
+    count.translated_words += translations
+        .clone()
+        .map(|s| s.unicode_words().count())
+        .sum::<usize>();
+    count.translated_chars += translations
+        .map(|s| s.graphemes(true).count())
+        .sum::<usize>();
+}
+
+/// Returns the `msgid` and `msgctxt` of an entry, which
+/// identify it in a file
+fn entry_key(entry: &POEntry) -> (&str, Option<&str>) {
+    (&entry.msgid, entry.msgctxt.as_deref())
+}
+
+/// Returns the `msgstr` of an entry or, for plural entries,
+/// the `msgstr` of each plural form
+fn translations(entry: &POEntry) -> Vec<String> {
+    if entry.is_plural() {
+        entry.msgstr_plural.clone()
+    } else {
+        vec![entry.msgstr.clone().unwrap_or_default()]
+    }
+}
+
+/// Formats comments as `#` prefixed lines
+fn comments_to_string(comments: &str) -> String {
+    if comments.is_empty() {
+        return "#\n".to_string();
+    }
+    let mut repr = String::new();
+    for line in comments.lines() {
+        if line.is_empty() {
+            repr.push_str("#\n");
+        } else {
+            repr.reserve(line.len() + 3);
+            repr.push_str("# ");
+            repr.push_str(line);
+            repr.push('\n');
+        }
+    }
+    repr
+}
+
+impl fmt::Display for POFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_string_with_wrapwidth(self.options.wrapwidth)
+        )
+    }
+}
+
+impl Default for POFile {
+    /// Returns a file without metadata nor entries and the
+    /// default options
+    fn default() -> Self {
+        Self::new(FileOptions::default())
+    }
+}
+
+// Method `save_as_pofile` is implemented in the trait
+impl SaveAsPOFile for POFile {}
+
+impl Save for POFile {
+    /// Save the PO file as the given path
+    fn save(&self, path: &str) -> io::Result<()> {
+        self.save_as_pofile(path)
+    }
+}
+
+impl SaveAsMOFile for POFile {
+    /// Save the PO file as a MO file as the given path
+    ///
+    /// The plural entries are checked first, see
+    /// [MOFile::check_plural_forms].
+    fn save_as_mofile(&self, path: &str) -> io::Result<()> {
+        MOFile::from(self).save(path)
+    }
+}
+
+impl<'a> From<&'a str> for POFile {
+    fn from(path_or_content: &'a str) -> Self {
+        pofile(path_or_content).unwrap()
+    }
+}
+
+impl Merge for POFile {
+    /// Merge another PO file into this one and return a new one
+    ///
+    /// Recursively calls `merge` on each entry if they are found
+    /// in the current file searching by msgid and msgctxt. If not
+    /// found, generates a new entry. The translations of the
+    /// current file are kept. See [POFile::merge_with].
+    ///
+    /// This method is commonly used to merge a POT reference file
+    /// with a PO file.
+    fn merge(&mut self, other: POFile) {
+        self.merge_in_place(&other);
+    }
+}
+
+impl AsBytes for POFile {
+    /// Return the PO file content as a bytes vector of the MO file version
+    ///
+    /// The MO file is encoded with little
+    /// endian magic number and revision number 0
+    ///
+    /// Use directly [MOFile::as_bytes_with] to customize
+    /// the magic number and revision number:
+    ///
+    /// ```rust
+    /// use rspolib::{pofile, MAGIC_SWAPPED, MOFile};
+    ///
+    /// let file = pofile("tests-data/all.po").unwrap();
+    /// let bytes = MOFile::from(&file).as_bytes_with(MAGIC_SWAPPED, 1);
+    /// ```
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        let mofile = MOFile::from(self);
+        let result = mofile.as_bytes_with(MAGIC, 0);
+        Cow::Owned(result.into_owned())
+    }
+
+    /// Return the PO file content as a bytes vector of the MO file version
+    ///
+    /// Just an alias for [POFile::as_bytes], for consistency with [MOFile].
+    fn as_bytes_le(&self) -> Cow<'_, [u8]> {
+        self.as_bytes()
+    }
+
+    /// Return the PO file content as a bytes vector of
+    /// the MO file version with big endianess
+    fn as_bytes_be(&self) -> Cow<'_, [u8]> {
+        let mofile = MOFile::from(self);
+        let result = mofile.as_bytes_with(MAGIC_SWAPPED, 0);
+        Cow::Owned(result.into_owned())
+    }
+}
+
+impl From<Vec<&POEntry>> for POFile {
+    fn from(entries: Vec<&POEntry>) -> Self {
+        let mut file = POFile::new("".into());
+        for entry in entries {
+            file.entries.push(entry.clone());
+        }
+        file
+    }
+}
+
+impl From<Vec<POEntry>> for POFile {
+    /// Creates a file with the given entries, default options
+    /// and empty metadata
+    fn from(entries: Vec<POEntry>) -> Self {
+        let mut file = POFile::new(FileOptions::default());
+        file.entries = entries;
+        file
+    }
+}
+
+impl FromIterator<POEntry> for POFile {
+    /// Creates a file with the entries of the iterator, default
+    /// options and empty metadata
+    ///
+    /// ```rust
+    /// use rspolib::{POEntry, POFile};
+    ///
+    /// let file: POFile = ["Hello", "Bye"]
+    ///     .into_iter()
+    ///     .map(POEntry::from)
+    ///     .collect();
+    /// assert_eq!(file.entries.len(), 2);
+    /// assert!(file.metadata.is_empty());
+    /// ```
+    fn from_iter<I: IntoIterator<Item = POEntry>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl From<&Path> for POFile {
+    fn from(path: &std::path::Path) -> Self {
+        POFile::from(path.to_str().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::mofile::mofile;
+    use std::fs;
+    use std::path::Path;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn pofile_test() {
+        let path = "tests-data/all.po";
+        let file = pofile(path).unwrap();
+
+        assert_eq!(file.entries.len(), 9);
+    }
+
+    #[test]
+    fn pofile_from_std_paths() {
+        let path = Path::new("tests-data/all.po");
+        assert_eq!(pofile(path).unwrap().entries.len(), 9);
+
+        let path = std::path::PathBuf::from("tests-data/all.po");
+        assert_eq!(pofile(path).unwrap().entries.len(), 9);
+    }
+
+    #[test]
+    fn pofile_metadata_as_entry() {
+        // File with metadata
+        let path = "tests-data/all.po";
+        let file = pofile(path).unwrap();
+        let entry = file.metadata_as_entry();
+
+        assert_eq!(entry.msgid, "");
+        assert_eq!(entry.msgstr.unwrap().lines().count(), 11);
+
+        // File without metadata
+        let path = "tests-data/empty-metadata.po";
+        let file = pofile(path).unwrap();
+        let entry = file.metadata_as_entry();
+
+        assert_eq!(entry.msgid, "");
+        assert!(entry.msgstr.is_none());
+
+        // File with fuzzy metadata
+        let path = "tests-data/fuzzy-header.po";
+        let file = pofile(path).unwrap();
+        let entry = file.metadata_as_entry();
+
+        assert_eq!(entry.msgid, "");
+        assert!(entry.fuzzy());
+        assert_eq!(entry.msgstr.unwrap().lines().count(), 12);
+    }
+
+    #[test]
+    fn metadata_keys_are_natural_sorted() {
+        let path = "tests-data/natural-unsorted-metadata.po";
+        let file = pofile(path).unwrap();
+
+        file.save("foobar-2-out.po").unwrap();
+        assert_eq!(
+            file.to_string(),
+            "#
+msgid \"\"
+msgstr \"\"
+\"Project-Id-Version: PACKAGE VERSION\\n\"
+\"Report-Msgid-Bugs-To: \\n\"
+\"Language-Team: LANGUAGE <LL@li.org>\\n\"
+\"Content-Type: text/plain; charset=UTF-8\\n\"
+\"Content-Transfer-Encoding: 8bit\\n\"
+\"X-Poedit-SearchPath-1: Foo\\n\"
+\"X-Poedit-SearchPath-2: Bar\\n\"
+\"X-Poedit-SearchPath-10: Baz\\n\"
+",
+        );
+    }
+
+    #[test]
+    fn pofile_percent_translated() {
+        let path = "tests-data/2-translated-entries.po";
+        let file = pofile(path).unwrap();
+
+        assert_eq!(file.percent_translated(), 40_f32);
+    }
+
+    #[test]
+    fn pofile_validate() {
+        use crate::ValidationCategory::*;
+
+        let mut file = pofile("tests-data/validation.po").unwrap();
+        let warnings = file
+            .validate()
+            .into_iter()
+            .map(|warning| (warning.category, warning.linenum))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                (Fuzzy, 9),
+                (PlaceholderMismatch, 13),
+                (IncompleteTranslation, 16),
+                (PluralFormsMismatch, 21),
+            ]
+        );
+
+        // obsolete entries are not checked
+        for entry in file.entries.iter_mut() {
+            entry.obsolete = true;
+        }
+        assert!(file.validate().is_empty());
+    }
+
+    #[test]
+    fn difference_and_intersection() {
+        let file = pofile(concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Language: es\\n\"\n",
+            "\n",
+            "msgid \"Open\"\n",
+            "msgstr \"Abrir\"\n",
+            "\n",
+            "msgctxt \"menu\"\n",
+            "msgid \"Open\"\n",
+            "msgstr \"Abrir menú\"\n",
+            "\n",
+            "msgctxt \"verb\"\n",
+            "msgid \"Close\"\n",
+            "msgstr \"Cerrar\"\n",
+        ))
+        .unwrap();
+        let other = pofile(concat!(
+            "msgctxt \"menu\"\n",
+            "msgid \"Open\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"Close\"\n",
+            "msgstr \"Cerrar\"\n",
+        ))
+        .unwrap();
+
+        let keys = |file: &POFile| {
+            file.entries
+                .iter()
+                .map(|e| (e.msgid.clone(), e.msgctxt.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let difference = file.difference(&other);
+        assert_eq!(
+            keys(&difference),
+            vec![
+                ("Open".to_string(), None),
+                ("Close".to_string(), Some("verb".to_string())),
+            ]
+        );
+        assert_eq!(difference.metadata_get("Language"), Some("es"));
+
+        // full entries of the current file are copied
+        let intersection = file.intersection(&other);
+        assert_eq!(
+            intersection.entries,
+            vec![file.entries[1].clone()]
+        );
+        assert_eq!(
+            intersection.entries[0].msgstr.as_deref(),
+            Some("Abrir menú")
+        );
+        assert_eq!(intersection.metadata, file.metadata);
+
+        let intersection = other.intersection(&file);
+        assert_eq!(
+            keys(&intersection),
+            vec![("Open".to_string(), Some("menu".to_string()))]
+        );
+        assert!(intersection.metadata.is_empty());
+    }
+
+    #[test]
+    fn pofile_from_owned_entries() {
+        let entries = vec![
+            POEntry::from(("Hello", "Hola")),
+            POEntry::from("Bye"),
+        ];
+        let file = POFile::from(entries.clone());
+        assert_eq!(file.entries, entries);
+        assert!(file.metadata.is_empty());
+        assert_eq!(file.options, FileOptions::default());
+
+        let collected: POFile = entries.clone().into_iter().collect();
+        assert_eq!(collected.entries, entries);
+        assert_eq!(collected.percent_translated(), 50.0);
+        assert_eq!(collected.to_string(), file.to_string());
+    }
+
+    #[test]
+    fn compact_and_remove_untranslated() {
+        let mut file = pofile(concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Language: es\\n\"\n",
+            "\n",
+            "msgid \"Translated\"\n",
+            "msgstr \"Traducido\"\n",
+            "\n",
+            "msgid \"Untranslated\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"Fuzzy\"\n",
+            "msgstr \"Difuso\"\n",
+            "\n",
+            "msgid \"One file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"Un archivo\"\n",
+            "msgstr[1] \"%d archivos\"\n",
+            "\n",
+            "msgid \"One day\"\n",
+            "msgid_plural \"%d days\"\n",
+            "msgstr[0] \"Un día\"\n",
+            "msgstr[1] \"\"\n",
+            "\n",
+            "#~ msgid \"Obsolete\"\n",
+            "#~ msgstr \"Obsoleto\"\n",
+        ))
+        .unwrap();
+        // metadata entries added manually are kept
+        file.entries
+            .insert(0, POEntry::from(("", "Language: es\n")));
+        assert_eq!(file.percent_translated(), 50.0);
+
+        let mut untranslated_removed = file.clone();
+        assert_eq!(untranslated_removed.remove_untranslated(), 3);
+        let msgids = |file: &POFile| {
+            file.entries
+                .iter()
+                .map(|e| e.msgid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            msgids(&untranslated_removed),
+            vec!["", "Translated", "One file", "Obsolete"]
+        );
+        assert_eq!(untranslated_removed.percent_translated(), 100.0);
+
+        assert_eq!(file.compact(), 1);
+        assert_eq!(file.compact(), 0);
+        assert!(file.obsolete_entries().is_empty());
+        assert_eq!(file.entries.len(), 6);
+        assert_eq!(file.metadata_get("Language"), Some("es"));
+    }
+
+    #[test]
+    fn word_count() {
+        let file = pofile(concat!(
+            "msgid \"Don't save the file\"\n",
+            "msgstr \"No guardes el archivo\"\n",
+            "\n",
+            "msgid \"One file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"Un archivo\"\n",
+            "msgstr[1] \"%d archivos\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"Café\"\n",
+            "msgstr \"Café\"\n",
+            "\n",
+            "#~ msgid \"Obsolete entry\"\n",
+            "#~ msgstr \"Entrada obsoleta\"\n",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            file.word_count(),
+            WordCount {
+                entries: 3,
+                translated_entries: 2,
+                // "Don't" is a single word and "%" is not a word
+                source_words: 4 + 2 + 2 + 1,
+                translated_words: 4 + 2 + 2,
+                source_chars: 19 + 8 + 8 + 4,
+                translated_chars: 21 + 10 + 11,
+            }
+        );
+        assert_eq!(file.source_word_count(), 9);
+        assert_eq!(file.translated_word_count(), 8);
+        assert_eq!(file.char_count(), 39);
+    }
+
+    #[test]
+    fn percent_translated_ignores_obsolete() {
+        let file = pofile(concat!(
+            "msgid \"translated\"\n",
+            "msgstr \"traducido\"\n",
+            "\n",
+            "msgid \"untranslated\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"fuzzy\"\n",
+            "msgstr \"difuso\"\n",
+            "\n",
+            "msgid \"another translated\"\n",
+            "msgstr \"otro traducido\"\n",
+            "\n",
+            "#~ msgid \"obsolete\"\n",
+            "#~ msgstr \"obsoleto\"\n",
+            "\n",
+            "#~ msgid \"another obsolete\"\n",
+            "#~ msgstr \"\"\n",
+        ))
+        .unwrap();
+        assert_eq!(file.percent_translated(), 50.0);
+        let percent = file.percent_translated_including_obsolete();
+        assert!((percent - 33.33).abs() < 0.01);
+
+        let mut file = pofile("tests-data/obsoletes.po").unwrap();
+        for entry in &mut file.entries {
+            entry.obsolete = true;
+        }
+        file.mark_dirty();
+        assert_eq!(file.percent_translated(), 0.0);
+    }
+
+    #[test]
+    fn pofile_translated_entries() {
+        let path = "tests-data/2-translated-entries.po";
+        let file = pofile(path).unwrap();
+
+        let translated_entries = file.translated_entries();
+        assert_eq!(file.entries.len(), 5);
+        assert_eq!(translated_entries.len(), 2);
+        assert_eq!(file.entries[0].msgid, "msgid 1");
+        assert_eq!(translated_entries[0].msgid, "msgid 2");
+    }
+
+    #[test]
+    fn pofile_untranslated_entries() {
+        let path = "tests-data/2-translated-entries.po";
+        let file = pofile(path).unwrap();
+
+        let untranslated_entries = file.untranslated_entries();
+        assert_eq!(file.entries.len(), 5);
+        assert_eq!(untranslated_entries.len(), 3);
+        assert_eq!(file.entries[0].msgid, "msgid 1");
+        assert_eq!(untranslated_entries[0].msgid, "msgid 1");
+        assert_eq!(untranslated_entries[1].msgid, "msgid 3");
+    }
+
+    #[test]
+    fn pofile_obsolete_entries() {
+        let path = "tests-data/obsoletes.po";
+        let file = pofile(path).unwrap();
+
+        let obsolete_entries = file.obsolete_entries();
+        assert_eq!(file.entries.len(), 3);
+        assert_eq!(obsolete_entries.len(), 2);
+    }
+
+    #[test]
+    fn pofile_iterators() {
+        let file = pofile("tests-data/all.po").unwrap();
+        assert_eq!(file.iter().count(), file.entries.len());
+        assert_eq!(
+            file.iter_translated().count()
+                + file.iter_untranslated().count(),
+            file.entries.len()
+        );
+        assert!(file.iter_obsolete().all(|entry| entry.obsolete));
+        assert_eq!(
+            file.iter_obsolete().count(),
+            file.obsolete_entries().len()
+        );
+        assert_eq!(
+            file.iter_fuzzy().collect::<Vec<&POEntry>>(),
+            file.fuzzy_entries()
+        );
+        assert_eq!(
+            file.iter_untranslated()
+                .find(|entry| !entry.obsolete)
+                .map(|entry| entry.msgid.as_str()),
+            file.untranslated_entries()
+                .into_iter()
+                .find(|entry| !entry.obsolete)
+                .map(|entry| entry.msgid.as_str()),
+        );
+    }
+
+    #[test]
+    fn build_index() {
+        let mut file = pofile("tests-data/all.po").unwrap();
+        let mut entry = POEntry::from(("Jan.", "Enero"));
+        entry.msgctxt = Some("".to_string());
+        file.entries.push(entry);
+        let unindexed = file.clone();
+        file.build_index();
+
+        for entry in &unindexed.entries {
+            let msgctxt = entry.msgctxt.as_deref().unwrap_or("");
+            assert_eq!(
+                file.find_by_msgid(&entry.msgid),
+                unindexed.find_by_msgid(&entry.msgid),
+            );
+            assert_eq!(
+                file.find_by_msgid_msgctxt(&entry.msgid, msgctxt),
+                unindexed
+                    .find_by_msgid_msgctxt(&entry.msgid, msgctxt),
+            );
+        }
+        assert_eq!(
+            file.find_by_msgid_msgctxt("Jan.", "")
+                .unwrap()
+                .msgstr
+                .as_deref(),
+            Some("Enero"),
+        );
+        assert_eq!(file.find_by_msgid("Unknown"), None);
+        assert_eq!(file.find_by_msgid_msgctxt("Jan.", "month"), None);
+
+        // modifying the entries through the file drops the index
+        file.remove_by_msgid_msgctxt("Jan.", "abbrev. month");
+        assert!(file.index.0.is_none());
+        assert_eq!(
+            file.find_by_msgid_msgctxt("Jan.", "abbrev. month"),
+            None
+        );
+
+        // stale positions fall back to scanning the entries
+        file.build_index();
+        file.entries.swap(0, 1);
+        let msgid = file.entries[0].msgid.clone();
+        assert_eq!(file.find_by_msgid(&msgid).unwrap().msgid, msgid,);
+
+        // editing through `entries_mut` drops the index
+        let mut file = POFile::new(FileOptions::default());
+        file.entries.push(POEntry::from(("first", "1")));
+        file.entries.push(POEntry::from(("dup", "second")));
+        file.build_index();
+        file.entries_mut()[0] = POEntry::from(("dup", "first"));
+        assert!(file.index.0.is_none());
+        assert_eq!(
+            file.find_by_msgid("dup").unwrap().msgstr.as_deref(),
+            Some("first"),
+        );
+    }
+
+    #[test]
+    fn entries_by_flag() {
+        let file = pofile("tests-data/format-flags.po").unwrap();
+
+        let msgids = |flag| {
+            file.iter_by_flag(flag)
+                .map(|entry| entry.msgid.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(msgids("c-format"), vec!["%d file", "Formatted"]);
+        assert_eq!(msgids("no-c-format"), vec!["Not formatted"]);
+        assert_eq!(msgids("no-wrap"), vec!["Unwrapped entry"]);
+        assert_eq!(msgids("format"), Vec::<&str>::new());
+        assert_eq!(
+            file.entries_by_flag("fuzzy"),
+            file.fuzzy_entries()
+        );
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut file = pofile("tests-data/all.po").unwrap();
+        file.build_index();
+        let original = file.to_string();
+        // fill the cache of the translated entries
+        let percent = file.percent_translated();
+
+        let mut copy = file.clone();
+        copy.entries[0].msgstr = Some("changed".to_string());
+        copy.entries[1].msgstr = Some("".to_string());
+        copy.entries.push(POEntry::from(("new", "nuevo")));
+        copy.metadata
+            .insert("Language".to_string(), "fr".to_string());
+        copy.header = Some("Another header".to_string());
+        copy.options.wrapwidth = 40;
+        copy.mark_dirty();
+
+        assert_eq!(file.to_string(), original);
+        assert_eq!(file.percent_translated(), percent);
+        assert_ne!(copy.percent_translated(), percent);
+        assert_eq!(file.options.wrapwidth, 78);
+        assert_eq!(file.find_by_msgid("new"), None);
+        assert!(copy.find_by_msgid("new").is_some());
+    }
+
+    #[test]
+    fn new_empty() {
+        let file = POFile::new_empty();
+        assert_eq!(
+            file.to_string(),
+            concat!(
+                "#\n",
+                "msgid \"\"\n",
+                "msgstr \"\"\n",
+                "\"MIME-Version: 1.0\\n\"\n",
+                "\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+                "\"Content-Transfer-Encoding: 8bit\\n\"\n",
+            )
+        );
+        let parsed = pofile(file.to_string().as_str()).unwrap();
+        assert_eq!(parsed.metadata, file.metadata);
+        assert_eq!(
+            POFile::default(),
+            POFile::new(FileOptions::default())
+        );
+    }
+
+    #[test]
+    fn to_xliff() {
+        let content = concat!(
+            "#\n",
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Language: es\\n\"\n",
+            "\n",
+            "#. Shown in the <title>\n",
+            "msgctxt \"menu\"\n",
+            "msgid \"Open & save\"\n",
+            "msgstr \"Abrir y guardar\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"Close\"\n",
+            "msgstr \"Cerrar\"\n",
+            "\n",
+            "msgid \"Untranslated \\\"quoted\\\"\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"%d archivo\"\n",
+            "msgstr[1] \"\"\n",
+            "\n",
+            "#~ msgid \"Obsolete\"\n",
+            "#~ msgstr \"Obsoleto\"\n",
+        );
+        let file = pofile(content).unwrap();
+
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+            "  <file original=\"messages.po\" source-language=\"en\" target-language=\"es\" datatype=\"po\">\n",
+            "    <body>\n",
+            "      <trans-unit id=\"1\" approved=\"yes\">\n",
+            "        <source>Open &amp; save</source>\n",
+            "        <target state=\"translated\">Abrir y guardar</target>\n",
+            "        <context-group purpose=\"information\"><context context-type=\"x-gettext-msgctxt\">menu</context></context-group>\n",
+            "        <note from=\"developer\">Shown in the &lt;title&gt;</note>\n",
+            "      </trans-unit>\n",
+            "      <trans-unit id=\"2\" approved=\"no\">\n",
+            "        <source>Close</source>\n",
+            "        <target state=\"needs-review-translation\">Cerrar</target>\n",
+            "      </trans-unit>\n",
+            "      <trans-unit id=\"3\" approved=\"no\">\n",
+            "        <source>Untranslated &quot;quoted&quot;</source>\n",
+            "      </trans-unit>\n",
+            "      <group id=\"4\" restype=\"x-gettext-plurals\">\n",
+            "        <trans-unit id=\"4[0]\" approved=\"no\">\n",
+            "          <source>%d file</source>\n",
+            "          <target state=\"translated\">%d archivo</target>\n",
+            "        </trans-unit>\n",
+            "        <trans-unit id=\"4[1]\" approved=\"no\">\n",
+            "          <source>%d files</source>\n",
+            "        </trans-unit>\n",
+            "      </group>\n",
+            "    </body>\n",
+            "  </file>\n",
+            "</xliff>\n",
+        );
+        assert_eq!(file.to_xliff("en", "es"), expected);
+
+        let file = pofile("tests-data/all.po").unwrap();
+        assert!(file
+            .to_xliff("en", "es")
+            .contains("<file original=\"tests-data/all.po\""));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn save_normalized() {
+        let mut file = pofile("tests-data/utf8-bom.po").unwrap();
+        file.header = Some("Traduccio\u{301}n".to_string());
+        file.metadata.insert(
+            "Last-Translator".to_string(),
+            "Jose\u{301} <jose@example.com>".to_string(),
+        );
+        let mut entry = POEntry::from(("Cafe\u{301}", "Cafe\u{301}"));
+        entry.msgctxt = Some("bebida\u{301}".to_string());
+        entry.set_plural(
+            "Cafe\u{301}s",
+            &["Cafe\u{301}", "Cafe\u{301}s"],
+        );
+        file.entries.push(entry);
+        let original = file.to_string();
+
+        let path = "tests-data/tests/save_normalized.po";
+        file.save_normalized(path).unwrap();
+        let bytes = fs::read(path).unwrap();
+        fs::remove_file(path).ok();
+
+        assert!(!bytes.starts_with(b"\xef\xbb\xbf"));
+        let content = String::from_utf8(bytes).unwrap();
+        assert!(!content.contains('\u{301}'));
+        assert!(content.starts_with("# Traducci\u{f3}n\n"));
+        assert!(content.contains("Jos\u{e9} <jose@example.com>"));
+        assert!(content.contains(concat!(
+            "msgctxt \"bebid\u{e1}\"\n",
+            "msgid \"Caf\u{e9}\"\n",
+            "msgid_plural \"Caf\u{e9}s\"\n",
+            "msgstr[0] \"Caf\u{e9}\"\n",
+            "msgstr[1] \"Caf\u{e9}s\"\n",
+        )));
+
+        // the file itself is not modified
+        assert_eq!(file.to_string(), original);
+    }
+
+    #[test]
+    fn statistics() {
+        for path in [
+            "tests-data/all.po",
+            "tests-data/obsoletes.po",
+            "tests-data/fuzzy-no-fuzzy.po",
+            "tests-data/2-translated-entries.po",
+        ] {
+            let file = pofile(path).unwrap();
+            assert_eq!(
+                file.statistics(),
+                Statistics {
+                    total: file.entries.len(),
+                    translated: file.translated_count(),
+                    untranslated: file.untranslated_count()
+                        - file.fuzzy_count()
+                        - file.obsolete_count(),
+                    fuzzy: file.fuzzy_count(),
+                    obsolete: file.obsolete_count(),
+                    percent_translated: file.percent_translated(),
+                    source_words: file.source_word_count(),
+                    translated_words: file.translated_word_count(),
+                },
+                "{path}",
+            );
+
+            let stats = file.statistics();
+            assert_eq!(
+                stats.translated
+                    + stats.untranslated
+                    + stats.fuzzy
+                    + stats.obsolete,
+                stats.total,
+                "{path}",
+            );
+        }
+        assert_eq!(
+            POFile::default().statistics(),
+            Statistics::default()
+        );
+    }
+
+    #[test]
+    fn pofile_counts() {
+        let file = pofile("tests-data/all.po").unwrap();
+        assert_eq!(
+            file.translated_count(),
+            file.translated_entries().len()
+        );
+        assert_eq!(
+            file.untranslated_count(),
+            file.untranslated_entries().len()
+        );
+        assert_eq!(
+            file.obsolete_count(),
+            file.obsolete_entries().len()
+        );
+        assert_eq!(file.fuzzy_count(), file.fuzzy_entries().len());
+
+        let file =
+            pofile("tests-data/2-translated-entries.po").unwrap();
+        assert_eq!(file.translated_count(), 2);
+        assert_eq!(file.untranslated_count(), 3);
+    }
+
+    #[test]
+    fn pofile_to_string() {
+        let po_path = "tests-data/all.po";
+        let file = pofile(po_path).unwrap();
+
+        let file_as_string = file.to_string();
+
+        for line in file_as_string.lines() {
+            let width = UnicodeWidthStr::width(line);
+            assert!(width <= file.options.wrapwidth + 2);
+        }
+    }
+
+    #[test]
+    fn to_string_with_wrapwidth() {
+        let mut file = POFile::new(FileOptions::default());
+        file.entries.push(POEntry::from(
+            "a long message that should be wrapped at a narrow width",
+        ));
+
+        let narrow = file.to_string_with_wrapwidth(30);
+        assert!(narrow.contains("msgid \"\"\n\"a long message"));
+        for line in narrow.lines().filter(|l| l.starts_with('"')) {
+            assert!(UnicodeWidthStr::width(line) <= 32);
+        }
+        assert!(file.to_string_with_wrapwidth(0).contains(
+            "msgid \"a long message that should be wrapped at a \
+             narrow width\"",
+        ));
+        assert_eq!(file.options.wrapwidth, 78);
+        assert_eq!(
+            file.to_string_with_wrapwidth(78),
+            file.to_string()
+        );
+
+        let tmp_path = "tests-data/tests/wrapwidth.po";
+        file.save_with_wrapwidth(tmp_path, 30).unwrap();
+        assert_eq!(fs::read_to_string(tmp_path).unwrap(), narrow);
+        fs::remove_file(tmp_path).ok();
+    }
+
+    #[test]
+    fn save_as_mofile_with() {
+        let file = pofile("tests-data/all.po").unwrap();
+        let default_path = "tests-data/tests/save_as_mofile.mo";
+        file.save_as_mofile(default_path).unwrap();
+        let expected = mofile(default_path).unwrap();
+        fs::remove_file(default_path).ok();
+
+        for (endianness, magic_bytes) in [
+            (Endianness::Little, MAGIC.to_le_bytes()),
+            (Endianness::Big, MAGIC.to_be_bytes()),
+        ] {
+            let path = format!(
+                "tests-data/tests/save_as_mofile_with_{:?}.mo",
+                endianness
+            );
+            file.save_as_mofile_with(&path, endianness, 0).unwrap();
+
+            let bytes = fs::read(&path).unwrap();
+            assert_eq!(bytes[..4], magic_bytes);
+            assert_eq!(bytes[4..8], [0; 4]);
+            assert!(mofile(path.as_str())
+                .unwrap()
+                .content_eq(&expected));
+            fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let path = "tests-data/unusual-header.po";
+        let mut file = pofile(path).unwrap();
+        let tmp_path = "tests-data/tests/unusual-header.po";
+        file.save(tmp_path).unwrap();
+        assert_eq!(
+            fs::read(tmp_path).unwrap(),
+            fs::read(path).unwrap()
+        );
+        fs::remove_file(tmp_path).ok();
+
+        // modified headers are formatted normally
+        file.header.as_mut().unwrap().push_str("\nNew line");
+        let file_as_string = file.to_string();
+        assert!(file_as_string.starts_with(
+            "# Unusual header\n#  indented with two spaces\n#\n"
+        ));
+        assert!(file_as_string.contains("\n# New line\n#, fuzzy\n"));
+    }
+
+    fn pofile_save_test(save_fn_name: &str, fname: &str) {
+        let tmpdir = "tests-data/tests";
+
+        let path = "tests-data/all.po";
+        let file = pofile(path).unwrap();
+        let file_as_string = file.to_string();
+
+        // Here the file name is parametrized to avoid data races
+        // when running tests in parallel
+        let tmp_path = Path::new(&tmpdir).join(fname);
+        let tmp_path_str = tmp_path.to_str().unwrap();
+
+        if save_fn_name == "save" {
+            file.save(tmp_path_str).unwrap();
+        } else {
+            file.save_as_pofile(tmp_path_str).unwrap();
+        }
+
+        assert_eq!(
+            file_as_string,
+            fs::read_to_string(tmp_path_str).unwrap()
+        );
+        fs::remove_file(tmp_path_str).ok();
+    }
+
+    #[test]
+    fn pofile_save() {
+        pofile_save_test("save", "all-1.po")
+    }
+
+    #[test]
+    fn pofile_save_as_pofile() {
+        pofile_save_test("save_as_pofile", "all-2.po")
+    }
+
+    #[test]
+    fn pofile_save_error() {
+        let file = pofile("tests-data/all.po").unwrap();
+        let path = "tests-data/tests/unexistent-directory/all.po";
+
+        let err = file.save(path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(file.save_as_pofile(path).is_err());
+        assert!(file.save_as_mofile(path).is_err());
+        assert!(!Path::new(path).exists());
+    }
+
+    #[test]
+    fn pofile_save_as_mofile() {
+        let tmpdir = "tests-data/tests";
+
+        let content =
+            concat!("msgid \"foo bar\"\n", "msgstr \"foo bar\"\n",);
+        let po_file = pofile(content).unwrap();
+
+        let tmp_path = Path::new(&tmpdir)
+            .join("pofile_save_as_mofile-simple.mo");
+        let tmp_path_str = tmp_path.to_str().unwrap();
+        po_file.save_as_mofile(tmp_path_str).unwrap();
+
+        assert!(tmp_path.exists());
+
+        let mo_file = mofile(tmp_path_str).unwrap();
+        assert_eq!(mo_file.entries.len(), po_file.entries.len());
+        assert_eq!(mo_file.metadata.len(), po_file.metadata.len());
+
+        assert_eq!(mo_file.entries[0].msgid, "foo bar");
+        assert_eq!(
+            mo_file.entries[0].msgstr.as_ref().unwrap(),
+            "foo bar"
+        );
+    }
+
+    #[test]
+    fn set_fuzzy() {
+        let path = "tests-data/fuzzy-no-fuzzy.po";
+
+        let mut file = pofile(path).unwrap();
+
+        assert!(!file.entries[0].fuzzy());
+        assert!(file.entries[1].fuzzy());
+
+        file.entries[0].set_fuzzy(true);
+        file.entries[1].set_fuzzy(false);
+
+        assert!(file.entries[0].fuzzy());
+        assert!(!file.entries[1].fuzzy());
+
+        assert_eq!(
+            file.entries[0].to_string(),
+            "#, fuzzy\nmsgid \"a\"\nmsgstr \"a\"\n",
+        );
+        assert_eq!(
+            file.entries[1].to_string(),
+            "msgid \"Line\"\nmsgstr \"Ligne\"\n",
+        );
+    }
+
+    #[test]
+    fn format_fuzzy_metadata() {
+        let path = "tests-data/fuzzy-header.po";
+
+        let file = pofile(path).unwrap();
+        let expected_start = concat!(
+            "# Po file with\n# a fuzzy header\n# \n#, fuzzy\n",
+            "msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version:",
+        );
+        assert!(file.to_string().starts_with(expected_start));
+    }
+
+    #[test]
+    fn format_comment_ordering() {
+        let path = "tests-data/comment-ordering.po";
+
+        let file = pofile(path).unwrap();
+        let expected_content = r#"#
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+# First comment line
+#. Second comment line
+msgid "foo"
+msgstr "oof"
+"#;
+        assert_eq!(file.to_string(), expected_content);
+    }
+
+    #[test]
+    fn remove() {
+        let mut entry_1 = POEntry::from("msgid 1");
+        entry_1.msgstr = Some("msgstr 1".to_string());
+
+        let mut entry_2 = POEntry::from("msgid 2");
+        entry_2.msgstr = Some("msgstr 2".to_string());
+
+        let mut file = POFile::from(vec![&entry_1, &entry_2]);
+        assert_eq!(file.entries.len(), 2);
+
+        // remove by entry
+        file.remove(&entry_1);
+
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].msgid, "msgid 2");
+
+        // line numbers are ignored removing by entry
+        file.entries.push(entry_1.clone());
+        let mut reparsed_entry = entry_1.clone();
+        reparsed_entry.linenum = 42;
+        file.remove(&reparsed_entry);
+        assert_eq!(file.entries.len(), 1);
+
+        // the obsolete copy of an entry is kept
+        let mut obsolete_entry = entry_1.clone();
+        obsolete_entry.obsolete = true;
+        file.entries.push(entry_1.clone());
+        file.entries.push(obsolete_entry.clone());
+        file.remove(&entry_1);
+        assert_eq!(file.entries.len(), 2);
+        assert!(file.entries[1].obsolete);
+        file.remove(&obsolete_entry);
+
+        file.entries.push(entry_1);
+        assert_eq!(file.entries.len(), 2);
+
+        // remove by msgid
+        assert!(file.remove_by_msgid("msgid 2"));
+        assert!(!file.remove_by_msgid("msgid 2"));
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].msgid, "msgid 1");
+
+        // remove by msgid and msgctxt
+        entry_2.msgctxt = Some("msgctxt 2".to_string());
+        entry_2.msgid = "msgid 1".to_string();
+        file.entries.push(entry_2);
+        assert_eq!(file.entries.len(), 2);
+        assert!(file.remove_by_msgid_msgctxt("msgid 1", "msgctxt 2"));
+
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].msgid, "msgid 1");
+        assert_eq!(
+            file.entries[0].msgstr.as_ref().unwrap(),
+            "msgstr 1",
+        );
+
+        // only the first matching entry is removed
+        file.entries.push(file.entries[0].clone());
+        assert!(file.remove_by_msgid_msgctxt("msgid 1", ""));
+        assert_eq!(file.entries.len(), 1);
+    }
+
+    #[test]
+    fn find() {
+        let mut entry_1 = POEntry::new(0);
+        entry_1.msgid = "msgid 1".to_string();
+        entry_1.msgstr = Some("msgstr 1".to_string());
+
+        let mut entry_2 = POEntry::new(3);
+        entry_2.msgid = "msgid 2".to_string();
+        entry_2.msgstr = Some("msgstr 2".to_string());
+
+        let mut file = POFile::from(vec![&entry_1, &entry_2]);
+        assert_eq!(file.entries.len(), 2);
+
+        // find by msgid
+        assert_eq!(
+            file.find_by_msgid("msgid 2").unwrap().msgid,
+            "msgid 2"
+        );
+
+        // find by msgid and msgctxt
+        entry_2.msgctxt = Some("msgctxt 2".to_string());
+        entry_2.msgid = "msgid 1".to_string();
+        file.entries.push(entry_2);
+        assert_eq!(file.entries.len(), 3);
+        assert_eq!(
+            file.find_by_msgid_msgctxt("msgid 1", "msgctxt 2")
+                .unwrap()
+                .msgstr
+                .as_ref()
+                .unwrap(),
+            "msgstr 2",
+        );
+
+        // find by msgid_plural, msgctxt, msgid...
+        let mut entry_3 = POEntry::new(6);
+        entry_3.msgid = "msgid for msgid_plural 1".to_string();
+        entry_3.msgid_plural = Some("msgid_plural 1".to_string());
+        entry_3.msgctxt =
+            Some("msgctxt for msgid_plural 1".to_string());
+        file.entries.push(entry_3);
+
+        let mut entry_4 = POEntry::new(6);
+        entry_4.msgid = "msgid for msgid_plural 1".to_string();
+        entry_4.msgid_plural = Some("msgid_plural 1".to_string());
+        entry_4.msgctxt = Some("other_msgctxt".to_string());
+        file.entries.push(entry_4);
+
+        let entries = file.find(
+            "msgid for msgid_plural 1",
+            "msgid",
+            None,
+            false,
+        );
+        assert_eq!(entries.len(), 2);
+
+        let entries = file.find(
+            "msgid for msgid_plural 1",
+            "msgid",
+            Some("msgctxt for msgid_plural 1"),
+            false,
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].msgctxt.as_ref().unwrap(),
+            "msgctxt for msgid_plural 1"
+        );
+
+        // typed field
+        assert_eq!(
+            file.find_by_field(
+                "msgid_plural 1",
+                ByField::MsgidPlural,
+                Some("other_msgctxt"),
+            ),
+            vec![&file.entries[file.entries.len() - 1]],
+        );
+        assert_eq!(
+            file.find_by_field(
+                "msgid_plural 1",
+                ByField::MsgidPlural,
+                None
+            )
+            .len(),
+            2
+        );
+        assert!(file
+            .find("msgid_plural 1", "unknown", None, true)
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_escapes_are_unescaped_on_format() {
+        let path = "tests-data/escapes.po";
+        let file = pofile(path).unwrap();
+
+        let expected_content =
+            "\\ \t \r \u{8} \n \\\n \u{b} \u{c} \\\\";
+
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].msgid, expected_content);
+        assert_eq!(
+            file.entries[0].msgstr.as_ref().unwrap(),
+            expected_content,
+        );
+    }
+
+    #[test]
+    fn parse_and_format_escapes() {
+        let path = "tests-data/escapes.po";
+        let out_path = "tests-data/tests/parse_and_format_escapes.po";
+
+        let file = pofile(path).unwrap();
+        file.save(out_path).unwrap();
+
+        let escapes_content = fs::read_to_string(path).unwrap();
+        let out_content = fs::read_to_string(out_path).unwrap();
+
+        assert_eq!(
+            escapes_content.replace("\r\n", "\n"),
+            out_content
+        );
+    }
+
+    #[test]
+    fn detect_and_repair_mojibake() {
+        let entry_1 = POEntry::from(("Coffee", "CafÃ©"));
+        let entry_2 = POEntry::from(("Tea", "Té"));
+        let mut entry_3 = POEntry::from("One apple");
+        entry_3.msgid_plural = Some("%d apples".to_string());
+        entry_3.msgstr_plural = vec![
+            "Una manzana".to_string(),
+            "%d maÃ±anas".to_string(),
+        ];
+
+        let mut file =
+            POFile::from(vec![&entry_1, &entry_2, &entry_3]);
+
+        let detected = file.detect_mojibake();
+        assert_eq!(detected.len(), 2);
+        assert_eq!(detected[0].msgid, "Coffee");
+        assert_eq!(detected[1].msgid, "One apple");
+
+        file.repair_mojibake();
+        assert!(file.detect_mojibake().is_empty());
+        assert_eq!(file.entries[0].msgstr.as_ref().unwrap(), "Café");
+        assert_eq!(file.entries[1].msgstr.as_ref().unwrap(), "Té");
+        assert_eq!(file.entries[2].msgstr_plural[1], "%d mañanas");
+    }
+
+    #[test]
+    fn as_pot() {
+        let path = "tests-data/fuzzy-header.po";
+        let file = pofile(path).unwrap();
+        assert!(file.metadata_is_fuzzy);
+
+        let template = file.as_pot();
+        assert!(!template.metadata_is_fuzzy);
+        assert_eq!(template.metadata, file.metadata);
+        assert!(!template.to_string().contains("#, fuzzy"));
+
+        let file = pofile("tests-data/all.po").unwrap();
+        let template = file.as_pot();
+        assert_eq!(
+            template.entries.len(),
+            file.entries.len() - file.obsolete_entries().len(),
+        );
+        assert!(template.translated_entries().is_empty());
+        assert!(template.fuzzy_entries().is_empty());
+        assert_eq!(file.to_template(), template);
+
+        assert!(!POFile::new_pot("".into()).metadata_is_fuzzy);
+    }
+
+    #[test]
+    fn is_template() {
+        assert!(POFile::new_pot("".into()).is_template());
+        assert!(pofile("tests-data/template.pot")
+            .unwrap()
+            .is_template());
 
-        // Metadata should not include spaces after values
-        ret.push_str(&po_metadata_entry_to_string(
-            &self.metadata_as_entry(),
-            self.metadata_is_fuzzy,
-        ));
-        ret.push('\n');
+        let content = concat!(
+            "msgid \"foo\"\n",
+            "msgid_plural \"foos\"\n",
+            "msgstr[0] \"\"\n",
+            "msgstr[1] \"\"\n",
+        );
+        let mut file = pofile(content).unwrap();
+        assert!(file.is_template());
 
-        let mut entries_ret = String::new();
-        let mut obsolete_entries_ret = String::new();
-        for entry in &self.entries {
-            if entry.obsolete {
-                obsolete_entries_ret.push_str(&entry.to_string());
-                obsolete_entries_ret.push('\n');
-            } else {
-                entries_ret.push_str(&entry.to_string());
-                entries_ret.push('\n');
+        file.entries[0].msgstr_plural[1] = "oofs".to_string();
+        assert!(!file.is_template());
+        assert!(file.as_pot().is_template());
+
+        // templates don't contain obsolete entries
+        let file = pofile("tests-data/obsoletes.po").unwrap();
+        let mut template = file.as_pot();
+        assert!(template.is_template());
+        template.entries[0].obsolete = true;
+        assert!(!template.is_template());
+    }
+
+    #[test]
+    fn text_len_equals_string_len() {
+        let mut n_files = 0;
+        for dir_entry in fs::read_dir("tests-data").unwrap() {
+            let path = dir_entry.unwrap().path();
+            if path.extension().unwrap_or_default() != "po" {
+                continue;
+            }
+            if let Ok(file) = pofile(path.to_str().unwrap()) {
+                assert_eq!(
+                    file.text_len(),
+                    file.to_string().len(),
+                    "{}",
+                    path.display(),
+                );
+                n_files += 1;
             }
         }
-        ret.push_str(&entries_ret);
-        ret.push_str(&obsolete_entries_ret);
-        ret.pop();
-        write!(f, "{}", ret)
+        assert!(n_files > 0);
     }
-}
 
-// Method `save_as_pofile` is implemented in the trait
-impl SaveAsPOFile for POFile {}
+    #[test]
+    fn review_export() {
+        let content = r#"#
+msgid ""
+msgstr ""
 
-impl Save for POFile {
-    /// Save the PO file as the given path
-    fn save(&self, path: &str) {
-        self.save_as_pofile(path);
-    }
-}
+#: src/main.rs:10
+#, fuzzy
+#| msgid "Hello"
+msgid "Hello world"
+msgstr "Hola"
 
-impl SaveAsMOFile for POFile {
-    /// Save the PO file as a MO file as the given path
-    fn save_as_mofile(&self, path: &str) {
-        MOFile::from(self).save(path);
-    }
-}
+#: src/main.rs:20
+msgid "Goodbye"
+msgstr ""
 
-impl<'a> From<&'a str> for POFile {
-    fn from(path_or_content: &'a str) -> Self {
-        pofile(path_or_content).unwrap()
+msgid "Translated"
+msgstr "Traducido"
+
+#~ msgid "Obsolete"
+#~ msgstr ""
+"#;
+        let file = pofile(content).unwrap();
+        let export = file.review_export();
+
+        assert_eq!(export.entries.len(), 2);
+        assert!(export.translated_entries().is_empty());
+        assert!(export.obsolete_entries().is_empty());
+
+        let fuzzy_entry = &export.entries[0];
+        assert_eq!(fuzzy_entry.msgid, "Hello world");
+        assert_eq!(
+            fuzzy_entry.previous_msgid.as_ref().unwrap(),
+            "Hello"
+        );
+        assert_eq!(
+            fuzzy_entry.occurrences,
+            vec![("src/main.rs".to_string(), "10".to_string())],
+        );
+
+        let untranslated_entry = &export.entries[1];
+        assert_eq!(untranslated_entry.msgid, "Goodbye");
+        assert!(untranslated_entry.previous_msgid.is_none());
+        assert_eq!(untranslated_entry.occurrences.len(), 1);
     }
-}
 
-impl Merge for POFile {
-    /// Merge another PO file into this one and return a new one
-    ///
-    /// Recursively calls `merge` on each entry if they are found
-    /// in the current file searching by msgid and msgctxt. If not
-    /// found, generates a new entry.
-    ///
-    /// This method is commonly used to merge a POT reference file
-    /// with a PO file.
-    fn merge(&mut self, other: POFile) {
-        for other_entry in other.entries.as_slice() {
-            let entry: Option<POEntry> = match other_entry.msgctxt {
-                Some(ref msgctxt) => self.find_by_msgid_msgctxt(
-                    &other_entry.msgid,
-                    msgctxt,
-                ),
-                None => self.find_by_msgid(&other_entry.msgid),
-            };
+    #[test]
+    fn metadata_newlines() {
+        let file = pofile("tests-data/metadata.po").unwrap();
+        let file_as_string = file.to_string();
+        let header = file_as_string.split("\n\n").next().unwrap();
 
-            if let Some(e) = entry {
-                let mut entry = e;
-                entry.merge(other_entry.clone());
-            } else {
-                let mut entry = POEntry::new(0);
-                entry.merge(other_entry.clone());
-                self.entries.push(entry);
+        let mut n_fields = 0;
+        for line in header.lines().skip_while(|l| l.starts_with('#'))
+        {
+            if line.starts_with("msg") {
+                continue;
             }
+            // each field is `"Key: value\n"`
+            assert!(line.ends_with("\\n\""), "{}", line);
+            assert!(line.contains(": "), "{}", line);
+            n_fields += 1;
         }
+        assert_eq!(n_fields, file.metadata.len());
+
+        // the header of MO files is terminated by a single newline
+        let mo_file = MOFile::from(&file);
+        let msgstr = mo_file.metadata_as_entry().msgstr.unwrap();
+        assert!(msgstr.ends_with('\n'));
+        assert!(!msgstr.ends_with("\n\n"));
+        assert_eq!(msgstr.matches('\n').count(), file.metadata.len());
+    }
+
+    #[test]
+    fn to_string_preallocated() {
+        for path in [
+            "tests-data/all.po",
+            "tests-data/django-complete.po",
+            "tests-data/obsoletes.po",
+        ] {
+            let file = pofile(path).unwrap();
 
-        let self_entries: &mut Vec<POEntry> = self.entries.as_mut();
-        for entry in self_entries {
-            if other.find_by_msgid(&entry.msgid).is_none() {
-                entry.obsolete = true;
+            // formatted entry by entry without preallocation
+            let mut expected = file.header_to_string();
+            expected.push_str(&po_metadata_entry_to_string(
+                &file.metadata_as_entry(),
+                file.metadata_is_fuzzy,
+            ));
+            expected.push('\n');
+            let mut obsoletes = String::new();
+            for entry in &file.entries {
+                let target = match entry.obsolete {
+                    true => &mut obsoletes,
+                    false => &mut expected,
+                };
+                target.push_str(&entry.to_string());
+                target.push('\n');
             }
+            expected.push_str(&obsoletes);
+            expected.pop();
+
+            assert_eq!(file.to_string(), expected);
         }
     }
-}
 
-impl AsBytes for POFile {
-    /// Return the PO file content as a bytes vector of the MO file version
-    ///
-    /// The MO file is encoded with little
-    /// endian magic number and revision number 0
-    ///
-    /// Use directly [MOFile::as_bytes_with] to customize
-    /// the magic number and revision number:
-    ///
-    /// ```rust
-    /// use rspolib::{pofile, MAGIC_SWAPPED, MOFile};
-    ///
-    /// let file = pofile("tests-data/all.po").unwrap();
-    /// let bytes = MOFile::from(&file).as_bytes_with(MAGIC_SWAPPED, 1);
-    /// ```
-    fn as_bytes(&self) -> Cow<[u8]> {
-        let mofile = MOFile::from(self);
-        let result = mofile.as_bytes_with(MAGIC, 0);
-        Cow::Owned(result.into_owned())
-    }
+    #[test]
+    fn sort() {
+        let mut obsolete = POEntry::from("a");
+        obsolete.obsolete = true;
+        let mut with_msgctxt = POEntry::from("b");
+        with_msgctxt.msgctxt = Some("context".to_string());
+        let mut duplicated = POEntry::from("item 2");
+        duplicated.msgstr = Some("second".to_string());
 
-    /// Return the PO file content as a bytes vector of the MO file version
-    ///
-    /// Just an alias for [POFile::as_bytes], for consistency with [MOFile].
-    fn as_bytes_le(&self) -> Cow<[u8]> {
-        self.as_bytes()
-    }
+        let mut file = POFile::from(vec![
+            &obsolete,
+            &POEntry::from("item 10"),
+            &with_msgctxt,
+            &POEntry::from("item 2"),
+            &duplicated,
+            &POEntry::from("c"),
+        ]);
+        file.sort();
 
-    /// Return the PO file content as a bytes vector of
-    /// the MO file version with big endianess
-    fn as_bytes_be(&self) -> Cow<[u8]> {
-        let mofile = MOFile::from(self);
-        let result = mofile.as_bytes_with(MAGIC_SWAPPED, 0);
-        Cow::Owned(result.into_owned())
+        let msgids = file
+            .entries
+            .iter()
+            .map(|e| e.msgid.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            msgids,
+            vec!["c", "item 2", "item 2", "item 10", "b", "a"]
+        );
+        assert!(file.entries[5].obsolete);
+        // stable
+        assert_eq!(file.entries[1].msgstr.as_deref(), None);
+        assert_eq!(file.entries[2].msgstr.as_deref(), Some("second"));
+
+        // custom key, obsoletes are kept at the end
+        file.sort_by(|a, b| b.msgid.cmp(&a.msgid));
+        let msgids = file
+            .entries
+            .iter()
+            .map(|e| e.msgid.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            msgids,
+            vec!["item 2", "item 2", "item 10", "c", "b", "a"]
+        );
     }
-}
 
-impl From<Vec<&POEntry>> for POFile {
-    fn from(entries: Vec<&POEntry>) -> Self {
-        let mut file = POFile::new("".into());
-        for entry in entries {
-            file.entries.push(entry.clone());
+    #[test]
+    fn pofile_from_reader() {
+        let path = "tests-data/all.po";
+        let reader = fs::File::open(path).unwrap();
+        let file =
+            POFile::from_reader(reader, FileOptions::default())
+                .unwrap();
+        assert_eq!(file.entries, pofile(path).unwrap().entries);
+        assert_eq!(
+            file.to_string(),
+            pofile(path).unwrap().to_string()
+        );
+
+        // errors reading are reported
+        struct FailingReader(usize);
+        impl Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(io::Error::other("connection reset"));
+                }
+                self.0 -= 1;
+                let line = b"# comment\n";
+                buf[..line.len()].copy_from_slice(line);
+                Ok(line.len())
+            }
         }
-        file
+        let err = POFile::from_reader(
+            FailingReader(2),
+            FileOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("error reading content: connection reset"));
     }
-}
 
-impl From<&Path> for POFile {
-    fn from(path: &std::path::Path) -> Self {
-        POFile::from(path.to_str().unwrap())
+    #[test]
+    fn multiline_style() {
+        let content = concat!(
+            "msgid \"\"\n",
+            "\"First line\\n\"\n",
+            "\"Second line\"\n",
+            "msgstr \"\"\n",
+        );
+        let file = pofile(FileOptions {
+            multiline_style: MultilineStyle::PreserveLines,
+            ..FileOptions::from(content)
+        })
+        .unwrap();
+        let file_as_string = file.to_string();
+        assert!(file_as_string.ends_with(content));
+        assert_eq!(file.text_len(), file_as_string.len());
+
+        let file = pofile(content).unwrap();
+        assert!(file.to_string().ends_with(
+            "msgid \"First line\\nSecond line\"\nmsgstr \"\"\n"
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::file::mofile::mofile;
-    use std::fs;
-    use std::path::Path;
-    use unicode_width::UnicodeWidthStr;
+    #[test]
+    fn occurrences_style() {
+        let content = concat!(
+            "#: src/main.rs:1 src/lib.rs:2\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+        );
+        let file = pofile(content).unwrap();
+        assert!(file.to_string().ends_with(content));
+
+        let file = pofile(FileOptions {
+            occurrences_style: crate::OccurrencesStyle::OnePerLine,
+            ..FileOptions::from(content)
+        })
+        .unwrap();
+        let file_as_string = file.to_string();
+        assert!(file_as_string.ends_with(concat!(
+            "#: src/main.rs:1\n",
+            "#: src/lib.rs:2\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+        )));
+        assert_eq!(file.text_len(), file_as_string.len());
+    }
 
     #[test]
-    fn pofile_test() {
-        let path = "tests-data/all.po";
-        let file = pofile(path).unwrap();
+    fn gettext_plural() {
+        let content = r#"msgid ""
+msgstr ""
+"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\n"
 
-        assert_eq!(file.entries.len(), 9);
+msgid "file"
+msgid_plural "files"
+msgstr[0] "plik"
+msgstr[1] "pliki"
+msgstr[2] "plików"
+"#;
+        let mut file = pofile(content).unwrap();
+        assert_eq!(file.gettext_plural("file", 1), Some("plik"));
+        assert_eq!(file.gettext_plural("file", 3), Some("pliki"));
+        assert_eq!(file.gettext_plural("file", 5), Some("plików"));
+        assert_eq!(file.gettext_plural("file", 22), Some("pliki"));
+        assert_eq!(file.gettext_plural("unknown", 1), None);
+
+        // falls back to the first form
+        file.metadata.insert(
+            "Plural-Forms".to_string(),
+            "nplurals=3; plural=n +;".to_string(),
+        );
+        assert_eq!(file.gettext_plural("file", 5), Some("plik"));
+        file.metadata.remove("Plural-Forms");
+        assert_eq!(file.gettext_plural("file", 5), Some("plik"));
     }
 
     #[test]
-    fn pofile_metadata_as_entry() {
-        // File with metadata
-        let path = "tests-data/all.po";
-        let file = pofile(path).unwrap();
-        let entry = file.metadata_as_entry();
+    fn to_string_vcs() {
+        let file = pofile("tests-data/all.po").unwrap();
+        let vcs_output = file.to_string_vcs();
+        assert!(!vcs_output.contains("POT-Creation-Date"));
+        assert!(!vcs_output.contains("PO-Revision-Date"));
+        assert!(vcs_output.contains("Project-Id-Version: django"));
 
-        assert_eq!(entry.msgid, "");
-        assert_eq!(entry.msgstr.unwrap().lines().count(), 11);
+        // updating the dates
+        let mut edited = file.clone();
+        edited.metadata.insert(
+            "PO-Revision-Date".to_string(),
+            "2030-01-01 00:00+0000".to_string(),
+        );
+        edited.metadata.insert(
+            "POT-Creation-Date".to_string(),
+            "2030-01-01 00:00+0000".to_string(),
+        );
+        assert_ne!(edited.to_string(), file.to_string());
+        assert_eq!(edited.to_string_vcs(), vcs_output);
 
-        // File without metadata
-        let path = "tests-data/empty-metadata.po";
-        let file = pofile(path).unwrap();
-        let entry = file.metadata_as_entry();
+        // reordering the entries
+        let mut edited = file.clone();
+        edited.entries.reverse();
+        assert_ne!(edited.to_string(), file.to_string());
+        assert_eq!(edited.to_string_vcs(), vcs_output);
 
-        assert_eq!(entry.msgid, "");
-        assert_eq!(entry.msgstr.is_none(), true);
+        // but changing a translation is reflected
+        let mut edited = file.clone();
+        edited.entries[0].msgstr = Some("changed".to_string());
+        assert_ne!(edited.to_string_vcs(), vcs_output);
+    }
 
-        // File with fuzzy metadata
-        let path = "tests-data/fuzzy-header.po";
+    #[test]
+    fn pofile_encoding() {
+        let file = pofile("tests-data/all.po").unwrap();
+        assert_eq!(file.encoding, "UTF-8");
+
+        let path = "tests-data/latin1.po";
         let file = pofile(path).unwrap();
-        let entry = file.metadata_as_entry();
+        assert_eq!(file.encoding, "windows-1252");
+        assert_eq!(file.metadata["Last-Translator"], "José Muñoz");
+        assert_eq!(
+            file.find_by_msgid("Spain").unwrap().msgstr,
+            Some("España".to_string()),
+        );
 
-        assert_eq!(entry.msgid, "");
-        assert_eq!(entry.fuzzy(), true);
-        assert_eq!(entry.msgstr.unwrap().lines().count(), 12);
+        let reader = fs::File::open(path).unwrap();
+        let from_reader =
+            POFile::from_reader(reader, FileOptions::default())
+                .unwrap();
+        assert_eq!(from_reader.encoding, "windows-1252");
+        assert_eq!(from_reader.entries, file.entries);
+
+        // invalid UTF-8 content is still reported
+        let err = POFile::from_reader(
+            &b"msgid \"Spain\"\nmsgstr \"Espa\xf1a\"\n"[..],
+            FileOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("valid UTF-8"));
     }
 
     #[test]
-    fn metadata_keys_are_natural_sorted() {
-        let path = "tests-data/natural-unsorted-metadata.po";
-        let file = pofile(path).unwrap();
+    fn pofile_encoding_round_trip() {
+        let file = pofile("tests-data/latin1.po").unwrap();
 
-        file.save("foobar-2-out.po");
+        let path = "tests-data/tests/latin1-round-trip.po";
+        file.save(path).unwrap();
+        let saved = pofile(path).unwrap();
+        fs::remove_file(path).ok();
+        assert_eq!(saved.encoding, "UTF-8");
         assert_eq!(
-            file.to_string(),
-            "#
-msgid \"\"
-msgstr \"\"
-\"Project-Id-Version: PACKAGE VERSION\\n\"
-\"Report-Msgid-Bugs-To: \\n\"
-\"Language-Team: LANGUAGE <LL@li.org>\\n\"
-\"Content-Type: text/plain; charset=UTF-8\\n\"
-\"Content-Transfer-Encoding: 8bit\\n\"
-\"X-Poedit-SearchPath-1: Foo\\n\"
-\"X-Poedit-SearchPath-2: Bar\\n\"
-\"X-Poedit-SearchPath-10: Baz\\n\"
-",
+            saved.metadata["Content-Type"],
+            "text/plain; charset=UTF-8",
+        );
+        assert_eq!(saved.metadata["Last-Translator"], "José Muñoz");
+        assert_eq!(saved.entries, file.entries);
+
+        let path = "tests-data/tests/latin1-round-trip.mo";
+        file.save_as_mofile(path).unwrap();
+        let saved = mofile(path).unwrap();
+        fs::remove_file(path).ok();
+        assert_eq!(saved.encoding, "UTF-8");
+        assert_eq!(
+            saved.find_by_msgid("Spain").unwrap().msgstr,
+            Some("España".to_string()),
         );
     }
 
     #[test]
-    fn pofile_percent_translated() {
-        let path = "tests-data/2-translated-entries.po";
-        let file = pofile(path).unwrap();
+    fn pofile_from_reader_with_path() {
+        let content = "msgid \"Hello\"\nmsgstr \"Ho\"la\"\n";
+        let err = POFile::from_reader_with_path(
+            content.as_bytes(),
+            "app.po",
+            FileOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            concat!(
+                "Syntax error found in file app.po at line 2,",
+                " column 11: unescaped double quote found",
+            ),
+        );
 
-        assert_eq!(file.percent_translated(), 40 as f32);
+        let file = POFile::from_reader_with_path(
+            fs::File::open("tests-data/all.po").unwrap(),
+            "all.po",
+            FileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(file.entries.len(), 9);
+        assert_eq!(file.options.path_or_content, "all.po");
     }
 
     #[test]
-    fn pofile_translated_entries() {
-        let path = "tests-data/2-translated-entries.po";
-        let file = pofile(path).unwrap();
+    fn append() {
+        let mut entry_with_msgctxt = POEntry::from("Hello");
+        entry_with_msgctxt.msgctxt = Some("greeting".to_string());
 
-        let translated_entries = file.translated_entries();
-        assert_eq!(file.entries.len(), 5);
-        assert_eq!(translated_entries.len(), 2);
-        assert_eq!(file.entries[0].msgid, "msgid 1");
-        assert_eq!(translated_entries[0].msgid, "msgid 2");
+        // duplicates are allowed by default
+        let mut file = POFile::new(FileOptions::default());
+        file.append(POEntry::from("Hello")).unwrap();
+        file.append(POEntry::from("Hello")).unwrap();
+        assert_eq!(file.entries.len(), 2);
+
+        let mut file = POFile::new(FileOptions {
+            check_for_duplicates: true,
+            ..Default::default()
+        });
+        file.append(POEntry::from("Hello")).unwrap();
+        file.append(entry_with_msgctxt.clone()).unwrap();
+        assert_eq!(
+            file.append(POEntry::from("Hello")),
+            Err(SyntaxError::DuplicatedEntry {
+                msgid: "Hello".to_string(),
+                msgctxt: None,
+            }),
+        );
+        let err = file.append(entry_with_msgctxt).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            concat!(
+                "Duplicated entry found with msgid \"Hello\"",
+                " and msgctxt \"greeting\"",
+            ),
+        );
+        assert_eq!(file.entries.len(), 2);
     }
 
     #[test]
-    fn pofile_untranslated_entries() {
-        let path = "tests-data/2-translated-entries.po";
-        let file = pofile(path).unwrap();
+    fn extend_from() {
+        struct Message<'a> {
+            context: &'a str,
+            source: &'a str,
+            target: &'a str,
+        }
 
-        let untranslated_entries = file.untranslated_entries();
-        assert_eq!(file.entries.len(), 5);
-        assert_eq!(untranslated_entries.len(), 3);
-        assert_eq!(file.entries[0].msgid, "msgid 1");
-        assert_eq!(untranslated_entries[0].msgid, "msgid 1");
-        assert_eq!(untranslated_entries[1].msgid, "msgid 3");
+        impl IntoPOEntry for Message<'_> {
+            fn into_poentry(self) -> POEntry {
+                let mut entry =
+                    POEntry::from((self.source, self.target));
+                entry.msgctxt = Some(self.context.to_string());
+                entry
+            }
+        }
+
+        let mut file = POFile::new(FileOptions::default());
+        file.extend_from(vec![
+            Message {
+                context: "menu",
+                source: "Open",
+                target: "Abrir",
+            },
+            Message {
+                context: "menu",
+                source: "Close",
+                target: "Cerrar",
+            },
+        ]);
+        file.extend_from(["Untranslated"]);
+
+        assert_eq!(file.entries.len(), 3);
+        assert_eq!(
+            file.find_by_msgid_msgctxt("Close", "menu")
+                .unwrap()
+                .msgstr
+                .as_deref(),
+            Some("Cerrar"),
+        );
+        assert!(file.to_string().ends_with(concat!(
+            "msgctxt \"menu\"\n",
+            "msgid \"Close\"\n",
+            "msgstr \"Cerrar\"\n",
+            "\n",
+            "msgid \"Untranslated\"\n",
+            "msgstr \"\"\n",
+        )));
     }
 
     #[test]
-    fn pofile_obsolete_entries() {
-        let path = "tests-data/obsoletes.po";
-        let file = pofile(path).unwrap();
+    fn dedup_occurrences() {
+        let content = "#: src/a.rs:1 src/b.rs:2 src/a.rs:1
+#: src/c.rs:3 src/b.rs:2 src/a.rs:10
+msgid \"foo\"
+msgstr \"\"
 
-        let obsolete_entries = file.obsolete_entries();
-        assert_eq!(file.entries.len(), 3);
-        assert_eq!(obsolete_entries.len(), 2);
+#: src/a.rs:1
+msgid \"bar\"
+msgstr \"\"
+";
+        let mut file = pofile(content).unwrap();
+        assert_eq!(file.entries[0].occurrences.len(), 6);
+
+        file.dedup_occurrences();
+        assert_eq!(
+            file.entries[0].occurrences,
+            vec![
+                ("src/a.rs".to_string(), "1".to_string()),
+                ("src/b.rs".to_string(), "2".to_string()),
+                ("src/c.rs".to_string(), "3".to_string()),
+                ("src/a.rs".to_string(), "10".to_string()),
+            ],
+        );
+        assert_eq!(file.entries[1].occurrences.len(), 1);
+        assert!(file.to_string().contains(
+            "#: src/a.rs:1 src/b.rs:2 src/c.rs:3 src/a.rs:10\n"
+        ));
     }
 
     #[test]
-    fn pofile_to_string() {
-        let po_path = "tests-data/all.po";
-        let file = pofile(po_path).unwrap();
+    fn strip_all_comments_and_occurrences() {
+        let mut file =
+            pofile("tests-data/comment-ordering.po").unwrap();
+        assert!(file
+            .entries
+            .iter()
+            .any(|entry| entry.comment.is_some()
+                || entry.tcomment.is_some()));
 
+        file.strip_all_comments();
         let file_as_string = file.to_string();
-
         for line in file_as_string.lines() {
-            let width = UnicodeWidthStr::width(line);
-            assert!(width <= file.options.wrapwidth + 2);
+            assert!(!line.starts_with("#."), "{}", line);
+            assert!(!line.starts_with("# "), "{}", line);
+        }
+
+        let mut file =
+            pofile("tests-data/weird-occurrences.po").unwrap();
+        assert!(file
+            .entries
+            .iter()
+            .any(|entry| !entry.occurrences.is_empty()));
+
+        file.strip_all_occurrences();
+        for line in file.to_string().lines() {
+            assert!(!line.starts_with("#:"), "{}", line);
         }
     }
 
-    fn pofile_save_test(save_fn_name: &str, fname: &str) {
-        let tmpdir = "tests-data/tests";
+    #[test]
+    fn unsorted_metadata() {
+        let path = "tests-data/metadata.po";
+        let file = pofile(FileOptions {
+            sort_metadata: false,
+            ..FileOptions::from(path)
+        })
+        .unwrap();
+        let keys_from_header = |content: &str| {
+            content
+                .split("\n\n")
+                .next()
+                .unwrap()
+                .lines()
+                .filter(|line| {
+                    line.starts_with("\"") && line.len() > 2
+                })
+                .map(|line| {
+                    line[1..].split(':').next().unwrap().to_string()
+                })
+                .collect::<Vec<String>>()
+        };
 
-        let path = "tests-data/all.po";
-        let file = pofile(path).unwrap();
-        let file_as_string = file.to_string();
+        let input_keys =
+            keys_from_header(&fs::read_to_string(path).unwrap());
+        assert_eq!(file.metadata_order, input_keys);
+        assert_eq!(keys_from_header(&file.to_string()), input_keys);
 
-        // Here the file name is parametrized to avoid data races
-        // when running tests in parallel
-        let tmp_path = Path::new(&tmpdir).join(fname);
-        let tmp_path_str = tmp_path.to_str().unwrap();
+        // sorted by default
+        let sorted_file = pofile(path).unwrap();
+        assert_ne!(
+            keys_from_header(&sorted_file.to_string()),
+            input_keys
+        );
+    }
 
-        if save_fn_name == "save" {
-            file.save(tmp_path_str);
-        } else {
-            file.save_as_pofile(tmp_path_str);
-        }
+    #[test]
+    fn custom_metadata_order() {
+        let mut file = pofile(FileOptions {
+            sort_metadata: false,
+            ..FileOptions::from("tests-data/metadata.po")
+        })
+        .unwrap();
+        file.metadata.insert(
+            "X-Generator".to_string(),
+            "Poedit 3.4".to_string(),
+        );
+        file.set_metadata_order(vec![
+            "X-Generator".to_string(),
+            "Language".to_string(),
+            "Unknown".to_string(),
+        ]);
 
+        let keys = file
+            .metadata_as_entry()
+            .msgstr
+            .unwrap()
+            .lines()
+            .map(|line| line.split(':').next().unwrap().to_string())
+            .collect::<Vec<_>>();
         assert_eq!(
-            file_as_string,
-            fs::read_to_string(tmp_path_str).unwrap()
+            keys,
+            vec![
+                "X-Generator",
+                "Language",
+                "Content-Transfer-Encoding",
+                "Content-Type",
+                "Language-Team",
+                "Last-Translator",
+                "MIME-Version",
+                "PO-Revision-Date",
+                "POT-Creation-Date",
+                "Plural-Forms",
+                "Project-Id-Version",
+                "Report-Msgid-Bugs-To",
+            ]
         );
-        fs::remove_file(tmp_path_str).ok();
+        assert!(file.to_string().contains(
+            "msgstr \"\"\n\"X-Generator: Poedit 3.4\\n\"\n"
+        ));
     }
 
     #[test]
-    fn pofile_save() {
-        pofile_save_test("save", "all-1.po")
+    fn percent_translated_cache() {
+        let mut file = POFile::new(FileOptions::default());
+        file.append(POEntry::from(("msgid 1", "msgstr 1"))).unwrap();
+        file.append(POEntry::from("msgid 2")).unwrap();
+        assert_eq!(file.percent_translated(), 50.0);
+
+        file.append(POEntry::from(("msgid 3", "msgstr 3"))).unwrap();
+        file.append(POEntry::from(("msgid 4", "msgstr 4"))).unwrap();
+        assert_eq!(file.percent_translated(), 75.0);
+
+        file.remove_by_msgid("msgid 2");
+        assert_eq!(file.percent_translated(), 100.0);
+
+        // direct edits are not detected until marked as dirty
+        file.entries[0].msgstr = None;
+        assert_eq!(file.percent_translated(), 100.0);
+        file.mark_dirty();
+        let percent = file.percent_translated();
+        assert!((percent - 66.67).abs() < 0.01);
+
+        // edits through `entries_mut` are detected
+        for entry in file.entries_mut() {
+            entry.msgstr = Some("translated".to_string());
+        }
+        assert_eq!(file.percent_translated(), 100.0);
+
+        // the cache is ignored comparing files
+        let mut other = file.clone();
+        other.mark_dirty();
+        assert_eq!(file, other);
     }
 
     #[test]
-    fn pofile_save_as_pofile() {
-        pofile_save_test("save_as_pofile", "all-2.po")
+    fn init_header() {
+        let mut file = POFile::new(FileOptions {
+            sort_metadata: false,
+            ..Default::default()
+        });
+        file.metadata
+            .insert("X-Generator".to_string(), "rspolib".to_string());
+        file.metadata_order.push("X-Generator".to_string());
+        file.init_header(HeaderInit {
+            project: "foo 1.0".to_string(),
+            language: "es".to_string(),
+            last_translator: "John Doe <john@example.com>"
+                .to_string(),
+            charset: "ISO-8859-1".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let output = file.to_string();
+        let positions = METADATA_KEYS_ORDER
+            .iter()
+            .chain(["X-Generator"].iter())
+            .map(|key| output.find(&format!("\"{}: ", key)).unwrap())
+            .collect::<Vec<usize>>();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        assert!(output.contains("\"Project-Id-Version: foo 1.0\\n\""));
+        assert!(output.contains("\"MIME-Version: 1.0\\n\""));
+        // files are written as UTF-8 whatever the charset
+        assert_eq!(
+            file.metadata["Content-Type"],
+            "text/plain; charset=ISO-8859-1",
+        );
+        assert!(output.contains(
+            "\"Content-Type: text/plain; charset=UTF-8\\n\""
+        ));
+        assert!(
+            output.contains("\"Content-Transfer-Encoding: 8bit\\n\"")
+        );
+        assert_eq!(file.last_translator().unwrap().name, "John Doe");
+        assert_eq!(file.metadata["Language"], "es");
+        assert_eq!(file.metadata["Report-Msgid-Bugs-To"], "");
+
+        // invalid plural forms are rejected
+        let mut file = POFile::new(FileOptions::default());
+        let result = file.init_header(HeaderInit {
+            plural_forms: "plural=n;".to_string(),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        assert!(file.metadata.is_empty());
+        assert!(file.metadata_order.is_empty());
     }
 
     #[test]
-    fn pofile_save_as_mofile() {
-        let tmpdir = "tests-data/tests";
+    fn apply_language_plurals() {
+        let content = concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Plural-Forms: nplurals=2; plural=(n != 1);\\n\"\n",
+            "\n",
+            "msgid \"file\"\n",
+            "msgid_plural \"files\"\n",
+            "msgstr[0] \"файл\"\n",
+            "msgstr[1] \"файла\"\n",
+            "\n",
+            "msgid \"directory\"\n",
+            "msgid_plural \"directories\"\n",
+            "msgstr[0] \"\"\n",
+            "msgstr[1] \"\"\n",
+            "\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Привет\"\n",
+        );
+        let mut file = pofile(content).unwrap();
+        let translations = file.entries[0].msgstr_plural.clone();
 
-        let content =
-            concat!("msgid \"foo bar\"\n", "msgstr \"foo bar\"\n",);
-        let po_file = pofile(content).unwrap();
+        assert!(file.apply_language_plurals("ru"));
+        assert!(
+            file.metadata["Plural-Forms"].starts_with("nplurals=3;")
+        );
+        for entry in &file.entries[..2] {
+            assert_eq!(entry.msgstr_plural.len(), 3);
+            assert_eq!(entry.msgstr_plural[2], "");
+        }
+        assert!(file.entries[2].msgstr_plural.is_empty());
+        assert_eq!(file.entries[0].msgstr_plural[..2], translations);
+        assert_eq!(
+            file.gettext_plural(&file.entries[0].msgid, 5),
+            Some("")
+        );
 
-        let tmp_path = Path::new(&tmpdir)
-            .join("pofile_save_as_mofile-simple.mo");
-        let tmp_path_str = tmp_path.to_str().unwrap();
-        po_file.save_as_mofile(tmp_path_str);
+        // unknown languages don't modify the file
+        let mut unchanged = file.clone();
+        assert!(!unchanged.apply_language_plurals("xx"));
+        assert_eq!(unchanged, file);
+    }
 
-        assert!(tmp_path.exists());
+    #[test]
+    fn merge_with() {
+        let file = pofile(concat!(
+            "#: old.rs:1\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+            "\n",
+            "msgid \"Bye\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"Removed\"\n",
+            "msgstr \"Eliminado\"\n",
+        ))
+        .unwrap();
+        let other = pofile(concat!(
+            "#: new.rs:2\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Buenas\"\n",
+            "\n",
+            "msgid \"Bye\"\n",
+            "msgstr \"Adiós\"\n",
+            "\n",
+            "msgid \"New\"\n",
+            "msgstr \"\"\n",
+        ))
+        .unwrap();
 
-        let mo_file = mofile(tmp_path_str).unwrap();
-        assert_eq!(mo_file.entries.len(), po_file.entries.len());
-        assert_eq!(mo_file.metadata.len(), po_file.metadata.len());
+        // default options, also used by `merge`
+        let mut merged = file.clone();
+        merged.merge_with(other.clone(), MergeOptions::default());
+        let mut merged_by_trait = file.clone();
+        merged_by_trait.merge(other.clone());
+        assert_eq!(merged, merged_by_trait);
 
-        assert_eq!(mo_file.entries[0].msgid, "foo bar");
+        assert_eq!(merged.entries.len(), 4);
+        let hello = merged.find_by_msgid("Hello").unwrap();
+        assert_eq!(hello.msgstr.as_deref(), Some("Hola"));
         assert_eq!(
-            mo_file.entries[0].msgstr.as_ref().unwrap(),
-            "foo bar"
+            hello.occurrences,
+            vec![("new.rs".to_string(), "2".to_string())]
+        );
+        let bye = merged.find_by_msgid("Bye").unwrap();
+        assert_eq!(bye.msgstr.as_deref(), Some("Adiós"));
+        assert!(merged.find_by_msgid("Removed").unwrap().obsolete);
+        assert!(!merged.find_by_msgid("New").unwrap().obsolete);
+
+        // overwrite translations
+        let mut merged = file.clone();
+        merged.merge_with(
+            other.clone(),
+            MergeOptions {
+                overwrite_translations: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            merged.find_by_msgid("Hello").unwrap().msgstr.as_deref(),
+            Some("Buenas"),
+        );
+
+        // don't mark obsolete nor add new entries
+        let mut merged = file.clone();
+        merged.merge_with(
+            other,
+            MergeOptions {
+                mark_obsolete: false,
+                add_new: false,
+                ..Default::default()
+            },
         );
+        assert_eq!(merged.entries.len(), 3);
+        assert!(!merged.find_by_msgid("Removed").unwrap().obsolete);
+        assert!(merged.find_by_msgid("New").is_none());
     }
 
     #[test]
-    fn set_fuzzy() {
-        let path = "tests-data/fuzzy-no-fuzzy.po";
+    fn merge_with_comments() {
+        let mut file = pofile(concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "# Translator note\n",
+            "#. Old extracted comment\n",
+            "#: old.rs:1\n",
+            "#, fuzzy\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+        ))
+        .unwrap();
+        let template = pofile(concat!(
+            "#. New extracted comment\n",
+            "#: new.rs:2\n",
+            "#, python-format\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"\"\n",
+        ))
+        .unwrap();
 
-        let mut file = pofile(path).unwrap();
+        let stats = file.merge_with(
+            template,
+            MergeOptions {
+                merge_comments: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(stats.updated, 1);
+        let entry = &file.entries[0];
+        assert_eq!(entry.msgstr.as_deref(), Some("Hola"));
+        assert_eq!(
+            entry.tcomment.as_deref(),
+            Some("Translator note")
+        );
+        assert_eq!(
+            entry.comment.as_deref(),
+            Some("New extracted comment")
+        );
+        assert_eq!(entry.occurrences_line(), "old.rs:1 new.rs:2");
+        assert_eq!(entry.flags, vec!["fuzzy", "python-format"]);
+    }
 
-        assert!(!file.entries[0].fuzzy());
-        assert!(file.entries[1].fuzzy());
+    #[test]
+    fn metadata_get_set_remove() {
+        let mut file = pofile(concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Project-Id-Version: foo\\n\"\n",
+            "\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+            "\"X-Generator: bar\\n\"\n",
+        ))
+        .unwrap();
+        file.options.sort_metadata = false;
 
-        // set fuzzy
-        file.entries[0].flags.push("fuzzy".to_string());
+        assert_eq!(
+            file.metadata_get("Project-Id-Version"),
+            Some("foo")
+        );
+        assert_eq!(file.metadata_get("Language"), None);
 
-        // unset fuzzy
-        let fuzzy_position = file.entries[1]
-            .flags
-            .iter()
-            .position(|p| p == "fuzzy")
+        // standard keys are placed in their canonical position
+        file.metadata_set("Language", "es").unwrap();
+        file.metadata_set("Plural-Forms", "nplurals=1; plural=0;")
             .unwrap();
-        file.entries[1].flags.remove(fuzzy_position);
+        file.metadata_set("X-Other", "baz").unwrap();
+        assert_eq!(
+            file.metadata_order,
+            vec![
+                "Project-Id-Version",
+                "Language",
+                "Content-Type",
+                "X-Generator",
+                "Plural-Forms",
+                "X-Other",
+            ]
+        );
 
-        assert!(file.entries[0].fuzzy());
-        assert!(!file.entries[1].fuzzy());
+        // existing keys keep their position
+        file.metadata_set("Project-Id-Version", "foo 2").unwrap();
+        assert_eq!(file.metadata_order[0], "Project-Id-Version");
+        assert_eq!(file.metadata["Project-Id-Version"], "foo 2");
 
+        // invalid plural forms are not set
+        assert!(matches!(
+            file.metadata_set(
+                "Plural-Forms",
+                "nplurals=1; plural=n;"
+            ),
+            Err(SyntaxError::InvalidPluralForms { .. })
+        ));
         assert_eq!(
-            file.entries[0].to_string(),
-            "#, fuzzy\nmsgid \"a\"\nmsgstr \"a\"\n",
+            file.metadata_get("Plural-Forms"),
+            Some("nplurals=1; plural=0;")
         );
+
         assert_eq!(
-            file.entries[1].to_string(),
-            "msgid \"Line\"\nmsgstr \"Ligne\"\n",
+            file.metadata_remove("X-Generator").as_deref(),
+            Some("bar")
         );
+        assert_eq!(file.metadata_remove("X-Generator"), None);
+        assert!(!file
+            .metadata_order
+            .contains(&"X-Generator".to_string()));
+        assert!(!file.to_string().contains("X-Generator"));
     }
 
     #[test]
-    fn format_fuzzy_metadata() {
-        let path = "tests-data/fuzzy-header.po";
+    fn dedup() {
+        let mut file = pofile(concat!(
+            "#: a.rs:1\n",
+            "msgid \"Open\"\n",
+            "msgstr \"Abrir\"\n",
+            "\n",
+            "msgctxt \"menu\"\n",
+            "msgid \"Open\"\n",
+            "msgstr \"Abrir menú\"\n",
+            "\n",
+            "#: a.rs:1 b.rs:2\n",
+            "#, fuzzy, c-format\n",
+            "msgid \"Open\"\n",
+            "msgstr \"Abre\"\n",
+            "\n",
+            "msgid \"Close\"\n",
+            "msgstr \"Cerrar\"\n",
+            "\n",
+            "#~ msgid \"Open\"\n",
+            "#~ msgstr \"Abrir\"\n",
+        ))
+        .unwrap();
+        assert_eq!(file.dedup(), 1);
+        assert_eq!(file.dedup(), 0);
 
-        let file = pofile(path).unwrap();
-        let expected_start = concat!(
-            "# Po file with\n# a fuzzy header\n#, fuzzy\n",
-            "msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version:",
+        let msgids = file
+            .entries
+            .iter()
+            .map(|e| (e.msgid.as_str(), e.msgctxt.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            msgids,
+            vec![
+                ("Open", None),
+                ("Open", Some("menu")),
+                ("Close", None),
+                ("Open", None),
+            ]
         );
-        assert!(file.to_string().starts_with(expected_start));
+        let open = &file.entries[0];
+        assert_eq!(open.msgstr.as_deref(), Some("Abrir"));
+        assert_eq!(
+            open.occurrences,
+            vec![
+                ("a.rs".to_string(), "1".to_string()),
+                ("b.rs".to_string(), "2".to_string()),
+            ]
+        );
+        // the kept translation doesn't become fuzzy
+        assert_eq!(open.flags, vec!["c-format".to_string()]);
+        assert!(!open.fuzzy());
+        assert!(file.entries[3].obsolete);
     }
 
     #[test]
-    fn format_comment_ordering() {
-        let path = "tests-data/comment-ordering.po";
+    fn merge_report() {
+        let mut file = pofile(concat!(
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+            "\n",
+            "msgctxt \"verb\"\n",
+            "msgid \"File\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"day\"\n",
+            "msgid_plural \"days\"\n",
+            "msgstr[0] \"día\"\n",
+            "msgstr[1] \"días\"\n",
+        ))
+        .unwrap();
+        let other = pofile(concat!(
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgctxt \"verb\"\n",
+            "msgid \"File\"\n",
+            "msgstr \"Archivar\"\n",
+            "\n",
+            "msgid \"File\"\n",
+            "msgstr \"Archivo\"\n",
+            "\n",
+            "msgid \"day\"\n",
+            "msgid_plural \"days\"\n",
+            "msgstr[0] \"jornada\"\n",
+            "msgstr[1] \"días\"\n",
+            "\n",
+            "#~ msgid \"Old\"\n",
+            "#~ msgstr \"Viejo\"\n",
+        ))
+        .unwrap();
 
-        let file = pofile(path).unwrap();
-        let expected_content = r#"#
-msgid ""
-msgstr ""
-"Content-Type: text/plain; charset=UTF-8\n"
+        let report = file.merge_report(&other);
+        assert_eq!(report.added, vec![("File".to_string(), None)]);
+        assert_eq!(
+            report.updated,
+            vec![TranslationChange {
+                msgid: "File".to_string(),
+                msgctxt: Some("verb".to_string()),
+                old_msgstr: vec!["".to_string()],
+                new_msgstr: vec!["Archivar".to_string()],
+            }]
+        );
+        assert_eq!(
+            report.conflicts,
+            vec![TranslationChange {
+                msgid: "day".to_string(),
+                msgctxt: None,
+                old_msgstr: vec![
+                    "día".to_string(),
+                    "días".to_string()
+                ],
+                new_msgstr: vec![
+                    "jornada".to_string(),
+                    "días".to_string()
+                ],
+            }]
+        );
 
-# First comment line
-#. Second comment line
-msgid "foo"
-msgstr "oof"
-"#;
-        assert_eq!(file.to_string(), expected_content);
+        let verb =
+            file.find_by_msgid_msgctxt("File", "verb").unwrap();
+        assert_eq!(verb.msgstr.as_deref(), Some("Archivar"));
+        assert!(verb.fuzzy());
+        let day = file.find_by_msgid("day").unwrap();
+        assert_eq!(day.msgstr_plural[0], "día");
+        assert!(file.find_by_msgid("Old").is_none());
+        assert_eq!(file.entries.len(), 4);
     }
 
     #[test]
-    fn remove() {
-        let mut entry_1 = POEntry::from("msgid 1");
-        entry_1.msgstr = Some("msgstr 1".to_string());
-
-        let mut entry_2 = POEntry::from("msgid 2");
-        entry_2.msgstr = Some("msgstr 2".to_string());
-
-        let mut file = POFile::from(vec![&entry_1, &entry_2]);
-        assert_eq!(file.entries.len(), 2);
+    fn merge_report_own_obsolete_entries() {
+        let mut file = pofile(concat!(
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+            "\n",
+            "#~ msgid \"Save\"\n",
+            "#~ msgstr \"Salvar\"\n",
+        ))
+        .unwrap();
+        let other = pofile(concat!(
+            "msgid \"Save\"\n",
+            "msgstr \"Guardar\"\n",
+        ))
+        .unwrap();
 
-        // remove by entry
-        file.remove(&entry_1);
+        // not reported as a conflict with the obsolete entry
+        let report = file.merge_report(&other);
+        assert_eq!(report.added, vec![("Save".to_string(), None)]);
+        assert!(report.updated.is_empty());
+        assert!(report.conflicts.is_empty());
 
-        assert_eq!(file.entries.len(), 1);
-        assert_eq!(file.entries[0].msgid, "msgid 2");
+        assert_eq!(file.entries.len(), 3);
+        assert!(file.entries[1].obsolete);
+        assert_eq!(file.entries[1].msgstr.as_deref(), Some("Salvar"));
+        assert!(!file.entries[2].obsolete);
+        assert_eq!(
+            file.entries[2].msgstr.as_deref(),
+            Some("Guardar")
+        );
+    }
 
-        file.entries.push(entry_1);
-        assert_eq!(file.entries.len(), 2);
+    #[test]
+    fn merge_in_place() {
+        let mut file = pofile(concat!(
+            "#: old.rs:1\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"Hola\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"Bye\"\n",
+            "msgstr \"Adiós\"\n",
+            "\n",
+            "msgid \"Removed\"\n",
+            "msgstr \"Eliminado\"\n",
+            "\n",
+            "#~ msgid \"Old\"\n",
+            "#~ msgstr \"Viejo\"\n",
+        ))
+        .unwrap();
+        let template = pofile(concat!(
+            "#. A greeting\n",
+            "#: new.rs:2\n",
+            "msgid \"Hello\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"Bye\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"New\"\n",
+            "msgstr \"\"\n",
+        ))
+        .unwrap();
 
-        // remove by msgid
-        file.remove_by_msgid("msgid 2");
-        assert_eq!(file.entries.len(), 1);
-        assert_eq!(file.entries[0].msgid, "msgid 1");
+        let stats = file.merge_in_place(&template);
+        assert_eq!(
+            stats,
+            MergeStats {
+                added: 1,
+                updated: 2,
+                obsoleted: 1,
+            }
+        );
 
-        // remove by msgid and msgctxt
-        entry_2.msgctxt = Some("msgctxt 2".to_string());
-        entry_2.msgid = "msgid 1".to_string();
-        file.entries.push(entry_2);
-        assert_eq!(file.entries.len(), 2);
-        file.remove_by_msgid_msgctxt("msgid 1", "msgctxt 2");
+        // the entries of the file are updated, not copies of them
+        let hello = file.find_by_msgid("Hello").unwrap();
+        assert_eq!(hello.comment.as_deref(), Some("A greeting"));
+        assert_eq!(
+            hello.occurrences,
+            vec![("new.rs".to_string(), "2".to_string())]
+        );
+        assert_eq!(hello.msgstr.as_deref(), Some("Hola"));
+        let bye = file.find_by_msgid("Bye").unwrap();
+        assert_eq!(bye.msgstr.as_deref(), Some("Adiós"));
+        assert!(bye.fuzzy());
+        assert!(file.find_by_msgid("Removed").unwrap().obsolete);
+        assert!(file.find_by_msgid("Old").unwrap().obsolete);
+        assert!(!file.find_by_msgid("New").unwrap().obsolete);
 
-        assert_eq!(file.entries.len(), 1);
-        assert_eq!(file.entries[0].msgid, "msgid 1");
+        // merging again doesn't change anything
+        let merged = file.clone();
+        let stats = file.merge_in_place(&template);
         assert_eq!(
-            file.entries[0].msgstr.as_ref().unwrap(),
-            "msgstr 1",
+            stats,
+            MergeStats {
+                added: 0,
+                updated: 3,
+                obsoleted: 0,
+            }
         );
+        assert_eq!(file, merged);
     }
 
     #[test]
-    fn find() {
-        let mut entry_1 = POEntry::new(0);
-        entry_1.msgid = "msgid 1".to_string();
-        entry_1.msgstr = Some("msgstr 1".to_string());
+    fn suggest_from() {
+        let memory = pofile(concat!(
+            "msgid \"Open the file\"\n",
+            "msgstr \"Abrir el archivo\"\n",
+            "\n",
+            "msgid \"Open the files\"\n",
+            "msgstr \"Abrir los archivos\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"%d archivo\"\n",
+            "msgstr[1] \"%d archivos\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "msgid \"Close the file\"\n",
+            "msgstr \"Cerrar el archivo\"\n",
+        ))
+        .unwrap();
+        let mut file = pofile(concat!(
+            "msgid \"Open the files!\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"%d filez\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"\"\n",
+            "msgstr[1] \"\"\n",
+            "\n",
+            "msgid \"Close the files\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"Something else\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "msgid \"Open the file\"\n",
+            "msgstr \"Abre el archivo\"\n",
+        ))
+        .unwrap();
+        assert_eq!(file.percent_translated(), 20.0);
 
-        let mut entry_2 = POEntry::new(3);
-        entry_2.msgid = "msgid 2".to_string();
-        entry_2.msgstr = Some("msgstr 2".to_string());
+        assert_eq!(file.suggest_from(&memory, 0.8), 2);
 
-        let mut file = POFile::from(vec![&entry_1, &entry_2]);
-        assert_eq!(file.entries.len(), 2);
+        // the most similar msgid is used
+        let entry = &file.entries[0];
+        assert_eq!(
+            entry.msgstr.as_deref(),
+            Some("Abrir los archivos")
+        );
+        assert!(entry.fuzzy());
+        assert_eq!(
+            entry.previous_msgid.as_deref(),
+            Some("Open the files")
+        );
 
-        // find by msgid
+        let entry = &file.entries[1];
         assert_eq!(
-            file.find_by_msgid("msgid 2").unwrap().msgid,
-            "msgid 2"
+            entry.msgstr_plural,
+            vec!["%d archivo", "%d archivos"]
         );
+        assert!(entry.fuzzy());
 
-        // find by msgid and msgctxt
-        entry_2.msgctxt = Some("msgctxt 2".to_string());
-        entry_2.msgid = "msgid 1".to_string();
-        file.entries.push(entry_2);
-        assert_eq!(file.entries.len(), 3);
+        // fuzzy entries of the memory are not used
+        assert!(!file.entries[2].fuzzy());
+        assert_eq!(file.entries[2].msgstr.as_deref(), Some(""));
+        // entries below the threshold are not modified
+        assert!(!file.entries[3].fuzzy());
+        // translated entries are not modified
         assert_eq!(
-            file.find_by_msgid_msgctxt("msgid 1", "msgctxt 2")
-                .unwrap()
-                .msgstr
-                .as_ref()
-                .unwrap(),
-            "msgstr 2",
+            file.entries[4].msgstr.as_deref(),
+            Some("Abre el archivo")
         );
+        // fuzzy entries are not translated
+        assert_eq!(file.percent_translated(), 20.0);
 
-        // find by msgid_plural, msgctxt, msgid...
-        let mut entry_3 = POEntry::new(6);
-        entry_3.msgid = "msgid for msgid_plural 1".to_string();
-        entry_3.msgid_plural = Some("msgid_plural 1".to_string());
-        entry_3.msgctxt =
-            Some("msgctxt for msgid_plural 1".to_string());
-        file.entries.push(entry_3);
+        // suggested entries are skipped when suggesting again
+        assert_eq!(file.suggest_from(&memory, 0.8), 0);
+    }
 
-        let mut entry_4 = POEntry::new(6);
-        entry_4.msgid = "msgid for msgid_plural 1".to_string();
-        entry_4.msgid_plural = Some("msgid_plural 1".to_string());
-        entry_4.msgctxt = Some("other_msgctxt".to_string());
-        file.entries.push(entry_4);
+    #[test]
+    fn contributors() {
+        let content = r#"# Spanish translation of foo.
+# Translators:
+# John Doe <john@example.com>, 2020
+# Jane, 2021, 2022.
+#
+# Other comment
+msgid ""
+msgstr ""
+"Last-Translator: Foo Bar <foobar@example.com>\n"
+"Language-Team: Spanish\n"
+"#;
+        let file = pofile(content).unwrap();
 
-        let entries = file.find(
-            "msgid for msgid_plural 1",
-            "msgid",
-            None,
-            false,
+        assert_eq!(
+            file.last_translator(),
+            Some(Contributor {
+                name: "Foo Bar".to_string(),
+                email: Some("foobar@example.com".to_string()),
+            }),
         );
-        assert_eq!(entries.len(), 2);
-
-        let entries = file.find(
-            "msgid for msgid_plural 1",
-            "msgid",
-            Some("msgctxt for msgid_plural 1"),
-            false,
+        assert_eq!(
+            file.language_team(),
+            Some(Contributor {
+                name: "Spanish".to_string(),
+                email: None,
+            }),
         );
-        assert_eq!(entries.len(), 1);
+
+        let translators = file.translators();
+        assert_eq!(translators.len(), 2);
+        assert_eq!(translators[0].name, "John Doe");
         assert_eq!(
-            entries[0].msgctxt.as_ref().unwrap(),
-            "msgctxt for msgid_plural 1"
+            translators[0].email.as_ref().unwrap(),
+            "john@example.com"
         );
+        assert_eq!(translators[1].name, "Jane");
+        assert!(translators[1].email.is_none());
+
+        let file = pofile("tests-data/empty-metadata.po").unwrap();
+        assert!(file.last_translator().is_none());
+        assert!(file.translators().is_empty());
     }
 
+    #[cfg(feature = "csv")]
     #[test]
-    fn parse_escapes_are_unescaped_on_format() {
-        let path = "tests-data/escapes.po";
-        let file = pofile(path).unwrap();
+    fn csv_round_trip() {
+        let file = pofile("tests-data/all.po").unwrap();
+        let template = file.as_pot();
+        assert_eq!(template.percent_translated(), 0.0);
+        let csv = file.to_csv();
 
-        let expected_content =
-            "\\ \t \r \u{8} \n \\\n \u{11} \u{12} \\\\";
+        let translated = POFile::from_csv(&csv, &template).unwrap();
+        let non_obsolete = file
+            .entries
+            .iter()
+            .filter(|entry| !entry.obsolete)
+            .collect::<Vec<_>>();
+        assert_eq!(translated.entries.len(), non_obsolete.len());
+        for (entry, expected) in
+            translated.entries.iter().zip(non_obsolete)
+        {
+            assert_eq!(entry.msgid, expected.msgid);
+            assert_eq!(entry.msgctxt, expected.msgctxt);
+            assert_eq!(
+                entry.msgstr.as_deref().unwrap_or(""),
+                expected.msgstr.as_deref().unwrap_or(""),
+            );
+            assert_eq!(entry.msgstr_plural, expected.msgstr_plural);
+            // the rest of fields are taken from the template
+            assert_eq!(entry.occurrences, expected.occurrences);
+            assert_eq!(entry.comment, expected.comment);
+        }
+        // the cached translated count of the template is not reused
+        assert!(translated.percent_translated() > 0.0);
+    }
 
-        assert_eq!(file.entries.len(), 1);
-        assert_eq!(file.entries[0].msgid, expected_content);
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_quoting_and_errors() {
+        let mut template = POFile::new_pot(FileOptions::default());
+        let mut entry = POEntry::from("Say \"hi\", please\nor not");
+        entry.msgctxt = Some("greeting".to_string());
+        entry.occurrences.push(("src/main.rs".into(), "3".into()));
+        template.entries.push(entry);
+        template.entries.push(POEntry::from("Bye"));
+
+        let csv = concat!(
+            "msgid,msgctxt,msgstr\n",
+            "\"Say \"\"hi\"\", please\nor not\",greeting,\"Di \"\"hola\"\"\"\n",
+            "Unknown,,Desconocido\n",
+            "Bye,,Adiós\n",
+        );
+        let file = POFile::from_csv(csv, &template).unwrap();
+        assert_eq!(file.entries.len(), 2);
         assert_eq!(
-            file.entries[0].msgstr.as_ref().unwrap(),
-            expected_content,
+            file.entries[0].msgstr.as_deref(),
+            Some("Di \"hola\"")
         );
+        assert_eq!(file.entries[0].occurrences.len(), 1);
+        assert_eq!(file.entries[1].msgstr.as_deref(), Some("Adiós"));
+
+        let mut translated = template.clone();
+        translated.entries[0].msgstr =
+            Some("Di \"hola\"".to_string());
+        assert!(translated.to_csv().contains(
+            "greeting,\"Say \"\"hi\"\", please\nor not\",\"Di \"\"hola\"\"\""
+        ));
+
+        let err = POFile::from_csv("msgctxt,msgstr\n", &template)
+            .unwrap_err();
+        assert!(err.to_string().contains("msgid column not found"));
+        let err = POFile::from_csv("msgid,msgstr\nfoo\n", &template)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid CSV"));
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn parse_and_format_escapes() {
-        let path = "tests-data/escapes.po";
-        let out_path = "tests-data/tests/parse_and_format_escapes.po";
+    fn json_round_trip() {
+        for path in [
+            "tests-data/all.po",
+            "tests-data/fuzzy-header.po",
+            "tests-data/obsoletes.po",
+            "tests-data/flags.po",
+        ] {
+            let file = pofile(path).unwrap();
+            let json = file.to_json();
 
-        let file = pofile(path).unwrap();
-        file.save(out_path);
+            let value: serde_json::Value =
+                serde_json::from_str(&json).unwrap();
+            assert!(value["entries"].is_array());
+            assert!(value["metadata"].is_object());
 
-        let escapes_content = fs::read_to_string(path).unwrap();
-        let out_content = fs::read_to_string(out_path).unwrap();
+            let file_from_json = POFile::from_json(&json).unwrap();
+            // options are not included
+            let mut expected = file.clone();
+            expected.options = FileOptions::default();
+            assert_eq!(file_from_json, expected);
+            assert_eq!(file_from_json.to_string(), file.to_string());
+        }
 
-        assert_eq!(
-            escapes_content.replace("\r\n", "\n"),
-            out_content
-        );
+        // JSON without the raw header formats it from the comments
+        let mut value: serde_json::Value = serde_json::from_str(
+            &pofile("tests-data/all.po").unwrap().to_json(),
+        )
+        .unwrap();
+        value.as_object_mut().unwrap().remove("raw_header");
+        let file_from_json =
+            POFile::from_json(&value.to_string()).unwrap();
+        let output = file_from_json.to_string();
+        assert!(output.contains("package.\n#\n# Translators:"));
+
+        let err = POFile::from_json("{").unwrap_err();
+        assert!(err.to_string().contains("invalid JSON"));
     }
 }
@@ -19,7 +19,7 @@
 //!     println!("{}", entry.msgid);
 //! }
 //!
-//! po.save("./file.po");
+//! po.save("./file.po").unwrap();
 //! ```
 //!
 //! See the documentation at [docs.rs/rspolib](https://docs.rs/rspolib)
@@ -44,7 +44,7 @@
 //! for entry in file.translated_entries() {
 //!     println!("{}", &entry.msgid);
 //! }
-//! file.save("tests-data/docs/pofile_save.po");
+//! file.save("tests-data/docs/pofile_save.po").unwrap();
 //! ```
 //!
 //! ### Read and save a MO file
@@ -58,7 +58,7 @@
 //!     // All entries are translated in MO files
 //!     println!("{}", entry.msgid);
 //! }
-//! file.save("tests-data/docs/mofile_save.mo");
+//! file.save("tests-data/docs/mofile_save.mo").unwrap();
 //! ```
 //!
 //! ## Features
@@ -67,6 +67,12 @@
 //! * Correct handling of empty and non existent PO fields values.
 //! * Detailed error handling parsing PO and MO files.
 //! * Custom byte order MO files generation.
+//! * Serialization of entries with [serde](https://serde.rs),
+//!   enabling the `serde` feature.
+//! * Export and import of translations as CSV, enabling the
+//!   `csv` feature.
+//! * Saving files normalized to NFC Unicode, enabling the
+//!   `encoding` feature.
 //!
 //! ## General view
 //!
@@ -85,26 +91,43 @@
 //!
 //! [polib]: https://github.com/izimobil/polib
 
+mod charset;
 mod entry;
 pub mod errors;
 #[doc(hidden)]
 pub mod escaping;
 mod file;
+mod mojibake;
 mod moparser;
+mod plurals;
 mod poparser;
 pub mod prelude;
+mod similarity;
 mod traits;
 mod twrapper;
+mod validation;
+mod xliff;
 
 pub use crate::entry::{
     mo_metadata_entry_to_string, po_metadata_entry_to_string,
-    EntryCmpByOptions, MOEntry, MsgidEotMsgctxt, POEntry,
+    EntryCmpByOptions, MOEntry, MOEntryKey, MsgidEotMsgctxt,
+    MultilineStyle, OccurrencesStyle, POEntry,
     Translated as TranslatedEntry,
 };
 pub use crate::file::{
     mofile::{mofile, MOFile},
-    pofile::{pofile, POFile},
-    AsBytes, FileOptions, Save, SaveAsMOFile, SaveAsPOFile,
+    pofile::{
+        pofile, pofile_lenient, ByField, Contributor, HeaderInit,
+        MergeOptions, MergeReport, MergeStats, POFile, Statistics,
+        TranslationChange, WordCount,
+    },
+    AsBytes, FileOptions, FileOptionsBuilder, Save, SaveAsMOFile,
+    SaveAsPOFile,
 };
-pub use crate::moparser::{MAGIC, MAGIC_SWAPPED};
-pub use crate::traits::Merge;
+pub use crate::moparser::{
+    Endianness, MoRevision, MAGIC, MAGIC_SWAPPED,
+};
+pub use crate::plurals::{parse_plural_forms, PluralForms};
+pub use crate::similarity::levenshtein_ratio;
+pub use crate::traits::{IntoPOEntry, Merge, SeekRead};
+pub use crate::validation::{ValidationCategory, ValidationWarning};
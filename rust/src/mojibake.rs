@@ -0,0 +1,87 @@
+//! Heuristics to detect and repair mojibake
+//!
+//! Mojibake is the garbled text produced when an UTF-8 encoded
+//! content is decoded using a single byte encoding like Latin-1
+//! or Windows-1252 and encoded again as UTF-8, like `Ã©` for `é`.
+
+/// Characters of the Windows-1252 encoding that are not
+/// mapped to the same code point in Latin-1
+const WINDOWS_1252: [(char, u8); 27] = [
+    ('\u{20ac}', 0x80),
+    ('\u{201a}', 0x82),
+    ('\u{0192}', 0x83),
+    ('\u{201e}', 0x84),
+    ('\u{2026}', 0x85),
+    ('\u{2020}', 0x86),
+    ('\u{2021}', 0x87),
+    ('\u{02c6}', 0x88),
+    ('\u{2030}', 0x89),
+    ('\u{0160}', 0x8a),
+    ('\u{2039}', 0x8b),
+    ('\u{0152}', 0x8c),
+    ('\u{017d}', 0x8e),
+    ('\u{2018}', 0x91),
+    ('\u{2019}', 0x92),
+    ('\u{201c}', 0x93),
+    ('\u{201d}', 0x94),
+    ('\u{2022}', 0x95),
+    ('\u{2013}', 0x96),
+    ('\u{2014}', 0x97),
+    ('\u{02dc}', 0x98),
+    ('\u{2122}', 0x99),
+    ('\u{0161}', 0x9a),
+    ('\u{203a}', 0x9b),
+    ('\u{0153}', 0x9c),
+    ('\u{017e}', 0x9e),
+    ('\u{0178}', 0x9f),
+];
+
+fn char_to_single_byte(c: char) -> Option<u8> {
+    if (c as u32) < 0x100 {
+        return Some(c as u8);
+    }
+    WINDOWS_1252
+        .iter()
+        .find(|(ch, _)| *ch == c)
+        .map(|(_, byte)| *byte)
+}
+
+/// Returns the repaired text if the given one looks like
+/// UTF-8 content that has been encoded twice
+///
+/// Each character is converted back to the byte that it
+/// represents in Latin-1 or Windows-1252 and the result is
+/// decoded as UTF-8. If some character can't be converted
+/// or the bytes are not valid UTF-8, the text is considered
+/// correct and `None` is returned.
+pub(crate) fn repair(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let bytes = text
+        .chars()
+        .map(char_to_single_byte)
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_double_encoded() {
+        assert_eq!(repair("CafÃ©"), Some("Café".to_string()));
+        assert_eq!(
+            repair("Donâ€™t"),
+            Some("Don\u{2019}t".to_string())
+        );
+    }
+
+    #[test]
+    fn no_repair_needed() {
+        assert_eq!(repair("Cafe"), None);
+        assert_eq!(repair("Café"), None);
+        assert_eq!(repair("日本語"), None);
+    }
+}
@@ -1,8 +1,11 @@
 use std::borrow::Cow;
 use std::fs::File;
-use std::io::{Cursor, SeekFrom};
+use std::io::{BufReader, Cursor, SeekFrom};
 use std::path::Path;
 
+use encoding_rs::UTF_8;
+
+use crate::charset;
 use crate::entry::MOEntry;
 use crate::errors::IOError;
 use crate::file::{mofile::MOFile, FileOptions};
@@ -26,6 +29,83 @@ pub const MAGIC: u32 = 0x950412de;
 /// Value as decimal: `3725722773`
 pub const MAGIC_SWAPPED: u32 = 0xde120495;
 
+/// Revision number of MO files
+///
+/// The MO files specification only defines the revisions
+/// 0 and 1, so any other number is rejected when parsing.
+///
+/// ```rust
+/// use rspolib::{errors::IOError, MoRevision};
+///
+/// assert_eq!(MoRevision::try_from(1), Ok(MoRevision::Rev1));
+/// assert_eq!(
+///     MoRevision::try_from(2),
+///     Err(IOError::UnsupportedMORevisionNumber { version: 2 }),
+/// );
+/// assert_eq!(u32::from(MoRevision::Rev0), 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MoRevision {
+    /// Revision number 0
+    #[default]
+    Rev0,
+    /// Revision number 1
+    Rev1,
+}
+
+impl TryFrom<u32> for MoRevision {
+    type Error = IOError;
+
+    fn try_from(version: u32) -> Result<Self, Self::Error> {
+        match version {
+            0 => Ok(MoRevision::Rev0),
+            1 => Ok(MoRevision::Rev1),
+            _ => {
+                Err(IOError::UnsupportedMORevisionNumber { version })
+            }
+        }
+    }
+}
+
+impl From<MoRevision> for u32 {
+    fn from(revision: MoRevision) -> Self {
+        match revision {
+            MoRevision::Rev0 => 0,
+            MoRevision::Rev1 => 1,
+        }
+    }
+}
+
+/// Byte order of MO files
+///
+/// Each byte order is identified in the files by a magic
+/// number, [MAGIC] for little endian and [MAGIC_SWAPPED] for
+/// big endian.
+///
+/// ```rust
+/// use rspolib::{Endianness, MAGIC_SWAPPED};
+///
+/// assert_eq!(u32::from(Endianness::Big), MAGIC_SWAPPED);
+/// assert_eq!(Endianness::default(), Endianness::Little);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// Little endian byte order
+    #[default]
+    Little,
+    /// Big endian byte order
+    Big,
+}
+
+impl From<Endianness> for u32 {
+    fn from(endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Little => MAGIC,
+            Endianness::Big => MAGIC_SWAPPED,
+        }
+    }
+}
+
 type MsgsIndex = Vec<(u32, u32)>;
 
 fn maybe_extract_plurals_from_msgid_msgstr<'a>(
@@ -46,15 +126,17 @@ fn maybe_extract_plurals_from_msgid_msgstr<'a>(
     (msgid.into(), Some(msgid_plural.into()), msgstr_plural)
 }
 
+/// Split the key of a translation in `msgid` and `msgctxt`
+///
+/// Contextual keys are stored as `msgctxt` + `EOT` + `msgid`.
 fn maybe_extract_msgctxt_from_msgid(
     msgid: &str,
 ) -> (Cow<'_, str>, Option<String>) {
-    let msgid_tokens = msgid.split('\x04').collect::<Vec<&str>>();
-
-    if msgid_tokens.len() == 2 {
-        (msgid_tokens[0].into(), Some(msgid_tokens[1].to_string()))
-    } else {
-        (msgid.into(), None)
+    match msgid.split_once('\x04') {
+        Some((msgctxt, msgid)) => {
+            (msgid.into(), Some(msgctxt.to_string()))
+        }
+        None => (msgid.into(), None),
     }
 }
 
@@ -70,24 +152,30 @@ pub(crate) struct MOFileParser<'a> {
 
 impl MOFileParser<'_> {
     pub fn new<'a>(file_options: FileOptions) -> MOFileParser<'a> {
-        let mut file = MOFile::new(file_options);
         let fhandle: Box<dyn SeekRead> = match Path::new(
-            &file.options.path_or_content,
+            &file_options.path_or_content,
         )
         .is_file()
         {
-            true => Box::new(
-                File::open(&file.options.path_or_content).unwrap(),
-            ),
+            true => Box::new(BufReader::new(
+                File::open(&file_options.path_or_content).unwrap(),
+            )),
             false => Box::new(Cursor::new(
-                file.options.byte_content.as_mut().unwrap().clone(),
+                file_options.byte_content.clone().unwrap(),
             )),
         };
+        Self::with_handle(file_options, fhandle)
+    }
 
+    /// Creates a parser that reads the content from the given handle
+    pub fn with_handle<'a>(
+        file_options: FileOptions,
+        fhandle: Box<dyn SeekRead + 'a>,
+    ) -> MOFileParser<'a> {
         MOFileParser {
             fhandle,
             freader: &u32::from_le_bytes,
-            file,
+            file: MOFile::new(file_options),
         }
     }
 
@@ -147,21 +235,13 @@ impl MOFileParser<'_> {
         }
     }
 
-    fn parse_revision_number(&mut self) -> Result<u32, IOError> {
+    fn parse_revision_number(
+        &mut self,
+    ) -> Result<MoRevision, IOError> {
         match self.parse_4_bytes() {
-            Ok(version) => {
-                // from MO file format specs: "A program seeing an unexpected major
-                // revision number should stop reading the MO file entirely"
-                let available_versions: [u32; 2] = [0, 1];
-                if !available_versions.contains(&version) {
-                    return Err(
-                        IOError::UnsupportedMORevisionNumber {
-                            version,
-                        },
-                    );
-                }
-                Ok(version)
-            }
+            // from MO file format specs: "A program seeing an unexpected major
+            // revision number should stop reading the MO file entirely"
+            Ok(version) => MoRevision::try_from(version),
             Err(_e) => Err(IOError::CorruptedMOData {
                 context: "parsing revision number".to_string(),
             }),
@@ -265,6 +345,7 @@ impl MOFileParser<'_> {
         msgids_index: Vec<(u32, u32)>,
         msgstrs_index: Vec<(u32, u32)>,
     ) {
+        let mut encoding = UTF_8;
         for i in 0..number_of_strings {
             let (msgid_length, msgid_offset) =
                 msgids_index[i as usize];
@@ -276,17 +357,26 @@ impl MOFileParser<'_> {
                 .ok();
             let mut msgid_buffer = vec![0; msgid_length as usize];
             self.fhandle.read_exact(&mut msgid_buffer).ok();
-            let msgid = String::from_utf8_lossy(&msgid_buffer);
 
             self.fhandle
                 .seek(SeekFrom::Start(msgstr_offset as u64))
                 .ok();
             let mut msgstr_buffer = vec![0; msgstr_length as usize];
             self.fhandle.read_exact(&mut msgstr_buffer).ok();
-            let msgstr = String::from_utf8_lossy(&msgstr_buffer);
 
-            if i == 0 && msgid.is_empty() {
-                // metadata entry
+            if i == 0 && msgid_buffer.is_empty() {
+                // the metadata entry declares the encoding of the rest
+                encoding =
+                    charset::encoding_from_header(&msgstr_buffer);
+                self.file.encoding = encoding.name().to_string();
+            }
+            let msgid = charset::decode(&msgid_buffer, encoding);
+            let msgstr = charset::decode(&msgstr_buffer, encoding);
+
+            if msgid.is_empty() {
+                // metadata entry, usually the first one as the
+                // strings are sorted, but stored in the metadata
+                // wherever it is found
                 for metadata_line in msgstr.split('\n') {
                     let mut tokens = metadata_line.splitn(2, ':');
                     let metadata_key =
@@ -297,6 +387,15 @@ impl MOFileParser<'_> {
                         .trim()
                         .to_string();
                     if !metadata_key.is_empty() {
+                        if !self
+                            .file
+                            .metadata
+                            .contains_key(&metadata_key)
+                        {
+                            self.file
+                                .metadata_order
+                                .push(metadata_key.clone());
+                        }
                         self.file
                             .metadata
                             .insert(metadata_key, metadata_value);
@@ -329,6 +428,7 @@ impl MOFileParser<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{AsBytes, POEntry, POFile};
     use rspolib_testing::{
         create_binary_content, create_corrupted_binary_content,
     };
@@ -526,4 +626,45 @@ mod tests {
             "parsing msgstrs table offset",
         );
     }
+
+    #[test]
+    fn msgctxt_is_split_from_msgid() {
+        let bytes = fs::read("tests-data/all.mo").unwrap();
+        let mut parser = MOFileParser::new(bytes.into());
+        parser.parse().unwrap();
+
+        let entry = parser
+            .file
+            .entries
+            .iter()
+            .find(|e| e.msgctxt.is_some())
+            .unwrap();
+        assert_eq!(entry.msgid, "Jan.");
+        assert_eq!(entry.msgctxt.as_ref().unwrap(), "abbrev. month");
+
+        let po_entries = parser
+            .file
+            .entries
+            .iter()
+            .map(POEntry::from)
+            .collect::<Vec<POEntry>>();
+        let po_file =
+            POFile::from(po_entries.iter().collect::<Vec<_>>());
+        let po_file_as_string = po_file.to_string();
+        assert!(!po_file_as_string.contains('\u{4}'));
+        assert!(po_file_as_string.contains(concat!(
+            "msgctxt \"abbrev. month\"\n",
+            "msgid \"Jan.\"\n",
+            "msgstr \"Ene.\"\n",
+        )));
+
+        // the key is joined again when the file is written
+        let mut parser =
+            MOFileParser::new(parser.file.as_bytes().to_vec().into());
+        parser.parse().unwrap();
+        let entry = parser
+            .file
+            .find_by_msgid_msgctxt("Jan.", "abbrev. month");
+        assert_eq!(entry.unwrap().msgstr.as_ref().unwrap(), "Ene.");
+    }
 }
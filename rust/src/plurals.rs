@@ -0,0 +1,628 @@
+//! Evaluation of the `Plural-Forms` header expressions
+//!
+//! The `plural` field of the header is a C expression of the
+//! variable `n` that returns the index of the plural form to
+//! use for a number, like `(n != 1)` or
+//! `(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2)`.
+
+use crate::errors::SyntaxError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(u64),
+    N,
+    Op(&'static str),
+    LParen,
+    RParen,
+    Question,
+    Colon,
+}
+
+/// Operators sorted so the longest ones are matched first
+const OPERATORS: [&str; 15] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*",
+    "/", "%", "=",
+];
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut rest = expression.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(rest[..end].parse().ok()?));
+            rest = &rest[end..];
+        } else {
+            let (token, len) = match c {
+                'n' => (Token::N, 1),
+                '(' => (Token::LParen, 1),
+                ')' => (Token::RParen, 1),
+                '?' => (Token::Question, 1),
+                ':' => (Token::Colon, 1),
+                _ => {
+                    let op = OPERATORS
+                        .iter()
+                        .find(|op| rest.starts_with(*op))?;
+                    // a single `=` is not a valid operator
+                    if *op == "=" {
+                        return None;
+                    }
+                    (Token::Op(op), op.len())
+                }
+            };
+            tokens.push(token);
+            rest = &rest[len..];
+        }
+        rest = rest.trim_start();
+    }
+    Some(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(u64),
+    N,
+    Not(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// Binary operators by precedence, from lowest to highest
+const PRECEDENCE: [&[&str]; 6] = [
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token) -> Option<()> {
+        (self.next()? == token).then_some(())
+    }
+
+    fn ternary(&mut self) -> Option<Expr> {
+        let condition = self.binary(0)?;
+        if self.peek() != Some(&Token::Question) {
+            return Some(condition);
+        }
+        self.next();
+        let if_true = self.ternary()?;
+        self.expect(Token::Colon)?;
+        let if_false = self.ternary()?;
+        Some(Expr::Ternary(
+            Box::new(condition),
+            Box::new(if_true),
+            Box::new(if_false),
+        ))
+    }
+
+    fn binary(&mut self, level: usize) -> Option<Expr> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if !PRECEDENCE[level].contains(&op) {
+                break;
+            }
+            self.next();
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        match self.next()? {
+            Token::Op("!") => {
+                Some(Expr::Not(Box::new(self.unary()?)))
+            }
+            Token::Number(number) => Some(Expr::Number(number)),
+            Token::N => Some(Expr::N),
+            Token::LParen => {
+                let expr = self.ternary()?;
+                self.expect(Token::RParen)?;
+                Some(expr)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn evaluate(expr: &Expr, n: u64) -> Option<u64> {
+    Some(match expr {
+        Expr::Number(number) => *number,
+        Expr::N => n,
+        Expr::Not(expr) => (evaluate(expr, n)? == 0) as u64,
+        Expr::Ternary(condition, if_true, if_false) => {
+            match evaluate(condition, n)? != 0 {
+                true => evaluate(if_true, n)?,
+                false => evaluate(if_false, n)?,
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            let left = evaluate(left, n)?;
+            // short circuit logical operators like C does
+            match *op {
+                "&&" if left == 0 => return Some(0),
+                "||" if left != 0 => return Some(1),
+                _ => {}
+            }
+            let right = evaluate(right, n)?;
+            match *op {
+                "&&" | "||" => (right != 0) as u64,
+                "==" => (left == right) as u64,
+                "!=" => (left != right) as u64,
+                "<" => (left < right) as u64,
+                "<=" => (left <= right) as u64,
+                ">" => (left > right) as u64,
+                ">=" => (left >= right) as u64,
+                "+" => left.checked_add(right)?,
+                "-" => left.checked_sub(right)?,
+                "*" => left.checked_mul(right)?,
+                "/" => left.checked_div(right)?,
+                "%" => left.checked_rem(right)?,
+                _ => return None,
+            }
+        }
+    })
+}
+
+/// `Plural-Forms` headers of languages by their codes, based
+/// on the plural rules of the Unicode CLDR
+const LANGUAGES_PLURAL_FORMS: [(&[&str], &str); 13] = [
+    (
+        &["id", "ja", "km", "ko", "lo", "ms", "th", "vi", "zh"],
+        "nplurals=1; plural=0;",
+    ),
+    (
+        &[
+            "af", "bg", "ca", "da", "de", "el", "en", "eo", "es", "et",
+            "eu", "fi", "fo", "fy", "gl", "he", "hu", "it", "nb", "nl",
+            "nn", "no", "pt", "sq", "sv", "sw", "tr",
+        ],
+        "nplurals=2; plural=(n != 1);",
+    ),
+    (&["fr", "hy", "pt_BR", "oc"], "nplurals=2; plural=(n > 1);"),
+    (&["is"], "nplurals=2; plural=(n%10 != 1 || n%100 == 11);"),
+    (
+        &["be", "bs", "hr", "ru", "sr", "uk"],
+        concat!(
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : ",
+            "n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+        ),
+    ),
+    (
+        &["cs", "sk"],
+        "nplurals=3; plural=(n==1 ? 0 : n>=2 && n<=4 ? 1 : 2);",
+    ),
+    (
+        &["lt"],
+        concat!(
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : ",
+            "n%10>=2 && (n%100<10 || n%100>=20) ? 1 : 2);",
+        ),
+    ),
+    (
+        &["lv"],
+        "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2);",
+    ),
+    (
+        &["pl"],
+        concat!(
+            "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && ",
+            "(n%100<10 || n%100>=20) ? 1 : 2);",
+        ),
+    ),
+    (
+        &["ro"],
+        concat!(
+            "nplurals=3; plural=(n==1 ? 0 : ",
+            "(n==0 || (n%100 > 0 && n%100 < 20)) ? 1 : 2);",
+        ),
+    ),
+    (
+        &["sl"],
+        concat!(
+            "nplurals=4; plural=(n%100==1 ? 0 : n%100==2 ? 1 : ",
+            "n%100==3 || n%100==4 ? 2 : 3);",
+        ),
+    ),
+    (
+        &["ga"],
+        concat!(
+            "nplurals=5; plural=(n==1 ? 0 : n==2 ? 1 : ",
+            "n<7 ? 2 : n<11 ? 3 : 4);",
+        ),
+    ),
+    (
+        &["ar"],
+        concat!(
+            "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : ",
+            "n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5);",
+        ),
+    ),
+];
+
+/// Returns the `Plural-Forms` header of a language
+///
+/// The language is a code like `ru` or `pt_BR`. If the code
+/// with the territory is not found, the language alone is
+/// looked up, so `es_AR` gets the plural forms of `es`.
+pub(crate) fn language_plural_forms(
+    language: &str,
+) -> Option<&'static str> {
+    let language = language.replace('-', "_");
+    let find = |code: &str| {
+        LANGUAGES_PLURAL_FORMS
+            .iter()
+            .find(|(codes, _)| codes.contains(&code))
+            .map(|(_, plural_forms)| *plural_forms)
+    };
+    find(&language).or_else(|| find(language.split('_').next()?))
+}
+
+/// Returns the raw value of a field of a `Plural-Forms` header
+fn field<'a>(plural_forms: &'a str, name: &str) -> Option<&'a str> {
+    plural_forms.split(';').find_map(|field| {
+        let (key, value) = field.split_once('=')?;
+        (key.trim() == name).then_some(value)
+    })
+}
+
+fn parse_expression(expression: &str) -> Option<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        position: 0,
+    };
+    let expr = parser.ternary()?;
+    if parser.position != parser.tokens.len() {
+        return None;
+    }
+    Some(expr)
+}
+
+/// Returns the value of the `nplurals` field of a
+/// `Plural-Forms` header
+pub(crate) fn nplurals(plural_forms: &str) -> Option<usize> {
+    field(plural_forms, "nplurals")?.trim().parse().ok()
+}
+
+/// Returns the index of the plural form to use for `n`
+///
+/// `plural_forms` is the value of the `Plural-Forms` header,
+/// like `nplurals=2; plural=(n != 1);`. Returns `None` if
+/// the `plural` expression is not found or it can't be
+/// parsed or evaluated.
+pub(crate) fn plural_index(
+    plural_forms: &str,
+    n: u64,
+) -> Option<usize> {
+    PluralExpression::parse(plural_forms)?.index(n)
+}
+
+/// Parsed `plural` expression of a `Plural-Forms` header
+///
+/// Used to evaluate the expression for several numbers
+/// without parsing it each time.
+#[derive(Clone, Debug)]
+pub(crate) struct PluralExpression(Expr);
+
+impl PluralExpression {
+    /// Parses the `plural` field of a `Plural-Forms` header,
+    /// returning `None` if it is not found or it's invalid
+    pub(crate) fn parse(plural_forms: &str) -> Option<Self> {
+        parse_expression(field(plural_forms, "plural")?).map(Self)
+    }
+
+    /// Returns the index of the plural form to use for `n`,
+    /// as [plural_index] does
+    pub(crate) fn index(&self, n: u64) -> Option<usize> {
+        usize::try_from(evaluate(&self.0, n)?).ok()
+    }
+}
+
+/// Numbers for which the `plural` expression is checked
+/// against `nplurals` by [parse_plural_forms]
+const CHECKED_NUMBERS: std::ops::Range<u64> = 0..1000;
+
+/// Parsed `Plural-Forms` header
+///
+/// Created by [parse_plural_forms].
+#[derive(Clone, Debug)]
+pub struct PluralForms {
+    /// Number of plural forms
+    pub nplurals: usize,
+    expression: Expr,
+}
+
+impl PluralForms {
+    /// Returns the index of the plural form to use for `n`
+    ///
+    /// Like gettext does, `0` is returned if the expression
+    /// can't be evaluated for `n` or it returns an index out
+    /// of the range defined by `nplurals`.
+    ///
+    /// ```rust
+    /// use rspolib::parse_plural_forms;
+    ///
+    /// let plural_forms =
+    ///     parse_plural_forms("nplurals=2; plural=(n != 1);").unwrap();
+    /// assert_eq!(plural_forms.eval(1), 0);
+    /// assert_eq!(plural_forms.eval(5), 1);
+    /// ```
+    pub fn eval(&self, n: u64) -> usize {
+        evaluate(&self.expression, n)
+            .and_then(|index| usize::try_from(index).ok())
+            .filter(|index| *index < self.nplurals)
+            .unwrap_or(0)
+    }
+}
+
+/// Parses the value of a `Plural-Forms` header
+///
+/// Both `nplurals` and `plural` fields are required. The
+/// `plural` expression is evaluated for the numbers from 0
+/// to 999 to check that it always returns a valid index
+/// for the number of plural forms defined by `nplurals`.
+///
+/// ```rust
+/// use rspolib::{errors::SyntaxError, parse_plural_forms};
+///
+/// let plural_forms = parse_plural_forms(
+///     "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 ? 1 : 2);",
+/// )
+/// .unwrap();
+/// assert_eq!(plural_forms.nplurals, 3);
+/// assert_eq!(plural_forms.eval(22), 1);
+///
+/// let header = "nplurals=2; plural=(n==1 ? 0 : n==2 ? 1 : 2);";
+/// assert_eq!(
+///     parse_plural_forms(header).unwrap_err(),
+///     SyntaxError::InvalidPluralForms {
+///         header: header.to_string(),
+///         message: concat!(
+///             "the plural expression returns 2 for n=0,",
+///             " but nplurals is 2",
+///         )
+///         .to_string(),
+///     },
+/// );
+/// ```
+pub fn parse_plural_forms(
+    header_value: &str,
+) -> Result<PluralForms, SyntaxError> {
+    let error = |message: String| SyntaxError::InvalidPluralForms {
+        header: header_value.to_string(),
+        message,
+    };
+
+    let nplurals = match field(header_value, "nplurals") {
+        Some(value) => {
+            value.trim().parse::<usize>().map_err(|_| {
+                error(format!(
+                    "invalid nplurals value {:?}",
+                    value.trim()
+                ))
+            })?
+        }
+        None => return Err(error("nplurals not found".to_string())),
+    };
+    if nplurals == 0 {
+        return Err(error(
+            "nplurals must be greater than 0".to_string(),
+        ));
+    }
+
+    let expression = match field(header_value, "plural") {
+        Some(value) => parse_expression(value).ok_or_else(|| {
+            error(format!(
+                "invalid plural expression {:?}",
+                value.trim()
+            ))
+        })?,
+        None => {
+            return Err(error(
+                "plural expression not found".to_string(),
+            ))
+        }
+    };
+
+    for n in CHECKED_NUMBERS {
+        match evaluate(&expression, n) {
+            Some(index) if index < nplurals as u64 => {}
+            Some(index) => {
+                return Err(error(format!(
+                    "the plural expression returns {} for n={}, but nplurals is {}",
+                    index, n, nplurals,
+                )))
+            }
+            None => {
+                return Err(error(format!(
+                    "the plural expression can't be evaluated for n={}",
+                    n,
+                )))
+            }
+        }
+    }
+
+    Ok(PluralForms {
+        nplurals,
+        expression,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn germanic_plurals() {
+        let plural_forms = "nplurals=2; plural=(n != 1);";
+        assert_eq!(plural_index(plural_forms, 0), Some(1));
+        assert_eq!(plural_index(plural_forms, 1), Some(0));
+        assert_eq!(plural_index(plural_forms, 2), Some(1));
+    }
+
+    #[test]
+    fn slavic_plurals() {
+        let plural_forms = concat!(
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : ",
+            "n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+        );
+        for (n, index) in [
+            (1, 0),
+            (2, 1),
+            (4, 1),
+            (5, 2),
+            (11, 2),
+            (21, 0),
+            (22, 1),
+        ] {
+            assert_eq!(plural_index(plural_forms, n), Some(index));
+        }
+    }
+
+    #[test]
+    fn parse_nplurals() {
+        assert_eq!(nplurals("nplurals=2; plural=(n != 1);"), Some(2));
+        assert_eq!(nplurals(" nplurals = 3 ;plural=0"), Some(3));
+        assert_eq!(nplurals("plural=(n != 1);"), None);
+        assert_eq!(nplurals("nplurals=x; plural=0;"), None);
+    }
+
+    #[test]
+    fn single_plural() {
+        assert_eq!(plural_index("nplurals=1; plural=0;", 5), Some(0));
+    }
+
+    #[test]
+    fn not_operator() {
+        let plural_forms = "nplurals=2; plural=!(n == 1);";
+        assert_eq!(plural_index(plural_forms, 1), Some(0));
+        assert_eq!(plural_index(plural_forms, 3), Some(1));
+    }
+
+    #[test]
+    fn languages_plural_forms() {
+        for (_, plural_forms) in LANGUAGES_PLURAL_FORMS {
+            let nplurals = nplurals(plural_forms).unwrap();
+            for n in 0..200 {
+                let index = plural_index(plural_forms, n).unwrap();
+                assert!(index < nplurals, "{}", plural_forms);
+            }
+        }
+
+        assert_eq!(
+            language_plural_forms("es_AR"),
+            Some("nplurals=2; plural=(n != 1);")
+        );
+        assert_eq!(
+            language_plural_forms("pt-BR"),
+            Some("nplurals=2; plural=(n > 1);")
+        );
+        assert_eq!(language_plural_forms("xx"), None);
+    }
+
+    #[test]
+    fn parse_plural_forms_header() {
+        let plural_forms =
+            parse_plural_forms("nplurals=1; plural=0;").unwrap();
+        assert_eq!(plural_forms.nplurals, 1);
+        assert_eq!(plural_forms.eval(7), 0);
+
+        for (_, header) in LANGUAGES_PLURAL_FORMS {
+            let plural_forms = parse_plural_forms(header).unwrap();
+            assert_eq!(Some(plural_forms.nplurals), nplurals(header));
+            for n in 0..200 {
+                assert_eq!(
+                    Some(plural_forms.eval(n)),
+                    plural_index(header, n)
+                );
+            }
+        }
+
+        // out of range indexes and failed evaluations return 0
+        let plural_forms = PluralForms {
+            nplurals: 2,
+            expression: parse_expression("n").unwrap(),
+        };
+        assert_eq!(plural_forms.eval(1), 1);
+        assert_eq!(plural_forms.eval(2), 0);
+    }
+
+    #[test]
+    fn parse_plural_forms_errors() {
+        let message = |header: &str| match parse_plural_forms(header)
+        {
+            Err(SyntaxError::InvalidPluralForms {
+                message, ..
+            }) => message,
+            other => panic!("unexpected result {:?}", other),
+        };
+
+        assert_eq!(message("plural=(n != 1);"), "nplurals not found");
+        assert_eq!(
+            message("nplurals=two; plural=(n != 1);"),
+            "invalid nplurals value \"two\""
+        );
+        assert_eq!(
+            message("nplurals=0; plural=0;"),
+            "nplurals must be greater than 0"
+        );
+        assert_eq!(
+            message("nplurals=2;"),
+            "plural expression not found"
+        );
+        assert_eq!(
+            message("nplurals=2; plural=(n != 1;"),
+            "invalid plural expression \"(n != 1\""
+        );
+        assert_eq!(
+            message("nplurals=2; plural=n % 0;"),
+            "the plural expression can't be evaluated for n=0"
+        );
+        assert_eq!(
+            message("nplurals=1; plural=(n != 1);"),
+            "the plural expression returns 1 for n=0, but nplurals is 1"
+        );
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert_eq!(plural_index("nplurals=2;", 1), None);
+        assert_eq!(
+            plural_index("nplurals=2; plural=(n != 1;", 1),
+            None
+        );
+        assert_eq!(
+            plural_index("nplurals=2; plural=n = 1;", 1),
+            None
+        );
+        assert_eq!(plural_index("nplurals=2; plural=x;", 1), None);
+        assert_eq!(
+            plural_index("nplurals=2; plural=n % 0;", 1),
+            None
+        );
+    }
+}
@@ -1,15 +1,21 @@
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::hash::Hash;
-use std::io::{BufRead, BufReader, Lines, Read};
+use std::io::{BufRead, BufReader, Read};
 use std::iter::Iterator;
 use std::path::Path;
 
+use encoding_rs::{Encoding, UTF_8};
 use lazy_static::lazy_static;
 
+use crate::charset;
 use crate::entry::POEntry;
 use crate::errors::{MaybeFilename, SyntaxError};
-use crate::file::{pofile::POFile, FileOptions};
+use crate::file::{
+    pofile::{POFile, RawHeader},
+    FileOptions,
+};
 
 #[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
 
@@ -64,13 +70,85 @@ type NextSt = St;
 pub type Transitions = HashMap<(Symbol, CurrentSt), (Action, NextSt)>;
 
 struct LinesHandler<'a> {
-    lines: Lines<BufReader<&'a mut dyn Read>>,
+    reader: BufReader<&'a mut dyn Read>,
+    /// Encoding of the content, declared in the header
+    encoding: &'static Encoding,
+    /// Raw lines read in advance to detect the encoding
+    pending: VecDeque<Vec<u8>>,
+    /// Raw lines separated by lone carriage returns, split from
+    /// the last line read
+    cr_lines: VecDeque<Vec<u8>>,
+    /// Error found reading the lines, if any
+    error: Option<std::io::Error>,
 }
 
 impl LinesHandler<'_> {
-    fn new(handler: &mut dyn Read) -> LinesHandler {
+    fn new(handler: &mut dyn Read) -> LinesHandler<'_> {
         LinesHandler {
-            lines: BufReader::new(handler).lines(),
+            reader: BufReader::new(handler),
+            encoding: UTF_8,
+            pending: VecDeque::new(),
+            cr_lines: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Creates a handler that decodes the lines using the
+    /// charset declared in the header of the content
+    ///
+    /// The lines of the first entry, where the header is
+    /// defined, are read in advance to detect the encoding.
+    fn with_detected_encoding(
+        handler: &mut dyn Read,
+    ) -> LinesHandler<'_> {
+        let mut lines_handler = Self::new(handler);
+        let mut msgstr_found = false;
+        while let Some(line) = lines_handler.next_raw_line() {
+            let line_is_empty =
+                line.iter().all(u8::is_ascii_whitespace);
+            msgstr_found |= line.starts_with(b"msgstr");
+            lines_handler.pending.push_back(line);
+            if msgstr_found && line_is_empty {
+                break;
+            }
+        }
+        lines_handler.encoding = charset::encoding_from_header(
+            &lines_handler.pending.make_contiguous().concat(),
+        );
+        lines_handler
+    }
+
+    /// Reads the next line, without its line ending
+    ///
+    /// Lines can end with `\n`, `\r\n` or a lone `\r`.
+    fn next_raw_line(&mut self) -> Option<Vec<u8>> {
+        if let Some(line) = self.cr_lines.pop_front() {
+            return Some(line);
+        }
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with(b"\n") {
+                    line.pop();
+                }
+                if line.ends_with(b"\r") {
+                    line.pop();
+                }
+                if !line.contains(&b'\r') {
+                    return Some(line);
+                }
+                let mut lines = line
+                    .split(|byte| *byte == b'\r')
+                    .map(<[u8]>::to_vec);
+                let first_line = lines.next();
+                self.cr_lines.extend(lines);
+                first_line
+            }
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
         }
     }
 }
@@ -79,18 +157,41 @@ impl Iterator for LinesHandler<'_> {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.lines.next() {
-            Some(Ok(line)) => Some(line),
-            Some(Err(_)) => None,
-            None => None,
+        let line = match self.pending.pop_front() {
+            Some(line) => line,
+            None => self.next_raw_line()?,
+        };
+        if self.encoding == UTF_8 {
+            return match String::from_utf8(line) {
+                Ok(line) => Some(line),
+                Err(_) => {
+                    self.error = Some(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "stream did not contain valid UTF-8",
+                    ));
+                    None
+                }
+            };
         }
+        Some(charset::decode(&line, self.encoding).into_owned())
     }
 }
 
+/// Recovery from a syntax error when parsing leniently
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Recovery {
+    /// There is no error to recover from
+    None,
+    /// Skipping the lines of the entry with the error
+    SkippingEntry,
+    /// Skipping the blank lines before the next entry
+    NextEntry,
+}
+
 /// PO file parser
 pub(crate) struct POFileParser {
     /// Whether the content is a path to a file or the file content
-    content_is_path: bool,
+    pub content_is_path: bool,
     /// Parsed PO file
     pub file: POFile,
     /// Current state
@@ -99,12 +200,32 @@ pub(crate) struct POFileParser {
     current_token: String,
     /// Current line number
     current_line: usize,
+    /// Line number where the last string field started, like
+    /// a `msgid` or `msgstr`
+    string_line: usize,
+    /// Line number of the first line of the last comments block
+    comments_line: usize,
     /// Current entry being constructed
     current_entry: POEntry,
     /// Current msgstr index
     msgstr_index: usize,
     /// Whether the current entry is obsolete
     entry_obsolete: bool,
+    /// Current line as it was written, used to locate errors
+    /// and to keep the header verbatim
+    raw_line: String,
+    /// Lines of the header as they were written
+    raw_header: String,
+    /// Blank lines found after the last line of the header,
+    /// added to it if more header lines follow
+    raw_header_blank_lines: String,
+    /// Errors found parsing leniently, `None` when parsing
+    /// strictly
+    pub errors: Option<Vec<SyntaxError>>,
+    /// Recovery from the last error when parsing leniently
+    recovery: Recovery,
+    /// Whether the previous line was blank
+    previous_line_blank: bool,
 }
 
 impl POFileParser {
@@ -118,24 +239,53 @@ impl POFileParser {
             current_state: St::ST,
             current_token: String::with_capacity(32),
             current_line: 0,
+            string_line: 0,
+            comments_line: 0,
             current_entry: POEntry::new(0),
             msgstr_index: 0,
             entry_obsolete: false,
+            raw_line: String::new(),
+            raw_header: String::new(),
+            raw_header_blank_lines: String::new(),
+            errors: None,
+            recovery: Recovery::None,
+            previous_line_blank: false,
+        }
+    }
+
+    /// Makes the parser collect the syntax errors in
+    /// [POFileParser::errors] instead of stopping at the first
+    ///
+    /// After an error, the lines until the next blank line are
+    /// skipped, so the entry with the error is discarded.
+    pub fn lenient(mut self) -> Self {
+        self.errors = Some(vec![]);
+        self
+    }
+
+    /// Returns the error in strict mode, or stores it in
+    /// lenient mode
+    fn recover(
+        &mut self,
+        err: SyntaxError,
+    ) -> Result<(), SyntaxError> {
+        match self.errors.as_mut() {
+            Some(errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            None => Err(err),
         }
     }
 
     fn add_current_entry(&mut self) -> Result<(), SyntaxError> {
-        let unescaped_entry = self.current_entry.unescaped();
-        if unescaped_entry.is_err() {
-            return Err(SyntaxError::BasicCustom {
-                maybe_filename: MaybeFilename::new(
-                    &self.file.options.path_or_content,
-                    self.content_is_path,
-                ),
-                message: unescaped_entry.err().unwrap().to_string(),
-            });
+        match self.current_entry.unescaped() {
+            Ok(entry) => self.file.entries.push(entry),
+            Err(err) => self.recover(SyntaxError::BasicCustom {
+                maybe_filename: self.maybe_filename(),
+                message: err.to_string(),
+            })?,
         }
-        self.file.entries.push(unescaped_entry.unwrap());
         self.current_entry = POEntry::new(self.current_line);
         self.msgstr_index = 0;
         Ok(())
@@ -153,8 +303,20 @@ impl POFileParser {
         symbol: &Symbol,
     ) -> Result<(), SyntaxError> {
         let next_transition = (*symbol, self.current_state);
-        let (action, next_state) =
-            *TRANSITIONS.get(&next_transition).unwrap();
+        let Some(&(action, next_state)) =
+            TRANSITIONS.get(&next_transition)
+        else {
+            return Err(self.unexpected_symbol_error(*symbol));
+        };
+
+        let is_comment =
+            |state| [St::TC, St::GC, St::OC, St::FL].contains(state);
+        if is_comment(symbol) && !is_comment(&self.current_state) {
+            self.comments_line = self.current_line;
+        } else if action != St::MC && !is_comment(symbol) {
+            // keywords of fields, like `msgid` or `#| msgid`
+            self.string_line = self.current_line;
+        }
 
         (transition_fn_factory(action)?)(self)?;
         if action != St::MC {
@@ -165,9 +327,116 @@ impl POFileParser {
         Ok(())
     }
 
+    /// Returns the error for a line not allowed in the current
+    /// state
+    fn unexpected_symbol_error(&self, symbol: Symbol) -> SyntaxError {
+        if symbol == St::MC
+            && [St::TC, St::GC, St::OC, St::FL]
+                .contains(&self.current_state)
+        {
+            return SyntaxError::Custom {
+                maybe_filename: self.maybe_filename(),
+                line: self.comments_line,
+                column: 1,
+                offset: 0,
+                message: format!(
+                    "comment found inside multiline string starting at line {}",
+                    self.string_line,
+                ),
+            };
+        }
+        self.generic_error(self.line_start())
+    }
+
+    fn maybe_filename(&self) -> MaybeFilename {
+        MaybeFilename::new(
+            &self.file.options.path_or_content,
+            self.content_is_path,
+        )
+    }
+
+    /// Returns the offset of the first non whitespace character
+    /// of the current line
+    fn line_start(&self) -> usize {
+        self.raw_line.len() - self.raw_line.trim_start().len()
+    }
+
+    /// Returns the 1-based column of a byte offset of the
+    /// current line
+    fn column(&self, offset: usize) -> usize {
+        self.raw_line
+            .get(..offset)
+            .map_or(offset, |text| text.chars().count())
+            + 1
+    }
+
+    /// Returns a [SyntaxError::Custom] located at a byte offset
+    /// of the current line
+    fn custom_error(
+        &self,
+        offset: usize,
+        message: String,
+    ) -> SyntaxError {
+        SyntaxError::Custom {
+            maybe_filename: self.maybe_filename(),
+            line: self.current_line,
+            column: self.column(offset),
+            offset,
+            message,
+        }
+    }
+
+    /// Returns a [SyntaxError::Generic] located at a byte offset
+    /// of the current line
+    fn generic_error(&self, offset: usize) -> SyntaxError {
+        SyntaxError::Generic {
+            maybe_filename: self.maybe_filename(),
+            line: self.current_line,
+            column: self.column(offset),
+            offset,
+        }
+    }
+
+    /// Checks that a string of the current line, starting at
+    /// the byte `offset`, is terminated and doesn't contain
+    /// unescaped double quotes
+    fn check_string(
+        &self,
+        text: &str,
+        offset: usize,
+    ) -> Result<(), SyntaxError> {
+        if text.len() < 2 || !text.ends_with('"') {
+            let last_char = text.char_indices().last();
+            return Err(self.custom_error(
+                offset + last_char.map_or(0, |(i, _)| i),
+                format!("unterminated string '{}'", text),
+            ));
+        }
+
+        if let Some(double_quote_i) =
+            find_unescaped_double_quote_index(
+                &text[1..text.len() - 1],
+            )
+        {
+            let offset = offset + 1 + double_quote_i;
+            return Err(SyntaxError::UnescapedDoubleQuoteFound {
+                maybe_filename: self.maybe_filename(),
+                line: self.current_line,
+                column: self.column(offset),
+                offset,
+            });
+        }
+        Ok(())
+    }
+
     pub fn parse(&mut self) -> Result<(), SyntaxError> {
         if self.content_is_path {
             self.parse_file()?;
+        } else if looks_like_path(&self.file.options.path_or_content)
+        {
+            return Err(SyntaxError::FileNotFound {
+                path: self.file.options.path_or_content.clone(),
+            });
         } else {
             self.parse_content()?;
         }
@@ -175,10 +444,16 @@ impl POFileParser {
     }
 
     fn parse_file(&mut self) -> Result<(), SyntaxError> {
-        let mut buf = BufReader::new(
-            File::open(&self.file.options.path_or_content).unwrap(),
-        );
-        let mut handler = LinesHandler::new(&mut buf);
+        let path = &self.file.options.path_or_content;
+        let file = File::open(path).map_err(|err| {
+            SyntaxError::ErrorOpeningFile {
+                path: path.clone(),
+                message: err.to_string(),
+            }
+        })?;
+        let mut buf = BufReader::new(file);
+        let mut handler =
+            LinesHandler::with_detected_encoding(&mut buf);
         self.parse_with_handler(&mut handler)?;
         Ok(())
     }
@@ -191,15 +466,34 @@ impl POFileParser {
         Ok(())
     }
 
+    pub fn parse_reader(
+        &mut self,
+        reader: &mut dyn Read,
+    ) -> Result<(), SyntaxError> {
+        let mut handler =
+            LinesHandler::with_detected_encoding(reader);
+        self.parse_with_handler(&mut handler)?;
+        Ok(())
+    }
+
     fn parse_with_handler(
         &mut self,
         handler: &mut LinesHandler,
     ) -> Result<(), SyntaxError> {
+        self.file.encoding = handler.encoding.name().to_string();
         let first_line = handler.next().unwrap_or("".to_string());
-        self.parse_line(maybe_lstrip_utf8_bom(&first_line))?;
+        self.parse_line_or_recover(maybe_lstrip_utf8_bom(
+            &first_line,
+        ))?;
 
         for line in handler.by_ref() {
-            self.parse_line(&line)?;
+            self.parse_line_or_recover(&line)?;
+        }
+        if let Some(err) = handler.error.take() {
+            self.recover(SyntaxError::BasicCustom {
+                maybe_filename: self.maybe_filename(),
+                message: format!("error reading content: {}", err),
+            })?;
         }
 
         if self.current_entry.msgid.is_empty() {
@@ -208,11 +502,22 @@ impl POFileParser {
                 if !msgstr.is_empty() {
                     self.add_current_entry()?;
                 }
+            } else if self.current_state == St::TC {
+                // Comments after the last entry
+                self.file.trailing_comments =
+                    self.current_entry.tcomment.take();
             }
         } else {
             self.add_current_entry()?;
         }
 
+        if let Some(header) = &self.file.header {
+            self.file.raw_header = RawHeader::new(
+                header,
+                std::mem::take(&mut self.raw_header),
+            );
+        }
+
         let metadata_entry = self.file.find_by_msgid("");
         if let Some(metadata_entry) = metadata_entry {
             // Remove header from entries and store it in metadata hashmap
@@ -233,6 +538,7 @@ impl POFileParser {
                         key.to_string(),
                         value.trim().to_string(),
                     );
+                    self.file.metadata_order.push(key.to_string());
                 } else {
                     let mut new_value =
                         self.file.metadata.remove(key).unwrap();
@@ -258,20 +564,102 @@ impl POFileParser {
         tokens
     }
 
-    fn parse_line(&mut self, line: &str) -> Result<(), SyntaxError> {
+    /// Parses a line, recovering from syntax errors when
+    /// parsing leniently
+    ///
+    /// The entry with the error is discarded and the lines
+    /// until the next blank line are skipped. If the error is
+    /// found after blank lines or in a line that starts an
+    /// entry, the entry parsed before is kept, as it is
+    /// complete.
+    fn parse_line_or_recover(
+        &mut self,
+        line: &str,
+    ) -> Result<(), SyntaxError> {
+        let blank = line.trim().is_empty();
+        match self.recovery {
+            Recovery::SkippingEntry | Recovery::NextEntry
+                if blank =>
+            {
+                self.current_line += 1;
+                self.recovery = Recovery::NextEntry;
+                return Ok(());
+            }
+            Recovery::SkippingEntry => {
+                self.current_line += 1;
+                return Ok(());
+            }
+            Recovery::NextEntry => {
+                self.recovery = Recovery::None;
+                self.current_entry.linenum = self.current_line + 1;
+            }
+            Recovery::None => {}
+        }
+
+        let result = self.parse_line(line);
+        let previous_line_blank =
+            std::mem::replace(&mut self.previous_line_blank, blank);
+        let Err(err) = result else {
+            return Ok(());
+        };
+        self.recover(err)?;
+
+        let starts_entry = matches!(
+            line.split_ascii_whitespace().next(),
+            Some(token) if token.starts_with('#')
+                || token == "msgctxt"
+                || token == "msgid"
+        );
+        if (previous_line_blank || starts_entry)
+            && [St::MS, St::MX].contains(&self.current_state)
+        {
+            self.add_current_entry()?;
+        }
+        self.current_entry = POEntry::new(0);
+        self.current_state = St::GC;
+        self.msgstr_index = 0;
+        self.recovery = Recovery::SkippingEntry;
+        Ok(())
+    }
+
+    fn parse_line(
+        &mut self,
+        raw_line: &str,
+    ) -> Result<(), SyntaxError> {
         self.current_line += 1;
+        self.raw_line.clear();
+        self.raw_line.push_str(raw_line);
 
-        let mut line = line.trim();
+        let mut line = raw_line.trim();
+        // byte offset of `line` in the raw line
+        let mut line_offset = start_in(raw_line, line);
         if line.is_empty() {
+            if self.current_state == St::HE {
+                self.raw_header_blank_lines.push_str(&self.raw_line);
+                self.raw_header_blank_lines.push('\n');
+            }
             return Ok(());
         }
 
         let mut tokens = self.tokens_from_line(line);
         let mut nb_tokens = tokens.len();
-        if nb_tokens == 0 || tokens[0] == "#~|" {
+        let obsolete_previous_line: String;
+        if nb_tokens == 0 || (nb_tokens == 1 && tokens[0] == "#~|") {
             return Ok(());
+        } else if tokens[0] == "#~|" {
+            // previous fields of an obsolete entry, parsed
+            // as the ones of non obsolete entries
+            obsolete_previous_line = format!("#|{}", &line[3..]);
+            line = &obsolete_previous_line;
+            // `#~|` is one byte longer than `#|`
+            line_offset += 1;
+            tokens = self.tokens_from_line(line);
+            nb_tokens = tokens.len();
+            self.entry_obsolete = true;
         } else if nb_tokens > 1 && tokens[0] == "#~" {
-            line = line[3..].trim();
+            let obsolete_line = line[3..].trim();
+            line_offset += start_in(line, obsolete_line);
+            line = obsolete_line;
             tokens = tokens[1..].to_vec();
             nb_tokens -= 1;
             self.entry_obsolete = true
@@ -280,17 +668,11 @@ impl POFileParser {
         }
 
         if nb_tokens > 1 && KEYWORDS.contains_key(&tokens[0]) {
-            line = line[tokens[0].len()..].trim_start();
-
-            maybe_raise_unescaped_double_quote_found_error(
-                line,
-                self.current_line,
-                self.content_is_path,
-                &self.file.options.path_or_content,
-                // +2 taking into account ' "' (space and first
-                // double quote)
-                tokens[0].chars().count() + 2,
-            )?;
+            let string = line[tokens[0].len()..].trim_start();
+            line_offset += start_in(line, string);
+            line = string;
+
+            self.check_string(line, line_offset)?;
             self.current_token = line.to_string();
             let symbol = *KEYWORDS.get(&tokens[0]).unwrap();
             self.process(symbol)?;
@@ -306,13 +688,7 @@ impl POFileParser {
             self.process(&St::OC)?;
         } else if line.starts_with('"') {
             // continuation line
-            maybe_raise_unescaped_double_quote_found_error(
-                line,
-                self.current_line,
-                self.content_is_path,
-                &self.file.options.path_or_content,
-                1,
-            )?;
+            self.check_string(line, line_offset)?;
             self.process(&St::MC)?;
         } else if self.current_token.starts_with("msgstr[") {
             // msgstr plural
@@ -330,21 +706,17 @@ impl POFileParser {
                     self.msgstr_index = index;
                 }
                 Err(_) => {
-                    return Err(SyntaxError::Custom {
-                        maybe_filename: MaybeFilename::new(
-                            &self.file.options.path_or_content,
-                            self.content_is_path,
-                        ),
-                        message: format!(
+                    return Err(self.custom_error(
+                        // after `msgstr[`
+                        line_offset + 7,
+                        format!(
                             concat!(
                                 "Invalid msgstr plural index.",
                                 " Expected digit, found '{}'."
                             ),
                             index,
                         ),
-                        line: self.current_line,
-                        index: 7,
-                    });
+                    ));
                 }
             };
 
@@ -369,49 +741,39 @@ impl POFileParser {
             self.process(&St::GC)?;
         } else if tokens[0] == "#|" {
             if nb_tokens < 2 {
-                return Err(SyntaxError::Custom {
-                    maybe_filename: MaybeFilename::new(
-                        &self.file.options.path_or_content,
-                        self.content_is_path,
-                    ),
-                    line: self.current_line,
-                    index: 2,
-                    message: "empty previous message found"
-                        .to_string(),
-                });
+                return Err(self.custom_error(
+                    // after `#|`
+                    line_offset + 2,
+                    "empty previous message found".to_string(),
+                ));
             }
+            // offset of the text after `#|`
+            let keyword_offset =
+                line_offset + start_in(line, line[2..].trim_start());
 
             // Remove the marker and any whitespace following it
             if tokens[1].starts_with('"') {
                 // Continuation of previous metadata
+                self.current_token =
+                    line[2..].trim_start().to_string();
                 self.process(&St::MC)?;
                 return Ok(());
             }
 
             if nb_tokens == 2 {
-                return Err(SyntaxError::Custom {
-                    message: "invalid continuation line".to_string(),
-                    maybe_filename: MaybeFilename::new(
-                        &self.file.options.path_or_content,
-                        self.content_is_path,
-                    ),
-                    line: self.current_line,
-                    index: 0,
-                });
+                return Err(self.custom_error(
+                    keyword_offset,
+                    "invalid continuation line".to_string(),
+                ));
             }
 
             // "previous translation" comment line
             if !PREV_KEYWORDS.contains_key(&tokens[1]) {
                 // Unknown keyword in previous translation comment
-                return Err(SyntaxError::Custom {
-                    message: format!("unknown keyword {}", tokens[1]),
-                    maybe_filename: MaybeFilename::new(
-                        &self.file.options.path_or_content,
-                        self.content_is_path,
-                    ),
-                    line: self.current_line,
-                    index: 0,
-                });
+                return Err(self.custom_error(
+                    keyword_offset,
+                    format!("unknown keyword {}", tokens[1]),
+                ));
             }
 
             // Remove the keyword and any whitespace
@@ -422,14 +784,7 @@ impl POFileParser {
                 .to_string();
             self.process(PREV_KEYWORDS.get(&tokens[1]).unwrap())?;
         } else {
-            return Err(SyntaxError::Generic {
-                maybe_filename: MaybeFilename::new(
-                    &self.file.options.path_or_content,
-                    self.content_is_path,
-                ),
-                line: self.current_line,
-                index: 0,
-            });
+            return Err(self.generic_error(self.line_start()));
         }
         Ok(())
     }
@@ -448,6 +803,12 @@ fn handle_he(parser: &mut POFileParser) -> Result<(), SyntaxError> {
         newheader.push_str(&parser.current_token[2..]);
     }
     parser.file.header = Some(newheader);
+
+    let blank_lines =
+        std::mem::take(&mut parser.raw_header_blank_lines);
+    parser.raw_header.push_str(&blank_lines);
+    parser.raw_header.push_str(&parser.raw_line);
+    parser.raw_header.push('\n');
     Ok(())
 }
 
@@ -502,40 +863,47 @@ fn handle_gc(parser: &mut POFileParser) -> Result<(), SyntaxError> {
 fn handle_oc(parser: &mut POFileParser) -> Result<(), SyntaxError> {
     parser.maybe_add_current_entry()?;
 
-    for occ in parser.current_token[3..].split_whitespace() {
-        if !occ.is_empty() {
-            let (mut fil, mut line) =
-                occ.split_once(':').unwrap_or((occ, ""));
-            let mut line_isdigit = true;
-            for c in line.chars() {
-                if !c.is_ascii_digit() {
-                    line_isdigit = false;
-                    break;
-                }
-            }
-            if !line_isdigit {
-                fil = occ;
-                line = "";
+    let occurrences = parse_occurrences(&parser.current_token[3..]);
+    parser.current_entry.occurrences.extend(occurrences);
+    Ok(())
+}
+
+/// Parses the occurrences of a `#:` line without its prefix
+///
+/// Inverse of [POEntry::occurrences_line].
+pub(crate) fn parse_occurrences(text: &str) -> Vec<(String, String)> {
+    let mut occurrences = vec![];
+    for occ in text.split_whitespace() {
+        let (mut fil, mut line) =
+            occ.split_once(':').unwrap_or((occ, ""));
+        let mut line_isdigit = true;
+        for c in line.chars() {
+            if !c.is_ascii_digit() {
+                line_isdigit = false;
+                break;
             }
-            parser
-                .current_entry
-                .occurrences
-                .push((fil.to_string(), line.to_string()))
         }
+        if !line_isdigit {
+            fil = occ;
+            line = "";
+        }
+        occurrences.push((fil.to_string(), line.to_string()));
     }
-    Ok(())
+    occurrences
 }
 
 fn handle_fl(parser: &mut POFileParser) -> Result<(), SyntaxError> {
     parser.maybe_add_current_entry()?;
     if parser.current_token.len() > 3 {
-        let current_token_split =
-            parser.current_token[3..].split(',');
-        for substr in current_token_split {
-            parser
-                .current_entry
-                .flags
-                .push(substr.trim().to_string());
+        // flags are separated by commas and can contain spaces,
+        // like `range: 0..10`, so only the surrounding whitespace
+        // is trimmed and empty flags are ignored
+        let flags = parser.current_token[3..]
+            .split(',')
+            .map(str::trim)
+            .filter(|flag| !flag.is_empty());
+        for flag in flags {
+            parser.current_entry.flags.push(flag.to_string());
         }
     }
     Ok(())
@@ -666,18 +1034,10 @@ fn handle_mc(parser: &mut POFileParser) -> Result<(), SyntaxError> {
         parser.current_entry.previous_msgctxt =
             Some(previous_msgctxt.to_string());
     } else {
-        return Err(SyntaxError::Custom {
-            maybe_filename: MaybeFilename::new(
-                &parser.file.options.path_or_content,
-                parser.content_is_path,
-            ),
-            message: format!(
-                "unexpected state {:?}",
-                parser.current_state
-            ),
-            line: parser.current_line,
-            index: 0,
-        });
+        return Err(parser.custom_error(
+            parser.line_start(),
+            format!("unexpected state {:?}", parser.current_state),
+        ));
     }
 
     Ok(())
@@ -708,13 +1068,42 @@ fn transition_fn_factory(
 }
 
 #[inline(always)]
+/// Whether a string that is not the path to an existing file
+/// looks like a path rather than the content of a PO file
+///
+/// Paths are single lines without double quotes, not starting
+/// with `#` as comments do, and containing a path separator,
+/// ending with a `.po` or `.pot` extension or being the path
+/// of a directory.
+fn looks_like_path(value: &str) -> bool {
+    let value = value.trim();
+    if value.is_empty()
+        || value.contains(['\n', '"'])
+        || value.starts_with('#')
+    {
+        return false;
+    }
+    value.contains(['/', '\\'])
+        || Path::new(value).is_dir()
+        || Path::new(value)
+            .extension()
+            .is_some_and(|ext| ext == "po" || ext == "pot")
+}
+
 fn maybe_lstrip_utf8_bom(line: &str) -> &str {
     line.trim_start_matches('\u{feff}')
 }
 
+/// Returns the byte index where `part`, a subslice of `text`,
+/// starts in it
+fn start_in(text: &str, part: &str) -> usize {
+    part.as_ptr() as usize - text.as_ptr() as usize
+}
+
+/// Returns the byte index of the first unescaped double quote
 fn find_unescaped_double_quote_index(line: &str) -> Option<usize> {
     let mut escaped = false;
-    for (i, c) in line.chars().enumerate() {
+    for (i, c) in line.char_indices() {
         if c == '"' && !escaped {
             return Some(i);
         } else if c == '\\' {
@@ -726,45 +1115,6 @@ fn find_unescaped_double_quote_index(line: &str) -> Option<usize> {
     None
 }
 
-fn maybe_raise_unescaped_double_quote_found_error(
-    text: &str,
-    linenum: usize,
-    path_or_content_is_path: bool,
-    path_or_content: &str,
-    index_offset: usize,
-) -> Result<(), SyntaxError> {
-    // Check if last character is not a double quote
-    // to prevent slicing out of bounds
-    let text_chars = text.chars();
-    if text_chars.last().unwrap_or('\0') != '"' {
-        return Err(SyntaxError::Custom {
-            maybe_filename: MaybeFilename::new(
-                path_or_content,
-                path_or_content_is_path,
-            ),
-            line: linenum,
-            index: text.chars().count() - 1,
-            message: format!("unterminated string '{}'", text),
-        });
-    }
-
-    let text_str = text.to_string();
-    let unescaped_double_quote_i = find_unescaped_double_quote_index(
-        &text_str[1..text_str.len() - 1],
-    );
-    if let Some(double_quote_i) = unescaped_double_quote_i {
-        return Err(SyntaxError::UnescapedDoubleQuoteFound {
-            maybe_filename: MaybeFilename::new(
-                path_or_content,
-                path_or_content_is_path,
-            ),
-            line: linenum,
-            index: double_quote_i + 1 + index_offset,
-        });
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -779,7 +1129,7 @@ mod tests {
         let parser = POFileParser::new(path.into());
 
         assert_eq!(parser.file.options.path_or_content, path);
-        assert_eq!(parser.content_is_path, true);
+        assert!(parser.content_is_path);
         assert_eq!(parser.file.options.wrapwidth, 78);
 
         assert_eq!(parser.current_line, 0);
@@ -790,13 +1140,13 @@ mod tests {
         // init from file path and wrapwidth
         let parser = POFileParser::new((path, 30).into());
         assert_eq!(parser.file.options.path_or_content, path);
-        assert_eq!(parser.content_is_path, true);
+        assert!(parser.content_is_path);
         assert_eq!(parser.file.options.wrapwidth, 30);
 
         // init from file content
         let parser = POFileParser::new(content.as_str().into());
         assert_eq!(parser.file.options.path_or_content, content);
-        assert_eq!(parser.content_is_path, false);
+        assert!(!parser.content_is_path);
         assert_eq!(parser.file.options.wrapwidth, 78);
     }
 
@@ -821,6 +1171,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_file_not_found() {
+        for path in [
+            "missing.po",
+            "tests-data/missing.pot",
+            "tests-data/missing",
+            "C:\\missing",
+            // directories are not files
+            "tests-data",
+        ] {
+            let mut parser = POFileParser::new(path.into());
+            assert_eq!(
+                parser.parse(),
+                Err(SyntaxError::FileNotFound {
+                    path: path.to_string()
+                })
+            );
+        }
+
+        // files that can't be opened are reported
+        let path = "tests-data/tests/parse_file_not_opened.po";
+        std::fs::write(path, "").unwrap();
+        let mut parser = POFileParser::new(path.into());
+        std::fs::remove_file(path).unwrap();
+        assert!(matches!(
+            parser.parse(),
+            Err(SyntaxError::ErrorOpeningFile { path: p, .. }) if p == path
+        ));
+
+        // existing files and contents are parsed
+        let mut parser =
+            POFileParser::new("tests-data/all.po".into());
+        assert!(parser.parse().is_ok());
+        for content in
+            ["", "#", "# Some comment.po", "msgid \"a.po\""]
+        {
+            let mut parser = POFileParser::new(content.into());
+            assert!(parser.parse().is_ok(), "{}", content);
+        }
+    }
+
     #[test]
     fn parse_utf8_bom() -> Result<(), SyntaxError> {
         let path = "tests-data/utf8-bom.po";
@@ -915,7 +1306,7 @@ mod tests {
         ]);
         for (key, value) in metadata.iter() {
             assert_eq!(
-                parser.file.metadata.get(&key as &str).unwrap(),
+                parser.file.metadata.get(key as &str).unwrap(),
                 value
             );
         }
@@ -936,11 +1327,11 @@ mod tests {
 
         assert_eq!(first_entry.msgid, "msgid 1");
         assert_eq!(first_entry.msgstr.as_ref().unwrap(), "msgstr 1");
-        assert_eq!(first_entry.obsolete, false);
+        assert!(!first_entry.obsolete);
 
         assert_eq!(second_entry.msgid, "msgid 2");
         assert_eq!(second_entry.msgstr.as_ref().unwrap(), "msgstr 2");
-        assert_eq!(second_entry.obsolete, false);
+        assert!(!second_entry.obsolete);
         Ok(())
     }
 
@@ -1000,7 +1391,7 @@ mod tests {
         );
         assert_eq!(
             first_entry.msgid.len(),
-            po_content.lines().nth(0).unwrap().len()
+            po_content.lines().next().unwrap().len()
                 - "msgid ".len()
                 - 2,
         );
@@ -1059,69 +1450,157 @@ mod tests {
 
         assert_eq!(entry_1.msgid, "msgid 1");
         assert_eq!(entry_1.msgstr.as_ref().unwrap(), "msgstr 1");
-        assert_eq!(entry_1.obsolete, false);
+        assert!(!entry_1.obsolete);
         assert_eq!(entry_1.flags.len(), 2);
         assert_eq!(entry_1.flags, vec!["python-format", "fuzzy"]);
-        assert_eq!(entry_1.fuzzy(), true);
+        assert!(entry_1.fuzzy());
 
         assert_eq!(entry_2.msgid, "msgid 2");
         assert_eq!(entry_2.msgstr.as_ref().unwrap(), "msgstr 2");
-        assert_eq!(entry_2.obsolete, false);
+        assert!(!entry_2.obsolete);
         assert_eq!(entry_2.flags.len(), 1);
         assert_eq!(entry_2.flags[0], "fuzzy");
-        assert_eq!(entry_2.fuzzy(), true);
+        assert!(entry_2.fuzzy());
 
         assert_eq!(entry_3.msgid, "msgid 3");
         assert_eq!(entry_3.msgstr.as_ref().unwrap(), "msgstr 3");
-        assert_eq!(entry_3.obsolete, false);
+        assert!(!entry_3.obsolete);
         assert_eq!(entry_3.flags.len(), 1);
         assert_eq!(entry_3.flags[0], "python-format");
-        assert_eq!(entry_3.fuzzy(), false);
+        assert!(!entry_3.fuzzy());
 
         assert_eq!(entry_4.msgid, "msgid 4");
         assert_eq!(entry_4.msgstr.as_ref().unwrap(), "msgstr 4");
-        assert_eq!(entry_4.obsolete, false);
+        assert!(!entry_4.obsolete);
         assert_eq!(entry_4.flags.len(), 7);
         assert_eq!(
             entry_4.flags,
             vec!["1", "2", "3", "4", "5", "6", "7"]
         );
-        assert_eq!(entry_4.fuzzy(), false);
+        assert!(!entry_4.fuzzy());
 
         assert_eq!(entry_5.msgid, "msgid 5");
         assert_eq!(entry_5.msgstr.as_ref().unwrap(), "msgstr 5");
-        assert_eq!(entry_5.obsolete, false);
+        assert!(!entry_5.obsolete);
         assert_eq!(entry_5.flags.len(), 7);
         assert_eq!(
             entry_5.flags,
             vec!["a", "b", "c", "d", "e", "f", "g"]
         );
-        assert_eq!(entry_5.fuzzy(), false);
+        assert!(!entry_5.fuzzy());
 
         assert_eq!(entry_6.msgid, "msgid 6");
         assert_eq!(entry_6.msgstr.as_ref().unwrap(), "msgstr 6");
-        assert_eq!(entry_6.obsolete, false);
+        assert!(!entry_6.obsolete);
         assert_eq!(entry_6.flags.len(), 0);
-        assert_eq!(entry_6.fuzzy(), false);
+        assert!(!entry_6.fuzzy());
 
         Ok(())
     }
 
     #[test]
-    fn parse_msgid_plural() -> Result<(), SyntaxError> {
-        let path = "tests-data/msgid-plural.po";
+    fn range_flags_round_trip() -> Result<(), SyntaxError> {
+        let path = "tests-data/range-flags.po";
         let mut parser = POFileParser::new(path.into());
         parser.parse()?;
 
-        assert_eq!(parser.file.entries.len(), 2);
+        let entries = &parser.file.entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].flags,
+            vec!["fuzzy", "c-format", "range: 0..10"]
+        );
+        assert!(entries[0].fuzzy());
+        assert_eq!(
+            entries[1].flags,
+            vec!["no-c-format", "range: 1..100", "sh-format"]
+        );
+
+        let output = parser.file.to_string();
+        assert!(output.contains("#, fuzzy, c-format, range: 0..10\n"));
+        assert!(output
+            .contains("#, no-c-format, range: 1..100, sh-format\n"));
+
+        let mut reparser = POFileParser::new(output.as_str().into());
+        reparser.parse()?;
+        for (reparsed, entry) in
+            reparser.file.entries.iter().zip(entries)
+        {
+            assert_eq!(reparsed.flags, entry.flags);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_obsolete_plurals() -> Result<(), SyntaxError> {
+        let path = "tests-data/obsolete-plurals.po";
+        let mut parser = POFileParser::new(path.into());
+        parser.parse()?;
+
+        assert_eq!(parser.file.entries.len(), 3);
+        assert!(!parser.file.entries[0].obsolete);
+
+        let entry = &parser.file.entries[1];
+        assert!(entry.obsolete);
+        assert_eq!(entry.msgctxt.as_deref(), Some("removed"));
+        assert_eq!(entry.msgid, "One directory");
         assert_eq!(
-            parser.file.entries[0].msgid_plural.is_some(),
-            true
+            entry.msgid_plural.as_deref(),
+            Some("%d directories")
         );
         assert_eq!(
-            parser.file.entries[1].msgid_plural.is_some(),
-            true
+            entry.msgstr_plural,
+            vec!["Un directorio", "%d directorios"]
+        );
+
+        // continuation lines of obsolete plurals
+        let entry = &parser.file.entries[2];
+        assert!(entry.obsolete);
+        assert_eq!(
+            entry.msgid_plural.as_deref(),
+            Some(concat!(
+                "%d files were removed from the directory, they can",
+                " be restored from the trash folder",
+            ))
+        );
+        assert!(entry.msgstr_plural[1].ends_with("desde la papelera"));
+
+        // re-serialized byte by byte
+        assert_eq!(
+            parser.file.to_string(),
+            std::fs::read_to_string(path).unwrap()
+        );
+
+        // built in code, written and parsed back
+        let mut entry = POEntry::from("One removed file");
+        entry.msgctxt = Some("trash".to_string());
+        entry.set_plural(
+            "%d removed files",
+            &["Un archivo eliminado", "%d archivos eliminados"],
         );
+        entry.obsolete = true;
+        let mut file = POFile::new(FileOptions::default());
+        file.entries.push(entry.clone());
+
+        let content = file.to_string();
+        let mut parser = POFileParser::new(content.as_str().into());
+        parser.parse()?;
+        assert_eq!(parser.file.entries.len(), 1);
+        assert!(parser.file.entries[0].semantic_eq(&entry));
+        assert_eq!(parser.file.to_string(), content);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_msgid_plural() -> Result<(), SyntaxError> {
+        let path = "tests-data/msgid-plural.po";
+        let mut parser = POFileParser::new(path.into());
+        parser.parse()?;
+
+        assert_eq!(parser.file.entries.len(), 2);
+        assert!(parser.file.entries[0].msgid_plural.is_some());
+        assert!(parser.file.entries[1].msgid_plural.is_some());
 
         let entry_1 = &parser.file.entries[0];
         let entry_2 = &parser.file.entries[1];
@@ -1180,7 +1659,7 @@ mod tests {
             entry_1.msgctxt.as_ref().unwrap(),
             "abbrev. month"
         );
-        assert_eq!(entry_1.fuzzy(), false);
+        assert!(!entry_1.fuzzy());
 
         assert_eq!(entry_2.msgid, "J.");
         assert_eq!(entry_2.msgstr.as_ref().unwrap(), "E.");
@@ -1188,7 +1667,7 @@ mod tests {
             entry_2.msgctxt.as_ref().unwrap(),
             "abbrev. month"
         );
-        assert_eq!(entry_2.fuzzy(), true);
+        assert!(entry_2.fuzzy());
 
         assert_eq!(entry_3.msgid, "To date");
         assert_eq!(
@@ -1196,7 +1675,7 @@ mod tests {
             "Hasta la fecha"
         );
         assert_eq!(entry_3.msgctxt.as_ref().unwrap(), "to date");
-        assert_eq!(entry_3.fuzzy(), false);
+        assert!(!entry_3.fuzzy());
 
         Ok(())
     }
@@ -1221,6 +1700,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_previous_fields() -> Result<(), SyntaxError> {
+        let path = "tests-data/previous-fields.po";
+        let mut parser = POFileParser::new(path.into());
+        parser.parse()?;
+
+        let entry = &parser.file.entries[0];
+        assert_eq!(
+            entry.previous_msgctxt.as_deref(),
+            Some("previous context")
+        );
+        assert_eq!(entry.previous_msgid.as_deref(), Some("One file"));
+        assert_eq!(
+            entry.previous_msgid_plural.as_deref(),
+            Some("%d files")
+        );
+
+        // re-serialized byte by byte
+        assert_eq!(
+            parser.file.to_string(),
+            std::fs::read_to_string(path).unwrap()
+        );
+        Ok(())
+    }
+
     #[test]
     fn parse_empty_occurrences_line() -> Result<(), SyntaxError> {
         let path = "tests-data/empty-occurrences-line.po";
@@ -1370,7 +1874,7 @@ mod tests {
                 .file
                 .entries
                 .iter()
-                .filter(|e| e.flags.len() > 0)
+                .filter(|e| !e.flags.is_empty())
                 .count(),
         );
 
@@ -1397,7 +1901,7 @@ mod tests {
         let metadata_as_entry = parser.file.metadata_as_entry();
         assert_eq!(parser.file.entries.len(), 0);
         assert_eq!(parser.file.header.unwrap().lines().count(), 2);
-        assert_eq!(metadata_as_entry.fuzzy(), true);
+        assert!(metadata_as_entry.fuzzy());
 
         Ok(())
     }
@@ -1427,6 +1931,10 @@ mod tests {
         parser.parse()?;
 
         assert_eq!(parser.file.entries.len(), 2);
+        assert_eq!(
+            parser.file.entries[1].previous_msgid.as_deref(),
+            Some("Bar baz qux")
+        );
         Ok(())
     }
 
@@ -1469,7 +1977,8 @@ mod tests {
             Err(SyntaxError::UnescapedDoubleQuoteFound {
                 maybe_filename: MaybeFilename::new(path, true,),
                 line: 5,
-                index: 11,
+                column: 11,
+                offset: 10,
             })
         );
 
@@ -1484,7 +1993,8 @@ mod tests {
             Err(SyntaxError::UnescapedDoubleQuoteFound {
                 maybe_filename: MaybeFilename::new(path, true,),
                 line: 6,
-                index: 27,
+                column: 27,
+                offset: 26,
             })
         );
     }
@@ -1499,12 +2009,216 @@ mod tests {
 
         let obs_entry = &parser.file.entries[1];
 
-        assert_eq!(obs_entry.obsolete, true);
-        assert!(obs_entry.previous_msgid.is_none());
+        assert!(obs_entry.obsolete);
+        assert_eq!(
+            obs_entry.previous_msgid.as_deref(),
+            Some("Error on %s\n%s")
+        );
         assert!(obs_entry.fuzzy());
         Ok(())
     }
 
+    #[test]
+    fn parse_obsolete_previous_fields() -> Result<(), SyntaxError> {
+        let path = "tests-data/obsolete-previous-fields.po";
+        let mut parser = POFileParser::new(path.into());
+        parser.parse()?;
+
+        assert_eq!(parser.file.entries.len(), 2);
+        assert!(parser.file.entries[0].previous_msgid.is_none());
+
+        let entry = &parser.file.entries[1];
+        assert!(entry.obsolete);
+        assert_eq!(
+            entry.previous_msgctxt.as_deref(),
+            Some("old context")
+        );
+        assert_eq!(
+            entry.previous_msgid.as_deref(),
+            Some(concat!(
+                "A previous message long enough to be wrapped in two",
+                " lines when it is formatted",
+            ))
+        );
+        assert_eq!(entry.msgctxt.as_deref(), Some("context"));
+
+        // re-serialized byte by byte
+        assert_eq!(
+            parser.file.to_string(),
+            std::fs::read_to_string(path).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn previous_fields_round_trip() -> Result<(), SyntaxError> {
+        // previous fields out of the canonical order and
+        // split in continuation lines
+        let content = concat!(
+            "#\n",
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "#| msgid_plural \"\"\n",
+            "#| \"%d old \"\n",
+            "#| \"files\"\n",
+            "#| msgid \"One old file\"\n",
+            "#| msgctxt \"old context\"\n",
+            "msgctxt \"context\"\n",
+            "msgid \"One file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[0] \"Un archivo\"\n",
+            "msgstr[1] \"%d archivos\"\n",
+        );
+        let mut parser = POFileParser::new(content.into());
+        parser.parse()?;
+
+        let entry = &parser.file.entries[0];
+        assert!(entry.fuzzy());
+        assert_eq!(
+            entry.previous_msgctxt.as_deref(),
+            Some("old context")
+        );
+        assert_eq!(
+            entry.previous_msgid.as_deref(),
+            Some("One old file")
+        );
+        assert_eq!(
+            entry.previous_msgid_plural.as_deref(),
+            Some("%d old files")
+        );
+
+        // written in the canonical gettext order
+        let written = parser.file.to_string();
+        assert!(written.contains(concat!(
+            "#, fuzzy\n",
+            "#| msgctxt \"old context\"\n",
+            "#| msgid \"One old file\"\n",
+            "#| msgid_plural \"%d old files\"\n",
+            "msgctxt \"context\"\n",
+        )));
+
+        let mut reparser = POFileParser::new(written.as_str().into());
+        reparser.parse()?;
+        assert_eq!(reparser.file.entries, parser.file.entries);
+        assert_eq!(reparser.file.to_string(), written);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_leniently() {
+        let content = concat!(
+            "#\n",
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Language: es\\n\"\n",
+            "\n",
+            "msgid \"First\"\n",
+            "msgstr \"Primero\"\n",
+            "\n",
+            "msgid \"Second\n",
+            "msgstr \"Segundo\"\n",
+            "\n",
+            "\n",
+            "#. Third\n",
+            "msgid \"Third\"\n",
+            "msgstr \"Tercero\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"%d files\"\n",
+            "msgstr[x] \"%d fichero\"\n",
+            "msgstr[1] \"%d ficheros\"\n",
+            "\n",
+            "#| foo \"Fourth\"\n",
+            "msgid \"Fifth\"\n",
+            "msgstr \"Quinto\"\n",
+            "\n",
+            "msgid \"Sixth\"\n",
+            "msgstr \"Sexto\"\n",
+            "msgid \"Seventh\n",
+        );
+
+        let mut parser = POFileParser::new(content.into()).lenient();
+        parser.parse().unwrap();
+
+        let errors = parser
+            .errors
+            .unwrap()
+            .into_iter()
+            .map(|err| match err {
+                SyntaxError::Custom { line, .. } => line,
+                _ => unreachable!("{err}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![9, 19, 22, 28]);
+
+        let entries = &parser.file.entries;
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.msgid.as_str(), entry.linenum))
+                .collect::<Vec<_>>(),
+            vec![("First", 6), ("Third", 13), ("Sixth", 26)],
+        );
+        assert_eq!(entries[1].comment.as_deref(), Some("Third"));
+        assert_eq!(
+            parser.file.metadata.get("Language").map(String::as_str),
+            Some("es")
+        );
+
+        // the strict parser stops at the first error
+        let err =
+            POFileParser::new(content.into()).parse().unwrap_err();
+        assert!(err.to_string().contains("at line 9"));
+    }
+
+    #[test]
+    fn parse_leniently_keeps_entry_before_error() {
+        let content = concat!(
+            "msgid \"First\"\n",
+            "msgstr \"Primero\"\n",
+            "\n",
+            "msgid \"Second\n",
+            "msgstr \"Segundo\"\n",
+        );
+        let mut parser = POFileParser::new(content.into()).lenient();
+        parser.parse().unwrap();
+
+        assert_eq!(parser.errors.unwrap().len(), 1);
+        assert_eq!(parser.file.entries.len(), 1);
+        assert_eq!(parser.file.entries[0].msgid, "First");
+        assert_eq!(
+            parser.file.entries[0].msgstr.as_deref(),
+            Some("Primero")
+        );
+    }
+
+    #[test]
+    fn parse_trailing_comments() -> Result<(), SyntaxError> {
+        let path = "tests-data/trailing-comments.po";
+        let mut parser = POFileParser::new(path.into());
+        parser.parse()?;
+
+        assert_eq!(parser.file.entries.len(), 2);
+        assert!(parser.file.entries[1].tcomment.is_none());
+        assert_eq!(
+            parser.file.trailing_comments.as_deref(),
+            Some("Comment at the end\nof the file\n\nspanning several lines")
+        );
+
+        // re-serialized byte by byte
+        assert_eq!(
+            parser.file.to_string(),
+            std::fs::read_to_string(path).unwrap()
+        );
+        assert_eq!(
+            parser.file.text_len(),
+            parser.file.to_string().len()
+        );
+        Ok(())
+    }
+
     #[test]
     fn error_when_empty_previous_message_line() {
         let content = concat!(
@@ -1524,7 +2238,8 @@ mod tests {
             Err(SyntaxError::Custom {
                 maybe_filename: MaybeFilename::new(content, false,),
                 line: 5,
-                index: 2,
+                column: 3,
+                offset: 2,
                 message: "empty previous message found".to_string(),
             })
         );
@@ -1548,7 +2263,62 @@ mod tests {
             Err(SyntaxError::Generic {
                 maybe_filename: MaybeFilename::new(content, false,),
                 line: 5,
-                index: 0,
+                column: 1,
+                offset: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn error_when_comment_inside_multiline_string() {
+        let path = "tests-data/comment-inside-multiline-string.po";
+        let mut parser = POFileParser::new(path.into());
+        let result = parser.parse();
+
+        assert_eq!(
+            result,
+            Err(SyntaxError::Custom {
+                maybe_filename: MaybeFilename::new(path, true),
+                line: 8,
+                column: 1,
+                offset: 0,
+                message: concat!(
+                    "comment found inside multiline string",
+                    " starting at line 6",
+                )
+                .to_string(),
+            })
+        );
+
+        // inside a msgstr, after other continuation lines
+        let content = concat!(
+            "msgid \"Hello world\"\n",
+            "msgstr \"\"\n",
+            "\"Hola \"\n",
+            "#. generated comment\n",
+            "#: src/main.rs:1\n",
+            "\"mundo\"\n",
+        );
+        let mut parser = POFileParser::new(content.into());
+        assert_eq!(
+            parser.parse().unwrap_err().to_string(),
+            concat!(
+                "Syntax error found at line 4, column 1: comment",
+                " found inside multiline string starting at line 2",
+            )
+        );
+
+        // other unexpected lines don't panic
+        let content =
+            "msgid \"a\"\nmsgstr \"b\"\nmsgid_plural \"c\"\n";
+        let mut parser = POFileParser::new(content.into());
+        assert_eq!(
+            parser.parse(),
+            Err(SyntaxError::Generic {
+                maybe_filename: MaybeFilename::new(content, false),
+                line: 3,
+                column: 1,
+                offset: 0,
             })
         );
     }
@@ -1570,7 +2340,8 @@ mod tests {
             Err(SyntaxError::Custom {
                 maybe_filename: MaybeFilename::new(content, false,),
                 line: 5,
-                index: 7,
+                column: 8,
+                offset: 7,
                 message: "Invalid msgstr plural index. Expected digit, found 'foo'.".to_string(),
             })
         );
@@ -1588,12 +2359,66 @@ mod tests {
             Err(SyntaxError::Custom {
                 maybe_filename: MaybeFilename::new(path, true),
                 line: 4,
-                index: 0,
+                column: 4,
+                offset: 3,
                 message: "invalid continuation line".to_string(),
             })
         );
     }
 
+    #[test]
+    fn error_column_and_offset() {
+        // the column counts characters and the offset bytes
+        for (line, column, offset) in [
+            ("msgid \"añadir\"x\"", 14, 14),
+            ("  msgid  \"añadir\"x\"", 17, 17),
+            ("#~ msgid \"añadir\"x\"", 17, 17),
+            ("\"añadir\" \"x\"", 8, 8),
+        ] {
+            let content =
+                format!("msgid \"a\"\nmsgstr \"b\"\n{}\n", line);
+            let mut parser =
+                POFileParser::new(content.as_str().into());
+            let error = parser.parse().unwrap_err();
+            assert_eq!(
+                error,
+                SyntaxError::UnescapedDoubleQuoteFound {
+                    maybe_filename: MaybeFilename::new(
+                        &content, false
+                    ),
+                    line: 3,
+                    column,
+                    offset,
+                },
+                "{line}",
+            );
+        }
+
+        let content =
+            "msgid \"a\"\nmsgstr \"b\"\n\n#~| añadir \"x\"\n";
+        let mut parser = POFileParser::new(content.into());
+        assert_eq!(
+            parser.parse().unwrap_err().to_string(),
+            concat!(
+                "Syntax error found at line 4, column 5:",
+                " unknown keyword añadir",
+            ),
+        );
+
+        let content = "msgid \"a\"\nmsgstr \"dí\n";
+        let mut parser = POFileParser::new(content.into());
+        assert_eq!(
+            parser.parse(),
+            Err(SyntaxError::Custom {
+                maybe_filename: MaybeFilename::new(content, false),
+                line: 2,
+                column: 10,
+                offset: 9,
+                message: "unterminated string '\"dí'".to_string(),
+            }),
+        );
+    }
+
     #[test]
     fn error_when_unclosed_string_delimiter() {
         let path = "tests-data/unclosed-string-delimiter.po";
@@ -1604,10 +2429,52 @@ mod tests {
             Err(SyntaxError::Custom {
                 maybe_filename: MaybeFilename::new(path, true),
                 line: 5,
-                index: 12,
+                column: 19,
+                offset: 18,
                 message: "unterminated string '\"Foo bar bazá'"
                     .to_string(),
             })
         )
     }
+
+    #[test]
+    fn parse_windows_and_mac_line_endings() -> Result<(), SyntaxError>
+    {
+        let lines = [
+            "msgid \"Hello\"",
+            "msgstr \"Hola\"",
+            "",
+            "# Translator comment",
+            "#: src/main.rs:1",
+            "msgid \"\"",
+            "\"Carriage return\\r\"",
+            "\"Line\"",
+            "msgstr \"Retorno\\r\"",
+            "",
+        ];
+        for line_ending in ["\r\n", "\r"] {
+            let content = lines.join(line_ending);
+            let mut parser =
+                POFileParser::new(content.as_str().into());
+            parser.parse()?;
+
+            let entries = &parser.file.entries;
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].msgid, "Hello");
+            assert_eq!(entries[0].msgstr.as_deref(), Some("Hola"));
+            // escaped carriage returns are kept
+            assert_eq!(entries[1].msgid, "Carriage return\rLine");
+            assert_eq!(
+                entries[1].msgstr.as_deref(),
+                Some("Retorno\r")
+            );
+            assert_eq!(
+                entries[1].tcomment.as_deref(),
+                Some("Translator comment"),
+            );
+            assert_eq!(entries[1].occurrences[0].1, "1");
+            assert_eq!(entries[1].linenum, 4);
+        }
+        Ok(())
+    }
 }
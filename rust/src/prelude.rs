@@ -9,7 +9,8 @@
 //! - [TranslatedEntry] trait to use the method `translated` on entries.
 //! - [AsBytes] trait to use the methods `as_bytes*` on POFile and MOFile.
 //! - [MsgidEotMsgctxt] trait to use the method `msgid_eot_msgctxt` on entries.
+//! - [IntoPOEntry] trait to convert other types into entries.
 pub use crate::{
-    AsBytes, Merge, MsgidEotMsgctxt, Save, SaveAsMOFile,
+    AsBytes, IntoPOEntry, Merge, MsgidEotMsgctxt, Save, SaveAsMOFile,
     SaveAsPOFile, TranslatedEntry,
 };
@@ -0,0 +1,78 @@
+//! Similarity between strings, used to suggest translations
+//! from translation memories
+
+/// Returns the similarity between two strings as a number
+/// between `0.0` and `1.0`, based on their Levenshtein distance
+///
+/// The ratio is computed as `1 - distance / length`, where
+/// `distance` is the minimum number of single character
+/// insertions, deletions and substitutions needed to convert
+/// one string into the other and `length` is the number of
+/// characters of the longest one. Identical strings, including
+/// two empty strings, have a ratio of `1.0`.
+///
+/// ```rust
+/// use rspolib::levenshtein_ratio;
+///
+/// assert_eq!(levenshtein_ratio("file", "file"), 1.0);
+/// assert_eq!(levenshtein_ratio("Open file", "Open files"), 0.9);
+/// assert_eq!(levenshtein_ratio("abc", "xyz"), 0.0);
+/// ```
+pub fn levenshtein_ratio(a: &str, b: &str) -> f32 {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let length = a.len().max(b.len());
+    if length == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein_distance(&a, &b) as f32 / length as f32
+}
+
+fn levenshtein_distance(a: &[char], b: &[char]) -> usize {
+    // only the previous row of the matrix is needed
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != cb) as usize;
+            current[j + 1] = substitution
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let distance = |a: &str, b: &str| {
+            let a = a.chars().collect::<Vec<char>>();
+            let b = b.chars().collect::<Vec<char>>();
+            levenshtein_distance(&a, &b)
+        };
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("abc", ""), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("flaw", "lawn"), 2);
+        // characters, not bytes
+        assert_eq!(distance("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn ratio() {
+        assert_eq!(levenshtein_ratio("", ""), 1.0);
+        assert_eq!(levenshtein_ratio("abc", ""), 0.0);
+        assert_eq!(levenshtein_ratio("ab", "ac"), 0.5);
+        assert_eq!(
+            levenshtein_ratio("kitten", "sitting"),
+            levenshtein_ratio("sitting", "kitten"),
+        );
+    }
+}
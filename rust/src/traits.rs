@@ -1,12 +1,58 @@
+use crate::entry::POEntry;
+
 /// Merge entries and files
 pub trait Merge {
     /// Merge a struct with another of the same type
     fn merge(&mut self, other: Self);
 }
 
+/// Conversion of foreign types into [POEntry]s
+///
+/// Implement it for your own types to add them to PO files
+/// with `POFile::extend_from`.
+///
+/// ```rust
+/// use rspolib::{IntoPOEntry, POEntry};
+///
+/// struct Message {
+///     key: String,
+///     translation: String,
+/// }
+///
+/// impl IntoPOEntry for Message {
+///     fn into_poentry(self) -> POEntry {
+///         POEntry::from((self.key.as_str(), self.translation.as_str()))
+///     }
+/// }
+/// ```
+pub trait IntoPOEntry {
+    /// Converts the value into an entry
+    fn into_poentry(self) -> POEntry;
+}
+
+impl IntoPOEntry for POEntry {
+    fn into_poentry(self) -> POEntry {
+        self
+    }
+}
+
+impl IntoPOEntry for &str {
+    fn into_poentry(self) -> POEntry {
+        POEntry::from(self)
+    }
+}
+
+impl IntoPOEntry for (&str, &str) {
+    fn into_poentry(self) -> POEntry {
+        POEntry::from(self)
+    }
+}
+
 use std::io::{Read, Seek};
 
-// Implementation to use `read_` and `seek_` methods
-// for different types of readers
-pub(crate) trait SeekRead: Seek + Read {}
+/// Readers that can also seek, like files or cursors
+///
+/// Implemented for all the types that implement [Read] and
+/// [Seek]. See [MOFile::from_seek_read](crate::MOFile::from_seek_read).
+pub trait SeekRead: Seek + Read {}
 impl<T: Seek + Read> SeekRead for T {}
@@ -7,7 +7,7 @@ use unicode_width::UnicodeWidthChar;
 
 #[allow(clippy::mut_range_bound)]
 fn get_linebreaks(
-    linebreaks: &Vec<(usize, BreakOpportunity)>,
+    linebreaks: &[(usize, BreakOpportunity)],
     text: &str,
     wrapwidth: usize,
 ) -> Vec<usize> {
@@ -55,7 +55,7 @@ fn get_linebreaks(
 /// - `wrapwidth` - Maximum width of a line
 pub(crate) fn wrap(text: &str, wrapwidth: usize) -> Vec<String> {
     let linebreaks = get_linebreaks(
-        &unicode_linebreaks(text).collect(),
+        &unicode_linebreaks(text).collect::<Vec<_>>(),
         text,
         wrapwidth,
     );
@@ -71,6 +71,35 @@ pub(crate) fn wrap(text: &str, wrapwidth: usize) -> Vec<String> {
     ret
 }
 
+/// Wrap a text in lines like [wrap] does, but also breaking
+/// the lines after each escaped newline (`\n`)
+///
+/// - `text` - Escaped text to wrap in lines
+/// - `wrapwidth` - Maximum width of a line
+pub(crate) fn wrap_hard_newlines(
+    text: &str,
+    wrapwidth: usize,
+) -> Vec<String> {
+    let mut ret = vec![];
+    let mut start = 0;
+    let mut chars = text.char_indices();
+    while let Some((_, c)) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        // the escaped character is always consumed, so escaped
+        // backslashes followed by `n` are not newlines
+        if let Some((i, 'n')) = chars.next() {
+            ret.extend(wrap(&text[start..i + 1], wrapwidth));
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        ret.extend(wrap(&text[start..], wrapwidth));
+    }
+    ret
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +147,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hard_newlines() {
+        let text = r"First line\nSecond line, a bit longer\nThird\n";
+        assert_eq!(
+            wrap_hard_newlines(text, 15),
+            vec![
+                r"First line\n",
+                "Second line, ",
+                r"a bit longer\n",
+                r"Third\n",
+            ]
+        );
+
+        // escaped backslashes followed by `n` are not newlines
+        let text = r"C:\\new\\n\nfile";
+        assert_eq!(
+            wrap_hard_newlines(text, 78),
+            vec![r"C:\\new\\n\n", "file"]
+        );
+
+        assert_eq!(
+            wrap_hard_newlines("no newlines", 78),
+            vec!["no newlines"]
+        );
+        assert!(wrap_hard_newlines("", 78).is_empty());
+    }
+
     #[test]
     fn unicode_characters() {
         let text = "123Ááé aabbcc ÁáééÚí aabbcc";
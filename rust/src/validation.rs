@@ -0,0 +1,638 @@
+//! Checks of the entries of PO files used by
+//! `POFile::validate`
+
+use std::fmt;
+
+use crate::entry::POEntry;
+use crate::errors::FormatError;
+
+/// Category of a [ValidationWarning]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationCategory {
+    /// The entry is marked as fuzzy, so its translation is
+    /// not used.
+    Fuzzy,
+    /// Some plural forms of the entry are translated, but
+    /// others are empty.
+    IncompleteTranslation,
+    /// The `printf` or brace placeholders of the translation
+    /// don't match the ones of the message.
+    PlaceholderMismatch,
+    /// The number of plural forms of the entry doesn't match
+    /// the `nplurals` of the `Plural-Forms` header.
+    PluralFormsMismatch,
+}
+
+/// Problem found by `POFile::validate` in an entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationWarning {
+    /// Kind of problem
+    pub category: ValidationCategory,
+    /// Line number of the entry. See [POEntry::linenum].
+    pub linenum: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.linenum, self.message)
+    }
+}
+
+/// Returns the warnings of an entry
+///
+/// `nplurals` is the number of plural forms defined in the
+/// header of the file, if any.
+pub(crate) fn validate_entry(
+    entry: &POEntry,
+    nplurals: Option<usize>,
+) -> Vec<ValidationWarning> {
+    let mut warnings = vec![];
+    let mut warn = |category, message| {
+        warnings.push(ValidationWarning {
+            category,
+            linenum: entry.linenum,
+            message,
+        })
+    };
+
+    if entry.fuzzy() {
+        warn(
+            ValidationCategory::Fuzzy,
+            format!("entry {:?} is fuzzy", entry.msgid),
+        );
+    }
+
+    if entry.is_plural() {
+        if let Some(nplurals) = nplurals {
+            if entry.plural_count() != nplurals {
+                warn(
+                    ValidationCategory::PluralFormsMismatch,
+                    format!(
+                        "entry {:?} has {} plural forms, but nplurals is {}",
+                        entry.msgid,
+                        entry.plural_count(),
+                        nplurals,
+                    ),
+                );
+            }
+        }
+
+        let empty =
+            entry.msgstr_plural.iter().filter(|m| m.is_empty());
+        let n_empty = empty.count();
+        if n_empty > 0 && n_empty < entry.plural_count() {
+            warn(
+                ValidationCategory::IncompleteTranslation,
+                format!(
+                    "entry {:?} has {} untranslated plural forms",
+                    entry.msgid, n_empty,
+                ),
+            );
+        }
+    }
+
+    // entries with format flags are checked using the syntax
+    // of their format, the rest looking for any placeholder
+    if FormatSyntax::from_flags(&entry.flags).is_some() {
+        if let Err(error) = check_format(entry) {
+            warn(
+                ValidationCategory::PlaceholderMismatch,
+                error.to_string(),
+            );
+        }
+    } else if entry.is_plural() {
+        // translations of plural forms don't need to include
+        // the placeholders of both messages, like `%d` in the
+        // singular form of some languages, so only unknown
+        // placeholders are reported
+        let mut known = placeholders(&entry.msgid);
+        known.extend(placeholders(
+            entry.msgid_plural.as_deref().unwrap_or(""),
+        ));
+        for (index, msgstr) in entry.msgstr_plural.iter().enumerate()
+        {
+            let unknown = placeholders(msgstr)
+                .into_iter()
+                .filter(|p| !known.contains(p))
+                .collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                warn(
+                    ValidationCategory::PlaceholderMismatch,
+                    format!(
+                        "msgstr[{}] of entry {:?} has unknown placeholders {}",
+                        index,
+                        entry.msgid,
+                        unknown.join(" "),
+                    ),
+                );
+            }
+        }
+    } else if let Some(msgstr) =
+        entry.msgstr.as_deref().filter(|m| !m.is_empty())
+    {
+        let mut expected = placeholders(&entry.msgid);
+        let mut found = placeholders(msgstr);
+        expected.sort();
+        found.sort();
+        if expected != found {
+            warn(
+                ValidationCategory::PlaceholderMismatch,
+                format!(
+                    "placeholders of entry {:?} don't match: expected [{}], found [{}]",
+                    entry.msgid,
+                    expected.join(" "),
+                    found.join(" "),
+                ),
+            );
+        }
+    }
+
+    warnings
+}
+
+/// Syntax of the format strings of an entry, defined by its
+/// `*-format` flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FormatSyntax {
+    /// `c-format`: `printf` specifiers, like `%s` or `%1$d`
+    C,
+    /// `python-format`: `%` specifiers, like `%s` or
+    /// `%(name)s`
+    Python,
+    /// `python-brace-format`: `str.format` fields, like `{}`
+    /// or `{name}`
+    PythonBrace,
+}
+
+impl FormatSyntax {
+    /// Returns the syntax defined by the flags of an entry
+    ///
+    /// Flags negated with `no-`, like `no-c-format`, are
+    /// ignored.
+    pub(crate) fn from_flags(flags: &[String]) -> Option<Self> {
+        flags.iter().find_map(|flag| match flag.as_str() {
+            "c-format" => Some(Self::C),
+            "python-format" => Some(Self::Python),
+            "python-brace-format" => Some(Self::PythonBrace),
+            _ => None,
+        })
+    }
+
+    /// Returns the flag that defines the syntax
+    pub(crate) fn flag(self) -> &'static str {
+        match self {
+            Self::C => "c-format",
+            Self::Python => "python-format",
+            Self::PythonBrace => "python-brace-format",
+        }
+    }
+
+    /// Guesses the syntax of a message from its placeholders
+    ///
+    /// The guess is conservative, so `None` is returned if
+    /// any `%` is not part of a valid specifier, if there are
+    /// specifiers with a space flag, which are usually plain
+    /// text like `100% sure`, or if `printf` specifiers and
+    /// brace fields are mixed.
+    ///
+    /// Python is chosen when there are mapping keys, like
+    /// `%(name)s`, and C for the rest of `printf` specifiers.
+    pub(crate) fn detect(text: &str) -> Option<Self> {
+        let percents = text.matches('%').count()
+            - 2 * text.matches("%%").count();
+        let braces = scan(text, None, true);
+        if percents == 0 {
+            return match braces.is_empty() {
+                true => None,
+                false => Some(Self::PythonBrace),
+            };
+        }
+        if !braces.is_empty() {
+            return None;
+        }
+
+        let valid = |specifiers: &[&str]| {
+            specifiers.len() == percents
+                && specifiers.iter().all(|s| !s.contains(' '))
+        };
+        let python = Self::Python.specifiers(text);
+        if valid(&python)
+            && python.iter().any(|s| s.starts_with("%("))
+        {
+            return Some(Self::Python);
+        }
+        match valid(&Self::C.specifiers(text)) {
+            true => Some(Self::C),
+            false => None,
+        }
+    }
+
+    /// Returns the format specifiers of a string
+    pub(crate) fn specifiers(self, text: &str) -> Vec<&str> {
+        match self {
+            Self::C => scan(text, Some(PRINTF_C), false),
+            Self::Python => scan(text, Some(PRINTF_PYTHON), false),
+            Self::PythonBrace => scan(text, None, true),
+        }
+    }
+}
+
+/// Options of the `printf` like specifiers of a syntax
+struct Printf {
+    /// Python mapping keys, like `%(name)s`, are allowed
+    mapping_keys: bool,
+    /// Characters of the conversion types
+    conversions: &'static [u8],
+}
+
+const PRINTF_C: &Printf = &Printf {
+    mapping_keys: false,
+    conversions: b"diouxXeEfFgGaAcspn",
+};
+
+const PRINTF_PYTHON: &Printf = &Printf {
+    mapping_keys: true,
+    conversions: b"diouxXeEfFgGcrsa",
+};
+
+/// Syntax used when the format of an entry is unknown, which
+/// accepts the specifiers of all the supported syntaxes
+const PRINTF_ANY: &Printf = &Printf {
+    mapping_keys: true,
+    conversions: b"diouxXeEfFgGaAcspr",
+};
+
+/// Checks that the format specifiers of the translations of
+/// an entry match the ones of its message
+///
+/// See `POEntry::check_format`.
+pub(crate) fn check_format(
+    entry: &POEntry,
+) -> Result<(), FormatError> {
+    let Some(syntax) = FormatSyntax::from_flags(&entry.flags) else {
+        return Ok(());
+    };
+    let msgid_specifiers = syntax.specifiers(&entry.msgid);
+
+    let (required, allowed, msgstrs) = match &entry.msgid_plural {
+        None => (
+            msgid_specifiers.clone(),
+            msgid_specifiers,
+            entry.msgstr.iter().collect::<Vec<_>>(),
+        ),
+        Some(msgid_plural) => {
+            // plural forms used for a single number, like the
+            // singular of most languages, can omit its
+            // specifier, so only the specifiers shared by both
+            // messages are required
+            let plural_specifiers = syntax.specifiers(msgid_plural);
+            let required = msgid_specifiers
+                .iter()
+                .copied()
+                .filter(|s| plural_specifiers.contains(s))
+                .collect::<Vec<_>>();
+            let mut allowed = msgid_specifiers;
+            allowed.extend(plural_specifiers);
+            (required, allowed, entry.msgstr_plural.iter().collect())
+        }
+    };
+
+    for msgstr in msgstrs.into_iter().filter(|m| !m.is_empty()) {
+        let found = syntax.specifiers(msgstr);
+        let missing = difference(&required, &found);
+        let extra = match entry.msgid_plural {
+            None => difference(&found, &allowed),
+            Some(_) => found
+                .iter()
+                .filter(|s| !allowed.contains(s))
+                .map(|s| s.to_string())
+                .collect(),
+        };
+        if !missing.is_empty() || !extra.is_empty() {
+            return Err(FormatError::MismatchedSpecifiers {
+                msgid: entry.msgid.clone(),
+                msgstr: msgstr.clone(),
+                missing,
+                extra,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the items of `a` not found in `b`, taking into
+/// account the number of times that each item appears
+fn difference(a: &[&str], b: &[&str]) -> Vec<String> {
+    let mut b = b.to_vec();
+    a.iter()
+        .filter(|item| match b.iter().position(|i| i == *item) {
+            Some(index) => {
+                b.remove(index);
+                false
+            }
+            None => true,
+        })
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Returns the `printf` (`%s`, `%1$d`, `%(name)s`...) and
+/// brace (`{}`, `{0}`, `{name}`...) placeholders of a string
+///
+/// Escaped `%%`, `{{` and `}}` are not placeholders, and
+/// neither are `printf` specifiers with a space flag, which
+/// are usually plain text like `50% of`. See
+/// [FormatSyntax::detect].
+pub(crate) fn placeholders(text: &str) -> Vec<&str> {
+    scan(text, Some(PRINTF_ANY), true)
+        .into_iter()
+        .filter(|p| !(p.starts_with('%') && p.contains(' ')))
+        .collect()
+}
+
+/// Returns the placeholders of a string, looking for `printf`
+/// specifiers if `printf` is defined and for brace fields if
+/// `braces` is `true`
+fn scan<'a>(
+    text: &'a str,
+    printf: Option<&Printf>,
+    braces: bool,
+) -> Vec<&'a str> {
+    let mut ret = vec![];
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = match (bytes[i], printf) {
+            (b'%', Some(printf)) => {
+                printf_placeholder_end(bytes, i, printf)
+            }
+            (b'{', _) if braces => brace_placeholder_end(bytes, i),
+            (b'}', _)
+                if braces && bytes.get(i + 1) == Some(&b'}') =>
+            {
+                i += 2;
+                continue;
+            }
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                ret.push(&text[i..end]);
+                i = end;
+            }
+            None if bytes[i] == b'%' || bytes[i] == b'{' => {
+                // skip escaped `%%` and `{{` as a whole
+                i += match bytes.get(i + 1) == Some(&bytes[i]) {
+                    true => 2,
+                    false => 1,
+                };
+            }
+            None => i += 1,
+        }
+    }
+    ret
+}
+
+/// Returns the end of the `printf` placeholder starting at
+/// `start`, which must be the index of a `%`
+fn printf_placeholder_end(
+    bytes: &[u8],
+    start: usize,
+    printf: &Printf,
+) -> Option<usize> {
+    let mut i = start + 1;
+    let skip = |i: &mut usize, f: &dyn Fn(u8) -> bool| {
+        while bytes.get(*i).is_some_and(|b| f(*b)) {
+            *i += 1;
+        }
+    };
+
+    // mapping key of Python, like `%(name)s`
+    if printf.mapping_keys && bytes.get(i) == Some(&b'(') {
+        i += bytes[i..].iter().position(|b| *b == b')')? + 1;
+    }
+    // argument position, like `%1$s`
+    let digits_start = i;
+    skip(&mut i, &|b| b.is_ascii_digit());
+    if i == digits_start || bytes.get(i) != Some(&b'$') {
+        i = digits_start;
+    } else {
+        i += 1;
+    }
+    // flags, width, precision and length modifiers
+    skip(&mut i, &|b| b"-+ #0'".contains(&b));
+    skip(&mut i, &|b| b.is_ascii_digit() || b == b'*');
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        skip(&mut i, &|b| b.is_ascii_digit() || b == b'*');
+    }
+    skip(&mut i, &|b| b"hlLqjzt".contains(&b));
+
+    match bytes.get(i) {
+        Some(b) if printf.conversions.contains(b) => Some(i + 1),
+        _ => None,
+    }
+}
+
+/// Returns the end of the brace placeholder starting at
+/// `start`, which must be the index of a `{`
+fn brace_placeholder_end(
+    bytes: &[u8],
+    start: usize,
+) -> Option<usize> {
+    let len = bytes[start + 1..].iter().position(|b| {
+        !(b.is_ascii_alphanumeric() || b"_.:<>^#+-?".contains(b))
+    })?;
+    match bytes[start + 1 + len] {
+        b'}' => Some(start + len + 2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn printf_placeholders() {
+        assert_eq!(
+            placeholders("%s has %d files (%.2f%%)"),
+            vec!["%s", "%d", "%.2f"]
+        );
+        assert_eq!(
+            placeholders("%(name)s and %1$s, %-10lu, %"),
+            vec!["%(name)s", "%1$s", "%-10lu"]
+        );
+        assert!(placeholders("100%% sure").is_empty());
+        assert!(placeholders("50% of").is_empty());
+        assert!(placeholders("50 % de").is_empty());
+    }
+
+    #[test]
+    fn brace_placeholders() {
+        assert_eq!(
+            placeholders("{} of {0}, {name} and {value:>8.2}"),
+            vec!["{}", "{0}", "{name}", "{value:>8.2}"]
+        );
+        assert!(placeholders("{{escaped}} { not closed").is_empty());
+        assert!(placeholders("{with spaces}").is_empty());
+    }
+
+    #[test]
+    fn format_syntaxes() {
+        let text = "%s, %(name)s, %p and {name}";
+        assert_eq!(
+            FormatSyntax::C.specifiers(text),
+            vec!["%s", "%p"]
+        );
+        assert_eq!(
+            FormatSyntax::Python.specifiers(text),
+            vec!["%s", "%(name)s"]
+        );
+        assert_eq!(
+            FormatSyntax::PythonBrace.specifiers(text),
+            vec!["{name}"]
+        );
+
+        let flags =
+            vec!["fuzzy".to_string(), "no-c-format".to_string()];
+        assert_eq!(FormatSyntax::from_flags(&flags), None);
+    }
+
+    #[test]
+    fn detect_syntaxes() {
+        let detect = FormatSyntax::detect;
+        assert_eq!(
+            detect("%s has %1$d files"),
+            Some(FormatSyntax::C)
+        );
+        assert_eq!(detect("%d%% done"), Some(FormatSyntax::C));
+        assert_eq!(
+            detect("%(name)s has %d files"),
+            Some(FormatSyntax::Python)
+        );
+        assert_eq!(
+            detect("{name} has {} files"),
+            Some(FormatSyntax::PythonBrace)
+        );
+
+        for text in [
+            "Hello world",
+            "100% sure",
+            "50%, more or less",
+            "100%%",
+            "%s and {name}",
+            "%r of %s",
+            "{with spaces}",
+        ] {
+            assert_eq!(detect(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn check_format_singular() {
+        let mut entry = POEntry::from(("%s of %s", "%s de %d de %s"));
+        assert!(check_format(&entry).is_ok());
+
+        entry.flags.push("c-format".to_string());
+        assert_eq!(
+            check_format(&entry),
+            Err(FormatError::MismatchedSpecifiers {
+                msgid: "%s of %s".to_string(),
+                msgstr: "%s de %d de %s".to_string(),
+                missing: vec![],
+                extra: vec!["%d".to_string()],
+            })
+        );
+
+        entry.msgstr = Some("%s".to_string());
+        assert_eq!(
+            check_format(&entry),
+            Err(FormatError::MismatchedSpecifiers {
+                msgid: "%s of %s".to_string(),
+                msgstr: "%s".to_string(),
+                missing: vec!["%s".to_string()],
+                extra: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn check_format_plural() {
+        let mut entry = POEntry::from("{name} has one file");
+        entry.flags.push("python-brace-format".to_string());
+        entry.msgid_plural = Some("{name} has {n} files".to_string());
+        entry.msgstr_plural = vec![
+            "{name} tiene un archivo".to_string(),
+            "{name} tiene {n} archivos".to_string(),
+            String::new(),
+        ];
+        assert!(check_format(&entry).is_ok());
+
+        entry.msgstr_plural[2] = "{n} archivos".to_string();
+        assert!(matches!(
+            check_format(&entry),
+            Err(FormatError::MismatchedSpecifiers { missing, .. })
+                if missing == vec!["{name}".to_string()]
+        ));
+
+        entry.msgstr_plural[2] = "{name} {nombre}".to_string();
+        let warnings = validate_entry(&entry, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("{nombre}"));
+    }
+
+    #[test]
+    fn singular_entry() {
+        let mut entry = POEntry::from(("%s files", "%d archivos"));
+        entry.linenum = 7;
+        let warnings = validate_entry(&entry, None);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].category,
+            ValidationCategory::PlaceholderMismatch
+        );
+        assert_eq!(warnings[0].linenum, 7);
+
+        // the order of the placeholders can change
+        let entry = POEntry::from(("%1$s of %2$s", "%2$s de %1$s"));
+        assert!(validate_entry(&entry, None).is_empty());
+
+        // untranslated entries are not checked
+        let entry = POEntry::from(("%s files", ""));
+        assert!(validate_entry(&entry, None).is_empty());
+
+        // literal percents in prose are not placeholders
+        let entry = POEntry::from((
+            "50% of the files",
+            "50 % de los archivos",
+        ));
+        assert!(validate_entry(&entry, None).is_empty());
+    }
+
+    #[test]
+    fn plural_entry() {
+        let mut entry = POEntry::from("One file");
+        entry.msgid_plural = Some("%d files".to_string());
+        entry.msgstr_plural =
+            vec!["Un archivo".to_string(), "%d archivos".to_string()];
+        assert!(validate_entry(&entry, Some(2)).is_empty());
+
+        entry.msgstr_plural.push(String::new());
+        entry.msgstr_plural[0] = "%s archivo".to_string();
+        let categories = validate_entry(&entry, Some(2))
+            .iter()
+            .map(|warning| warning.category)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            categories,
+            vec![
+                ValidationCategory::PluralFormsMismatch,
+                ValidationCategory::IncompleteTranslation,
+                ValidationCategory::PlaceholderMismatch,
+            ]
+        );
+    }
+}
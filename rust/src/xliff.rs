@@ -0,0 +1,158 @@
+//! Conversion of the entries of PO files to XLIFF 1.2 used by
+//! `POFile::to_xliff`
+
+use crate::entry::{POEntry, Translated};
+
+/// Writes an XLIFF 1.2 document with the non obsolete entries
+///
+/// `original` is written as the name of the file translated
+/// by the document.
+pub(crate) fn to_xliff(
+    entries: &[POEntry],
+    original: &str,
+    source_lang: &str,
+    target_lang: &str,
+) -> String {
+    let mut xliff = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<xliff version=\"1.2\" ",
+        "xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+    ));
+    xliff.push_str(&format!(
+        "  <file original=\"{}\" source-language=\"{}\" \
+         target-language=\"{}\" datatype=\"po\">\n    <body>\n",
+        xml_escape(original),
+        xml_escape(source_lang),
+        xml_escape(target_lang),
+    ));
+
+    let entries = entries.iter().filter(|e| !e.obsolete);
+    for (i, entry) in entries.enumerate() {
+        let id = (i + 1).to_string();
+        if !entry.is_plural() {
+            let msgstr = entry.msgstr.as_deref().unwrap_or("");
+            push_trans_unit(
+                &mut xliff,
+                &id,
+                entry,
+                &entry.msgid,
+                msgstr,
+                6,
+            );
+            continue;
+        }
+
+        xliff.push_str(&format!(
+            "      <group id=\"{}\" restype=\"x-gettext-plurals\">\n",
+            id
+        ));
+        for (form, msgstr) in entry.msgstr_plural.iter().enumerate() {
+            let source = match form {
+                0 => &entry.msgid,
+                _ => entry.msgid_plural.as_deref().unwrap_or(""),
+            };
+            push_trans_unit(
+                &mut xliff,
+                &format!("{}[{}]", id, form),
+                entry,
+                source,
+                msgstr,
+                8,
+            );
+        }
+        xliff.push_str("      </group>\n");
+    }
+
+    xliff.push_str("    </body>\n  </file>\n</xliff>\n");
+    xliff
+}
+
+/// Escapes a text to be included in XML content or attributes,
+/// omitting the characters not allowed in XML documents
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Writes a `<trans-unit>` of the document indented
+/// with `indent` spaces
+fn push_trans_unit(
+    xliff: &mut String,
+    id: &str,
+    entry: &POEntry,
+    source: &str,
+    target: &str,
+    indent: usize,
+) {
+    let pad = " ".repeat(indent);
+    let approved = match entry.translated() {
+        true => "yes",
+        false => "no",
+    };
+    xliff.push_str(&format!(
+        "{pad}<trans-unit id=\"{}\" approved=\"{}\">\n",
+        xml_escape(id),
+        approved,
+    ));
+    xliff.push_str(&format!(
+        "{pad}  <source>{}</source>\n",
+        xml_escape(source)
+    ));
+    if !target.is_empty() {
+        let state = match entry.fuzzy() {
+            true => "needs-review-translation",
+            false => "translated",
+        };
+        xliff.push_str(&format!(
+            "{pad}  <target state=\"{}\">{}</target>\n",
+            state,
+            xml_escape(target),
+        ));
+    }
+    if let Some(msgctxt) = &entry.msgctxt {
+        xliff.push_str(&format!(
+            "{pad}  <context-group purpose=\"information\">\
+             <context context-type=\"x-gettext-msgctxt\">{}\
+             </context></context-group>\n",
+            xml_escape(msgctxt),
+        ));
+    }
+    for (comment, from) in [
+        (&entry.comment, "developer"),
+        (&entry.tcomment, "translator"),
+    ] {
+        if let Some(comment) = comment {
+            xliff.push_str(&format!(
+                "{pad}  <note from=\"{}\">{}</note>\n",
+                from,
+                xml_escape(comment),
+            ));
+        }
+    }
+    xliff.push_str(&format!("{pad}</trans-unit>\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            xml_escape("<a href=\"#\">Tom & Jerry</a>"),
+            "&lt;a href=&quot;#&quot;&gt;Tom &amp; Jerry&lt;/a&gt;"
+        );
+        assert_eq!(xml_escape("a\u{4}b\tc\u{fffe}"), "ab\tc");
+    }
+}